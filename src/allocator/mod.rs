@@ -0,0 +1,166 @@
+//! Allocator support for the heap-backed types.
+//!
+//! [`SmartString`](crate::SmartString), [`StrStack`](crate::StrStack) and [`TaggedStrStack`](crate::TaggedStrStack)
+//! take an allocator type parameter `A` that defaults to [`Global`], the global allocator used by `String` and `Vec`.
+//!
+//! With the `allocator-api2` feature, [`Allocator`], [`Global`] and [`AllocError`] are re-exported from the
+//! [allocator-api2](https://crates.io/crates/allocator-api2) crate, so any allocator implementing its `Allocator`
+//! trait (e.g. an arena or a bump allocator) can be plugged in. Without it, they are minimal stand-ins and [`Global`]
+//! is the only allocator available.
+//!
+//! The fallible allocation methods (`try_reserve`, `try_push_str`, ...) return a [`TryReserveError`]: the standard
+//! `alloc::collections::TryReserveError` by default, and allocator-api2's own one with the feature, since the
+//! standard one can't be created by a custom allocator.
+
+#[cfg(not(feature = "allocator-api2"))]
+mod shim;
+mod vec_in;
+
+#[cfg(not(feature = "allocator-api2"))]
+pub use alloc::collections::TryReserveError;
+
+#[cfg(feature = "allocator-api2")]
+pub use allocator_api2::alloc::AllocError;
+#[cfg(feature = "allocator-api2")]
+pub use allocator_api2::alloc::Allocator;
+#[cfg(feature = "allocator-api2")]
+pub use allocator_api2::alloc::Global;
+#[cfg(feature = "allocator-api2")]
+pub use allocator_api2::collections::TryReserveError;
+#[cfg(not(feature = "allocator-api2"))]
+pub use shim::AllocError;
+#[cfg(not(feature = "allocator-api2"))]
+pub use shim::Allocator;
+#[cfg(not(feature = "allocator-api2"))]
+pub use shim::Global;
+pub(crate) use vec_in::from_vec;
+pub(crate) use vec_in::into_vec;
+pub(crate) use vec_in::VecIn;
+
+#[cfg(all(test, feature = "allocator-api2"))]
+mod tests {
+    use core::alloc::Layout;
+    use core::cell::Cell;
+    use core::ptr::NonNull;
+
+    use super::*;
+    use crate::smart_string::NeverDemote;
+    use crate::SmartString;
+    use crate::StrStack;
+    use crate::TaggedStrStack;
+
+    /// Counts the live allocations made through it, leaving out the empty ones (a `Vec` shrunk to nothing holds one
+    /// it never frees).
+    #[derive(Default)]
+    struct Counting {
+        live: Cell<usize>,
+        total: Cell<usize>,
+    }
+
+    // SAFETY: delegates to `Global`.
+    unsafe impl Allocator for Counting {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            if layout.size() != 0 {
+                self.live.set(self.live.get() + 1);
+                self.total.set(self.total.get() + 1);
+            }
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            if layout.size() != 0 {
+                self.live.set(self.live.get() - 1);
+            }
+            Global.deallocate(ptr, layout)
+        }
+    }
+
+    #[test]
+    fn test_smart_string_in() {
+        let alloc = Counting::default();
+        {
+            let mut s = SmartString::<4, NeverDemote, _>::new_in(&alloc);
+            s.push_str("abcd");
+            assert!(s.is_stack());
+            assert_eq!(alloc.total.get(), 0);
+
+            s.push_str("efgh");
+            assert!(s.is_heap());
+            assert_eq!(s, "abcdefgh");
+            assert_eq!(alloc.live.get(), 1);
+
+            let t = s.clone();
+            assert_eq!(t, "abcdefgh");
+            assert_eq!(alloc.live.get(), 2);
+
+            let u = s.split_off(2);
+            assert_eq!((s.as_str(), u.as_str()), ("ab", "cdefgh"));
+            assert_eq!(alloc.live.get(), 3);
+
+            s.clear();
+            s.shrink_to_fit();
+            assert!(s.is_heap());
+        }
+        assert_eq!(alloc.live.get(), 0);
+    }
+
+    #[test]
+    fn test_smart_string_in_demote() {
+        use crate::smart_string::DemoteOnShrinkToFit;
+
+        let alloc = Counting::default();
+        let mut s = SmartString::<4, DemoteOnShrinkToFit, _>::from_str_in("abcdef", &alloc);
+        assert_eq!(alloc.live.get(), 1);
+        s.truncate(3);
+        s.shrink_to_fit();
+        assert!(s.is_stack());
+        assert_eq!(s, "abc");
+        assert_eq!(alloc.live.get(), 0);
+    }
+
+    #[test]
+    fn test_str_stack_in() {
+        let alloc = Counting::default();
+        {
+            let mut stack = StrStack::<usize, _>::new_in(&alloc);
+            stack.push("abc");
+            stack.push("def");
+            assert_eq!(stack.iter().collect::<Vec<_>>(), ["abc", "def"]);
+            assert_eq!(alloc.live.get(), 2);
+
+            let copy = stack.clone();
+            assert!(copy == stack);
+            assert_eq!(alloc.live.get(), 4);
+
+            // The scratch space of the sort comes from the stack's allocator too, and the spare data capacity is
+            // reused: the second sort only allocates the bounds.
+            let total = alloc.total.get();
+            stack.sort_by(|a, b| b.cmp(a));
+            assert_eq!(stack.iter().collect::<Vec<_>>(), ["def", "abc"]);
+            assert!(alloc.total.get() > total);
+            assert_eq!(alloc.live.get(), 4);
+            let total = alloc.total.get();
+            stack.sort();
+            assert_eq!(stack.iter().collect::<Vec<_>>(), ["abc", "def"]);
+            assert_eq!((alloc.live.get(), alloc.total.get()), (4, total + 1));
+        }
+        assert_eq!(alloc.live.get(), 0);
+    }
+
+    #[test]
+    fn test_tagged_str_stack_in() {
+        let alloc = Counting::default();
+        {
+            let mut stack = TaggedStrStack::<String, usize, _>::new_in(&alloc);
+            stack.push("abc", String::from("x"));
+            assert_eq!(stack.get(0), Some(("abc", &String::from("x"))));
+            // The data, the ends and the tags.
+            assert_eq!(alloc.live.get(), 3);
+
+            let copy = stack.clone();
+            assert!(copy == stack);
+            assert_eq!(alloc.live.get(), 6);
+        }
+        assert_eq!(alloc.live.get(), 0);
+    }
+}
@@ -0,0 +1,35 @@
+//! Stand-ins for the `allocator-api2` items when the feature is disabled.
+//!
+//! The trait is sealed: enabling the feature must not break anyone, and an implementation of this trait would.
+
+use core::fmt;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// The error type returned by `Allocator` methods on failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocError;
+
+impl fmt::Display for AllocError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("memory allocation failed")
+    }
+}
+
+/// A stand-in for the unstable `core::alloc::Allocator` trait, implemented by [`Global`] only; enable the
+/// `allocator-api2` feature to plug in your own.
+pub trait Allocator: sealed::Sealed {}
+
+/// The global memory allocator, the one used by `String` and `Vec`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Global;
+
+impl sealed::Sealed for Global {}
+
+impl Allocator for Global {}
+
+impl<A: Allocator + ?Sized> sealed::Sealed for &A {}
+
+impl<A: Allocator + ?Sized> Allocator for &A {}
@@ -0,0 +1,146 @@
+//! A `Vec<T, A>`: the heap storage of `SmartString`, `StrStack` and `TaggedStrStack`.
+//!
+//! With the `allocator-api2` feature it is allocator-api2's `Vec`. Without it, every allocator is the global one,
+//! so a `Vec<T>` does the job.
+
+#[cfg(feature = "allocator-api2")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "allocator-api2")]
+pub(crate) use allocator_api2::vec::Vec as VecIn;
+#[cfg(not(feature = "allocator-api2"))]
+pub(crate) use shim::from_vec;
+#[cfg(not(feature = "allocator-api2"))]
+pub(crate) use shim::into_vec;
+#[cfg(not(feature = "allocator-api2"))]
+pub(crate) use shim::VecIn;
+
+#[cfg(feature = "allocator-api2")]
+use crate::allocator::Global;
+
+/// Converts a `Vec<T>` into a `VecIn<T, Global>`, reusing its buffer.
+#[cfg(feature = "allocator-api2")]
+#[inline]
+pub(crate) fn from_vec<T>(vec: Vec<T>) -> VecIn<T, Global> {
+    let mut vec = core::mem::ManuallyDrop::new(vec);
+    // SAFETY: allocator-api2's `Global` is the global allocator `Vec` allocates through, with the same layout.
+    unsafe { VecIn::from_raw_parts_in(vec.as_mut_ptr(), vec.len(), vec.capacity(), Global) }
+}
+
+/// Converts a `VecIn<T, Global>` into a `Vec<T>`, reusing its buffer.
+#[cfg(feature = "allocator-api2")]
+#[inline]
+pub(crate) fn into_vec<T>(vec: VecIn<T, Global>) -> Vec<T> {
+    let (ptr, len, capacity, Global) = vec.into_raw_parts_with_alloc();
+    // SAFETY: allocator-api2's `Global` is the global allocator `Vec` allocates through, with the same layout.
+    unsafe { Vec::from_raw_parts(ptr, len, capacity) }
+}
+
+#[cfg(not(feature = "allocator-api2"))]
+mod shim {
+    use alloc::vec::Vec;
+    use core::mem::ManuallyDrop;
+    use core::ops;
+
+    use crate::allocator::Allocator;
+    use crate::allocator::Global;
+
+    /// A `Vec<T>` standing in for a `Vec<T, A>`; it dereferences to the `Vec`.
+    #[derive(Clone)]
+    pub(crate) struct VecIn<T, A: Allocator> {
+        vec: Vec<T>,
+        alloc: A,
+    }
+
+    impl<T, A: Allocator> VecIn<T, A> {
+        #[inline]
+        pub fn new_in(alloc: A) -> Self {
+            Self {
+                vec: Vec::new(),
+                alloc,
+            }
+        }
+
+        #[inline]
+        pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+            Self {
+                vec: Vec::with_capacity(capacity),
+                alloc,
+            }
+        }
+
+        /// # Safety
+        ///
+        /// See `Vec::from_raw_parts`.
+        #[inline]
+        pub unsafe fn from_raw_parts_in(
+            ptr: *mut T,
+            length: usize,
+            capacity: usize,
+            alloc: A,
+        ) -> Self {
+            Self {
+                vec: Vec::from_raw_parts(ptr, length, capacity),
+                alloc,
+            }
+        }
+
+        #[inline]
+        pub fn into_raw_parts_with_alloc(self) -> (*mut T, usize, usize, A) {
+            let mut vec = ManuallyDrop::new(self.vec);
+            (vec.as_mut_ptr(), vec.len(), vec.capacity(), self.alloc)
+        }
+
+        #[inline]
+        pub fn allocator(&self) -> &A {
+            &self.alloc
+        }
+    }
+
+    impl<T, A: Allocator> ops::Deref for VecIn<T, A> {
+        type Target = Vec<T>;
+
+        #[inline]
+        fn deref(&self) -> &Vec<T> {
+            &self.vec
+        }
+    }
+
+    impl<T, A: Allocator> ops::DerefMut for VecIn<T, A> {
+        #[inline]
+        fn deref_mut(&mut self) -> &mut Vec<T> {
+            &mut self.vec
+        }
+    }
+
+    #[inline]
+    pub(crate) fn from_vec<T>(vec: Vec<T>) -> VecIn<T, Global> {
+        VecIn { vec, alloc: Global }
+    }
+
+    #[inline]
+    pub(crate) fn into_vec<T>(vec: VecIn<T, Global>) -> Vec<T> {
+        vec.vec
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    #[test]
+    fn test_vec_roundtrip() {
+        let vec = Vec::from([1_usize, 2, 3]);
+        let ptr = vec.as_ptr();
+        let mut buf = from_vec(vec);
+        assert_eq!(buf.as_ptr(), ptr);
+        buf.push(4);
+        let vec = into_vec(buf);
+        assert_eq!(vec, [1, 2, 3, 4]);
+
+        let buf = from_vec(Vec::<u8>::new());
+        assert_eq!(buf.capacity(), 0);
+    }
+}
@@ -1,13 +1,23 @@
-use std::fmt;
+use core::fmt;
+#[cfg(feature = "std")]
 use std::io;
 
 use crate::PascalString;
 
+#[cfg(feature = "ufmt")]
+mod with_ufmt;
+
+#[cfg(feature = "ufmt")]
+pub(crate) use with_ufmt::write_debug_str;
+#[cfg(feature = "ufmt")]
+pub use with_ufmt::UDisplayExt;
+
 pub trait DisplayExt {
     fn is_empty(&self) -> bool;
 
     fn write_to_fmt<W: fmt::Write>(&self, writer: W) -> fmt::Result;
 
+    #[cfg(feature = "std")]
     fn write_to_bytes<W: io::Write>(&self, writer: W) -> fmt::Result;
 
     fn try_to_fmt<T: fmt::Write + Default>(&self) -> Result<T, T> {
@@ -18,6 +28,7 @@ pub trait DisplayExt {
         }
     }
 
+    #[cfg(feature = "std")]
     fn try_to_bytes<T: io::Write + Default>(&self) -> Result<T, T> {
         let mut writer = T::default();
         match self.write_to_bytes(&mut writer) {
@@ -31,6 +42,7 @@ pub trait DisplayExt {
             .unwrap_or_else(|_writer| panic!("Failed to write to target"))
     }
 
+    #[cfg(feature = "std")]
     fn to_bytes<T: io::Write + Default>(&self) -> T {
         self.try_to_bytes()
             .unwrap_or_else(|_writer| panic!("Failed to write to target"))
@@ -53,6 +65,7 @@ where
         write!(writer, "{}", self)
     }
 
+    #[cfg(feature = "std")]
     fn write_to_bytes<W: io::Write>(&self, mut writer: W) -> fmt::Result {
         writer
             .write_fmt(format_args!("{}", self))
@@ -0,0 +1,121 @@
+use ufmt::uDisplay;
+use ufmt::uWrite;
+use ufmt::Formatter;
+
+/// A `ufmt` counterpart of [`DisplayExt`](crate::DisplayExt) for types implementing [`uDisplay`].
+///
+/// `ufmt` is a smaller, panic-free alternative to `core::fmt`; together with [`PascalString`](crate::PascalString)
+/// it allows formatting into fixed buffers on embedded targets.
+pub trait UDisplayExt {
+    fn write_to_ufmt<W: uWrite + ?Sized>(&self, writer: &mut W) -> Result<(), W::Error>;
+
+    fn try_to_ufmt<T: uWrite + Default>(&self) -> Result<T, T> {
+        let mut writer = T::default();
+        match self.write_to_ufmt(&mut writer) {
+            Ok(_) => Ok(writer),
+            Err(_err) => Err(writer),
+        }
+    }
+
+    fn to_ufmt<T: uWrite + Default>(&self) -> T {
+        self.try_to_ufmt()
+            .unwrap_or_else(|_writer| panic!("Failed to write to target"))
+    }
+}
+
+impl<T> UDisplayExt for T
+where
+    T: uDisplay + ?Sized,
+{
+    #[inline]
+    fn write_to_ufmt<W: uWrite + ?Sized>(&self, writer: &mut W) -> Result<(), W::Error> {
+        self.fmt(&mut Formatter::new(writer))
+    }
+}
+
+/// Writes `s` as a quoted and escaped string literal, the same way `fmt::Debug` does for `str`.
+///
+/// `ufmt` does not provide `uDebug` for `str`, so the string types of this crate use this helper.
+pub(crate) fn write_debug_str<W: uWrite + ?Sized>(
+    s: &str,
+    f: &mut Formatter<'_, W>,
+) -> Result<(), W::Error> {
+    f.write_char('"')?;
+    let mut from = 0;
+    for (idx, ch) in s.char_indices() {
+        let mut escaped = ch.escape_debug();
+        // Unlike `char`, `str` does not escape single quotes in its `Debug` output.
+        if escaped.len() == 1 || ch == '\'' {
+            continue;
+        }
+        f.write_str(&s[from..idx])?;
+        escaped.try_for_each(|c| f.write_char(c))?;
+        from = idx + ch.len_utf8();
+    }
+    f.write_str(&s[from..])?;
+    f.write_char('"')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PascalString;
+
+    struct Answer;
+
+    impl uDisplay for Answer {
+        fn fmt<W: uWrite + ?Sized>(&self, f: &mut Formatter<'_, W>) -> Result<(), W::Error> {
+            ufmt::uwrite!(f, "answer={}", 42_u8)
+        }
+    }
+
+    struct Quoted<'a>(&'a str);
+
+    impl uDisplay for Quoted<'_> {
+        fn fmt<W: uWrite + ?Sized>(&self, f: &mut Formatter<'_, W>) -> Result<(), W::Error> {
+            write_debug_str(self.0, f)
+        }
+    }
+
+    #[test]
+    fn test_to_ufmt() {
+        assert_eq!(Answer.to_ufmt::<PascalString<9>>(), "answer=42");
+        assert_eq!("Hello".to_ufmt::<PascalString<5>>(), "Hello");
+        assert!(Answer.try_to_ufmt::<PascalString<9>>().is_ok());
+
+        let partial = Answer.try_to_ufmt::<PascalString<8>>().unwrap_err();
+        assert_eq!(partial, "answer=");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_to_ufmt_panic() {
+        "Hello".to_ufmt::<PascalString<4>>();
+    }
+
+    #[test]
+    fn test_write_to_ufmt() {
+        let mut buf = PascalString::<16>::new();
+        Answer.write_to_ufmt(&mut buf).unwrap();
+        "!".write_to_ufmt(&mut buf).unwrap();
+        assert_eq!(buf, "answer=42!");
+    }
+
+    #[test]
+    fn test_write_debug_str_matches_fmt_debug() {
+        for s in [
+            "",
+            "abc",
+            "a\"b",
+            "it's",
+            "tab\there",
+            "€\n😊",
+            "\\",
+            "\u{0}",
+        ] {
+            let expected = format!("{s:?}");
+            let actual: PascalString<32> = Quoted(s).to_ufmt();
+            assert_eq!(actual, expected);
+        }
+    }
+}
@@ -1,13 +1,38 @@
 //! `smart-string` is a collection of small string primitives:
 //!
 //! - [`PascalString`]: fixed-capacity UTF-8 string stored inline (stack / in-place).
-//! - [`SmartString`]: stack-or-heap string that promotes to heap when needed.
+//! - [`PascalStringLong`]: the same as `PascalString`, but with a `u32` length prefix for capacities above 255 bytes.
+//! - [`SmartString`]: stack-or-heap string that promotes to heap when needed; as small as `String` by default.
+//! - [`SharedSmartString`]: stack-or-`Arc<str>` string with O(1) clone and copy-on-write `make_mut`.
+//! - [`SmartCow`]: borrows a `&'a str` without copying; becomes an owned `SmartString` on the first mutation.
 //! - [`StrStack`]: a compact “stack” of string slices backed by a single byte buffer.
+//! - [`TaggedStrStack`]: a `StrStack` that stores a value alongside each entry.
+//! - [`StrSet`]: a hash set of strings on the `StrStack` storage, with stable ids in insertion order.
+//! - [`StrInterner`]: interns strings into a `StrSet` and hands out `Copy` symbols; [`SyncStrInterner`] is its
+//!   thread-safe variant.
+//! - [`StrMap`]: a string-keyed hash map with the keys packed in a `StrStack`.
+//!
+//! ## Features
+//!
+//! - `std` (default): enables `std::io`-based [`DisplayExt`] methods (`write_to_bytes`, `to_bytes`), [`StrSet`],
+//!   [`StrInterner`] and [`StrMap`] (their default hasher is std's `RandomState`); implies `alloc`.
+//! - `alloc`: enables heap-backed types ([`SmartString`], [`SharedSmartString`], [`SmartCow`], [`StrStack`],
+//!   [`TaggedStrStack`]) and the conversions into/from `String`, `Box`, `Rc`, `Arc`.
+//! - `serde` (default): enables serde support.
+//! - `ufmt`: implements [ufmt](https://crates.io/crates/ufmt)'s `uWrite` for `PascalString`, `PascalStringLong`,
+//!   `SmartString`, and `uDisplay`/`uDebug` for all of them; adds `UDisplayExt` (`to_ufmt`, `try_to_ufmt`).
+//! - `allocator-api2`: lets [`SmartString`], [`StrStack`] and [`TaggedStrStack`] allocate through any
+//!   [allocator-api2](https://crates.io/crates/allocator-api2) `Allocator`; see the [`allocator`] module.
+//!   Requires Rust 1.64.
+//!
+//! Without `std`, the crate is `no_std`: [`PascalString`], [`PascalStringLong`], their error types and the
+//! `fmt`-based [`DisplayExt`] methods only depend on `core`.
 //!
 //! ## Notes
 //!
 //! - `SmartString` promotion (stack → heap) can happen implicitly during mutation when capacity is exceeded.
-//! - Demotion (heap → stack) is **explicit** and must be requested via [`SmartString::try_into_stack`].
+//! - Demotion (heap → stack) is **explicit** by default and must be requested via [`SmartString::try_into_stack`];
+//!   a [`smart_string::DemotionPolicy`] type parameter can opt into implicit demotion.
 //!
 //! ## Safety & invariants
 //!
@@ -16,7 +41,9 @@
 //!
 //! Soundness relies on internal invariants:
 //!
-//! - `PascalString`: `len <= CAPACITY` and `data[..len]` is always valid UTF‑8.
+//! - `PascalString`, `PascalStringLong`: `len <= CAPACITY` and `data[..len]` is always valid UTF‑8.
+//! - `SmartString`: the first byte is the heap marker iff the value holds the raw parts of a buffer owned through `A`;
+//!   otherwise it holds a valid `PascalString<N>` (hence `N <= 254`).
 //! - `StrStack`: `data` is always valid UTF‑8 and `ends` stores valid UTF‑8 segment boundaries within `data`.
 //!
 //! See also: `API-PARITY.md` for the “std `String` parity” checklist and compatibility notes.
@@ -25,13 +52,54 @@
 //!     (`SmartString<const N: usize = DEFAULT_CAPACITY>`), which requires newer compilers.
 //!   - Note: MSRV is a `rustc` guarantee for this crate. Without a committed `Cargo.lock`, transitive dependency MSRVs
 //!     can drift over time; our CI runs an MSRV job to detect such drift.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+pub mod allocator;
 mod display_ext;
+mod macros;
 pub mod pascal_string;
+pub mod pascal_string_long;
+#[cfg(feature = "alloc")]
+pub mod shared_smart_string;
+#[cfg(feature = "alloc")]
+pub mod smart_cow;
+#[cfg(feature = "alloc")]
 pub mod smart_string;
+#[cfg(feature = "std")]
+pub mod str_interner;
+#[cfg(feature = "std")]
+pub mod str_map;
+#[cfg(feature = "std")]
+pub mod str_set;
+#[cfg(feature = "alloc")]
 pub mod str_stack;
 
 pub use crate::display_ext::DisplayExt;
+#[cfg(feature = "ufmt")]
+pub use crate::display_ext::UDisplayExt;
 pub use crate::pascal_string::PascalString;
+pub use crate::pascal_string_long::PascalStringLong;
+#[cfg(feature = "alloc")]
+pub use crate::shared_smart_string::SharedSmartString;
+#[cfg(feature = "alloc")]
+pub use crate::smart_cow::SmartCow;
+#[cfg(feature = "alloc")]
 pub use crate::smart_string::SmartString;
+#[cfg(feature = "std")]
+pub use crate::str_interner::StrInterner;
+#[cfg(feature = "std")]
+pub use crate::str_interner::SyncStrInterner;
+#[cfg(feature = "std")]
+pub use crate::str_map::StrMap;
+#[cfg(feature = "std")]
+pub use crate::str_set::StrSet;
+#[cfg(feature = "alloc")]
 pub use crate::str_stack::StrStack;
+#[cfg(feature = "alloc")]
 pub use crate::str_stack::StrStackIter;
+#[cfg(feature = "alloc")]
+pub use crate::str_stack::TaggedStrStack;
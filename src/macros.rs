@@ -0,0 +1,42 @@
+/// Implements `PartialEq<$target>` for each of the string types `$t`, and for references to them, by comparing
+/// `$t::as_ref()` with `$target::as_str()`.
+///
+/// The generic parameters of `$target` are given in brackets, e.g.
+/// `impl_reverse_eq_for_str_types!([const N: usize] Foo<N>; String, str)`.
+macro_rules! impl_reverse_eq_for_str_types {
+    ($generics:tt $target:ty; $($t:ty),* $(,)?) => {
+        $(
+            $crate::macros::impl_reverse_eq_for_str_types!(@impl $generics $target; $t);
+        )*
+    };
+    (@impl [$($generics:tt)*] $target:ty; $t:ty) => {
+        impl<$($generics)*> PartialEq<$target> for $t {
+            #[inline(always)]
+            fn eq(&self, other: &$target) -> bool {
+                let a: &str = self.as_ref();
+                let b = other.as_str();
+                a.eq(b)
+            }
+        }
+
+        impl<$($generics)*> PartialEq<$target> for &$t {
+            #[inline(always)]
+            fn eq(&self, other: &$target) -> bool {
+                let a: &str = self.as_ref();
+                let b = other.as_str();
+                a.eq(b)
+            }
+        }
+
+        impl<$($generics)*> PartialEq<$target> for &mut $t {
+            #[inline(always)]
+            fn eq(&self, other: &$target) -> bool {
+                let a: &str = self.as_ref();
+                let b = other.as_str();
+                a.eq(b)
+            }
+        }
+    };
+}
+
+pub(crate) use impl_reverse_eq_for_str_types;
@@ -0,0 +1,139 @@
+use core::fmt;
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+use core::str::from_utf8_unchecked;
+
+/// A draining iterator for `PascalString` and `PascalStringLong`.
+///
+/// This struct is created by the [`drain`] method on [`PascalString`] and
+/// [`PascalStringLong`](crate::PascalStringLong). See its documentation for more.
+///
+/// [`drain`]: crate::PascalString::drain
+/// [`PascalString`]: crate::PascalString
+pub struct Drain<'a, const CAPACITY: usize> {
+    /// The removed part of the string, in `data[..end]` initially.
+    data: [u8; CAPACITY],
+    /// Start of the part not yet iterated.
+    start: usize,
+    /// End of the part not yet iterated.
+    end: usize,
+    marker: PhantomData<&'a mut [u8; CAPACITY]>,
+}
+
+impl<'a, const CAPACITY: usize> Drain<'a, CAPACITY> {
+    /// # Safety
+    ///
+    /// `data[..len]` must be valid UTF-8.
+    #[inline]
+    pub(crate) unsafe fn new(data: [u8; CAPACITY], len: usize) -> Self {
+        Self {
+            data,
+            start: 0,
+            end: len,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns the remaining (sub)string of this iterator as a slice.
+    #[inline]
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        // SAFETY: `data[..len]` is valid UTF-8, and `start` and `end` only move by whole chars.
+        unsafe { from_utf8_unchecked(self.data.get_unchecked(self.start..self.end)) }
+    }
+}
+
+impl<const CAPACITY: usize> fmt::Debug for Drain<'_, CAPACITY> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Drain").field(&self.as_str()).finish()
+    }
+}
+
+impl<const CAPACITY: usize> AsRef<str> for Drain<'_, CAPACITY> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const CAPACITY: usize> AsRef<[u8]> for Drain<'_, CAPACITY> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_str().as_bytes()
+    }
+}
+
+impl<const CAPACITY: usize> Iterator for Drain<'_, CAPACITY> {
+    type Item = char;
+
+    #[inline]
+    fn next(&mut self) -> Option<char> {
+        let ch = self.as_str().chars().next()?;
+        self.start += ch.len_utf8();
+        Some(ch)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.as_str().chars().size_hint()
+    }
+
+    #[inline]
+    fn last(mut self) -> Option<char> {
+        self.next_back()
+    }
+}
+
+impl<const CAPACITY: usize> DoubleEndedIterator for Drain<'_, CAPACITY> {
+    #[inline]
+    fn next_back(&mut self) -> Option<char> {
+        let ch = self.as_str().chars().next_back()?;
+        self.end -= ch.len_utf8();
+        Some(ch)
+    }
+}
+
+impl<const CAPACITY: usize> FusedIterator for Drain<'_, CAPACITY> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::PascalString;
+
+    #[test]
+    fn test_drain_iterates_both_ends() {
+        let mut s = PascalString::<8>::try_from("a€bc").unwrap();
+        {
+            let mut drain = s.drain(..5);
+            assert_eq!(drain.as_str(), "a€b");
+            assert_eq!(drain.next(), Some('a'));
+            assert_eq!(drain.next_back(), Some('b'));
+            assert_eq!(drain.as_str(), "€");
+            assert_eq!(drain.next_back(), Some('€'));
+            assert_eq!(drain.next(), None);
+            assert_eq!(drain.next_back(), None);
+        }
+        assert_eq!(s, "c");
+    }
+
+    #[test]
+    fn test_drain_removes_range_if_not_iterated() {
+        let mut s = PascalString::<8>::try_from("abcdef").unwrap();
+        let _ = s.drain(1..=2);
+        assert_eq!(s, "adef");
+
+        let _ = s.drain(..);
+        assert_eq!(s, "");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_drain_panics_on_char_boundary() {
+        let mut s = PascalString::<8>::try_from("a€").unwrap();
+        let _ = s.drain(..2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_drain_panics_out_of_bounds() {
+        let mut s = PascalString::<8>::try_from("ab").unwrap();
+        let _ = s.drain(1..3);
+    }
+}
@@ -0,0 +1,729 @@
+/// Defines a fixed-capacity inline string type with a length prefix of type `$len`.
+///
+/// `PascalString` and `PascalStringLong` differ only in the type of their length prefix, so their
+/// implementation is shared here (a trait over the prefix would not work in the `const fn`s).
+macro_rules! define_pascal_string {
+    (
+        $(#[$attr:meta])*
+        $name:ident, $len:ty, max = $max:literal
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy)]
+        #[repr(C)]
+        pub struct $name<const CAPACITY: usize> {
+            // NOTE: `len` must stay the first field: `SmartString` overlays `PascalString::len` with the heap
+            // marker byte.
+            len: $len,
+            data: [u8; CAPACITY],
+        }
+
+        const _: () = {
+            use core::borrow::Borrow;
+            use core::borrow::BorrowMut;
+            use core::cmp;
+            use core::fmt;
+            use core::hash::Hash;
+            use core::hash::Hasher;
+            use core::ops;
+            use core::str::from_utf8_unchecked;
+            use core::str::from_utf8_unchecked_mut;
+
+            use $crate::pascal_string::Drain;
+            use $crate::pascal_string::InsertError;
+            use $crate::pascal_string::RemoveError;
+            use $crate::pascal_string::TryFromBytesError;
+            use $crate::pascal_string::TryFromStrError;
+            use $crate::DisplayExt;
+
+            impl<const CAPACITY: usize> $name<CAPACITY> {
+                pub const CAPACITY: usize = {
+                    assert!(
+                        CAPACITY <= <$len>::MAX as usize,
+                        concat!(stringify!($name), " max capacity is ", $max)
+                    );
+                    CAPACITY
+                };
+
+                #[inline(always)]
+                pub const fn new() -> Self {
+                    // This line triggers a compile time error, if CAPACITY doesn't fit the length prefix.
+                    // TODO look for a better way to assert CAPACITY.
+                    let _ = Self::CAPACITY;
+
+                    Self {
+                        len: 0,
+                        data: [0; CAPACITY],
+                    }
+                }
+
+                /// Creates a new instance from a `&str` within a const context.
+                /// This implementation prioritizes const context compatibility over performance.
+                /// If a const context is not required, use `try_from` for better performance.
+                /// In the future, once const in trait methods is stabilized, this method will be deprecated
+                /// in favor of `try_from`.
+                pub const fn try_from_str_const(string: &str) -> Option<Self> {
+                    let _ = Self::CAPACITY;
+
+                    if string.len() > CAPACITY {
+                        return None;
+                    }
+                    let mut this = Self {
+                        len: string.len() as $len,
+                        data: [0; CAPACITY],
+                    };
+                    let bytes = string.as_bytes();
+                    let mut i = 0;
+                    while i < string.len() {
+                        this.data[i] = bytes[i];
+                        i += 1;
+                    }
+                    Some(this)
+                }
+
+                /// Creates a new instance from a `&str`.
+                /// If the length of the string exceeds `CAPACITY`,
+                /// the string is truncated at the nearest valid UTF-8 boundary
+                /// to ensure its length does not exceed `CAPACITY`.
+                #[inline]
+                pub fn from_str_truncated(string: &str) -> Self {
+                    let _ = Self::CAPACITY;
+
+                    if let Ok(ps) = Self::try_from(string) {
+                        return ps;
+                    }
+
+                    let mut ps = Self::new();
+                    ps.push_str_truncated(string);
+                    ps
+                }
+
+                #[inline(always)]
+                pub const fn into_inner(self) -> ($len, [u8; CAPACITY]) {
+                    (self.len, self.data)
+                }
+
+                #[inline(always)]
+                pub const fn capacity(&self) -> usize {
+                    CAPACITY
+                }
+
+                #[inline(always)]
+                pub const fn len(&self) -> usize {
+                    self.len as usize
+                }
+
+                #[inline(always)]
+                pub const fn is_empty(&self) -> bool {
+                    self.len == 0
+                }
+
+                #[inline(always)]
+                pub fn as_str(&self) -> &str {
+                    self
+                }
+
+                #[inline(always)]
+                pub fn as_mut_str(&mut self) -> &mut str {
+                    self
+                }
+
+                #[inline]
+                pub fn try_push_str(&mut self, string: &str) -> Result<(), TryFromStrError> {
+                    let len = self.len();
+                    let new_len = len + string.len();
+
+                    if new_len > CAPACITY {
+                        return Err(TryFromStrError::TooLong);
+                    }
+
+                    self.data[len..new_len].copy_from_slice(string.as_bytes());
+                    self.len = new_len as $len;
+
+                    Ok(())
+                }
+
+                #[inline]
+                pub fn try_push(&mut self, ch: char) -> Result<(), TryFromStrError> {
+                    // TODO special case for ch.len_utf8() == 1
+                    self.try_push_str(ch.encode_utf8(&mut [0; 4]))
+                }
+
+                /// Appends a string slice, panicking if the capacity would be exceeded.
+                #[inline]
+                pub fn push_str_expect_capacity(&mut self, string: &str) {
+                    self.try_push_str(string)
+                        .expect(concat!(stringify!($name), " capacity exceeded"));
+                }
+
+                /// Appends a character, panicking if the capacity would be exceeded.
+                #[inline]
+                pub fn push_expect_capacity(&mut self, ch: char) {
+                    self.try_push(ch)
+                        .expect(concat!(stringify!($name), " capacity exceeded"));
+                }
+
+                /// Inserts a string slice at the given byte index.
+                ///
+                /// This is a true `try_` API: it **never panics**. All failure modes are returned as `InsertError`.
+                #[inline]
+                pub fn try_insert_str(&mut self, idx: usize, string: &str) -> Result<(), InsertError> {
+                    let len = self.len();
+                    if idx > len {
+                        return Err(InsertError::OutOfBounds { idx, len });
+                    }
+                    if !self.is_char_boundary(idx) {
+                        return Err(InsertError::NotCharBoundary { idx });
+                    }
+
+                    let insert_len = string.len();
+                    let new_len = len + insert_len;
+                    if new_len > CAPACITY {
+                        return Err(InsertError::TooLong);
+                    }
+
+                    // Shift tail to make room.
+                    self.data.copy_within(idx..len, idx + insert_len);
+                    // Copy inserted bytes.
+                    self.data[idx..idx + insert_len].copy_from_slice(string.as_bytes());
+                    self.len = new_len as $len;
+                    Ok(())
+                }
+
+                /// Inserts a string slice at the given byte index, truncating the inserted string to available
+                /// capacity.
+                ///
+                /// Returns the remainder that did not fit.
+                ///
+                /// This is a true `try_` API: it **never panics**. Index/boundary errors are returned as
+                /// `InsertError`.
+                #[inline]
+                pub fn try_insert_str_truncated<'s>(
+                    &mut self,
+                    idx: usize,
+                    string: &'s str,
+                ) -> Result<&'s str, InsertError> {
+                    let len = self.len();
+                    if idx > len {
+                        return Err(InsertError::OutOfBounds { idx, len });
+                    }
+                    if !self.is_char_boundary(idx) {
+                        return Err(InsertError::NotCharBoundary { idx });
+                    }
+
+                    let available = CAPACITY.saturating_sub(len);
+                    if available >= string.len() {
+                        self.try_insert_str(idx, string)?;
+                        return Ok("");
+                    }
+
+                    let mut prefix_len = 0;
+                    for c in string.chars() {
+                        let l = c.len_utf8();
+                        if prefix_len + l > available {
+                            break;
+                        }
+                        prefix_len += l;
+                    }
+
+                    let (prefix, remainder) = string.split_at(prefix_len);
+                    // Prefix is constructed from `chars()` boundaries, so it is valid UTF-8 and fits by
+                    // construction.
+                    self.try_insert_str(idx, prefix)?;
+                    Ok(remainder)
+                }
+
+                /// Inserts a string slice at the given byte index, truncating to capacity, panicking on invalid
+                /// index/boundary.
+                ///
+                /// Returns the remainder that did not fit.
+                #[inline]
+                pub fn insert_str_truncated<'s>(&mut self, idx: usize, string: &'s str) -> &'s str {
+                    self.try_insert_str_truncated(idx, string)
+                        .expect("invalid index or char boundary")
+                }
+
+                /// Inserts a string slice at the given byte index, panicking if the capacity would be exceeded.
+                ///
+                /// This is an explicit opt-in panicking API for fixed-capacity strings.
+                #[inline]
+                pub fn insert_str_expect_capacity(&mut self, idx: usize, string: &str) {
+                    self.try_insert_str(idx, string)
+                        .expect(concat!(stringify!($name), " insert failed"));
+                }
+
+                /// Inserts a character at the given byte index.
+                ///
+                /// This is a true `try_` API: it **never panics**. All failure modes are returned as `InsertError`.
+                #[inline]
+                pub fn try_insert(&mut self, idx: usize, ch: char) -> Result<(), InsertError> {
+                    let mut buf = [0_u8; 4];
+                    let s = ch.encode_utf8(&mut buf);
+                    self.try_insert_str(idx, s)
+                }
+
+                /// Inserts a character at the given byte index, panicking if the capacity would be exceeded.
+                #[inline]
+                pub fn insert_expect_capacity(&mut self, idx: usize, ch: char) {
+                    self.try_insert(idx, ch)
+                        .expect(concat!(stringify!($name), " insert failed"));
+                }
+
+                /// Removes and returns the `char` at the given byte index.
+                ///
+                /// # Panics
+                ///
+                /// - If `idx >= self.len()`
+                /// - If `idx` is not on a UTF-8 character boundary
+                #[inline]
+                pub fn remove(&mut self, idx: usize) -> char {
+                    let len = self.len();
+                    assert!(idx < len, "index out of bounds");
+                    assert!(self.is_char_boundary(idx), "index is not a char boundary");
+
+                    let ch = self.as_str()[idx..].chars().next().expect("idx < len");
+                    let ch_len = ch.len_utf8();
+
+                    // Shift tail left to close the gap.
+                    self.data.copy_within(idx + ch_len..len, idx);
+                    let new_len = len - ch_len;
+                    self.len = new_len as $len;
+
+                    // Keep deterministic contents beyond len (not required for soundness, but helps
+                    // debugging/tests).
+                    self.data[new_len..len].fill(0);
+
+                    ch
+                }
+
+                /// Removes and returns the `char` at the given byte index.
+                ///
+                /// This is a true `try_` API: it **never panics**. All failure modes are returned as `RemoveError`.
+                #[inline]
+                pub fn try_remove(&mut self, idx: usize) -> Result<char, RemoveError> {
+                    let len = self.len();
+                    if idx >= len {
+                        return Err(RemoveError::OutOfBounds { idx, len });
+                    }
+                    if !self.is_char_boundary(idx) {
+                        return Err(RemoveError::NotCharBoundary { idx });
+                    }
+                    Ok(self.remove(idx))
+                }
+
+                /// Returns the remainder of the string that was not pushed.
+                #[inline]
+                pub fn push_str_truncated<'s>(&mut self, string: &'s str) -> &'s str {
+                    if self.try_push_str(string).is_ok() {
+                        return "";
+                    }
+
+                    // TODO is there more efficient way to do this?
+                    //   Maybe iter four bytes from the end of the slice and find the UTF-8 boundary?
+
+                    let mut new_len = self.len();
+                    for c in string.chars() {
+                        let len = c.len_utf8();
+                        if new_len + len > CAPACITY {
+                            break;
+                        };
+                        new_len += len;
+                    }
+
+                    let pos = new_len - self.len();
+                    let (substring, remainder) = string.split_at(pos);
+                    self.try_push_str(substring).unwrap();
+
+                    remainder
+                }
+
+                #[inline]
+                pub fn truncate(&mut self, new_len: usize) {
+                    if new_len <= self.len() {
+                        assert!(self.is_char_boundary(new_len));
+                        self.len = new_len as $len;
+                    }
+                }
+
+                #[inline]
+                pub fn pop(&mut self) -> Option<char> {
+                    let ch = self.chars().next_back()?;
+                    let newlen = self.len() - ch.len_utf8();
+                    self.len = newlen as $len;
+                    Some(ch)
+                }
+
+                #[inline]
+                pub fn clear(&mut self) {
+                    self.len = 0;
+                }
+
+                /// Retains only the characters specified by the predicate.
+                ///
+                /// If `f` panics, the string is left with the characters retained so far (like `String::retain`).
+                #[inline]
+                pub fn retain<F>(&mut self, mut f: F)
+                where
+                    F: FnMut(char) -> bool,
+                {
+                    let source = *self;
+                    self.len = 0;
+                    for ch in source.chars() {
+                        if f(ch) {
+                            let len = self.len();
+                            let new_len = len + ch.len_utf8();
+                            ch.encode_utf8(&mut self.data[len..new_len]);
+                            self.len = new_len as $len;
+                        }
+                    }
+                }
+
+                /// Removes the specified byte range from the string, returning the removed characters as an
+                /// iterator.
+                ///
+                /// Unlike `String::drain`, the range is removed immediately: the iterator owns a copy of the
+                /// removed part.
+                ///
+                /// # Panics
+                ///
+                /// - If the range is out of bounds
+                /// - If the start or the end of the range is not on a UTF-8 character boundary
+                #[inline]
+                pub fn drain<R>(&mut self, range: R) -> Drain<'_, CAPACITY>
+                where
+                    R: ops::RangeBounds<usize>,
+                {
+                    let ops::Range { start, end } = match self.try_char_range(range) {
+                        Ok(range) => range,
+                        Err(e) => panic!("{}", e),
+                    };
+
+                    let mut drained = self.split_off(start);
+                    let tail = drained.split_off(end - start);
+                    self.push_str_expect_capacity(&tail);
+
+                    let (len, data) = drained.into_inner();
+                    // SAFETY: these are the parts of a string, so `data[..len]` is valid UTF-8.
+                    unsafe { Drain::new(data, len as usize) }
+                }
+
+                /// Replaces the specified byte range with the given string slice.
+                ///
+                /// This is a true `try_` API: it **never panics**. All failure modes are returned as `InsertError`;
+                /// a reversed range `start > end` is reported as `InsertError::OutOfBounds { idx: start, len: end }`.
+                #[inline]
+                pub fn try_replace_range<R>(
+                    &mut self,
+                    range: R,
+                    replace_with: &str,
+                ) -> Result<(), InsertError>
+                where
+                    R: ops::RangeBounds<usize>,
+                {
+                    let ops::Range { start, end } = self.try_char_range(range)?;
+
+                    let len = self.len();
+                    let new_len = len - (end - start) + replace_with.len();
+                    if new_len > CAPACITY {
+                        return Err(InsertError::TooLong);
+                    }
+
+                    let new_end = start + replace_with.len();
+                    // Move the tail to its new place, then copy the replacement bytes.
+                    self.data.copy_within(end..len, new_end);
+                    self.data[start..new_end].copy_from_slice(replace_with.as_bytes());
+                    self.len = new_len as $len;
+                    Ok(())
+                }
+
+                /// Replaces the specified byte range with the given string slice, panicking if the capacity would
+                /// be exceeded.
+                ///
+                /// This is an explicit opt-in panicking API for fixed-capacity strings.
+                #[inline]
+                pub fn replace_range_expect_capacity<R>(&mut self, range: R, replace_with: &str)
+                where
+                    R: ops::RangeBounds<usize>,
+                {
+                    self.try_replace_range(range, replace_with)
+                        .expect(concat!(stringify!($name), " replace_range failed"));
+                }
+
+                /// Splits the string into two at the given byte index, returning the tail.
+                ///
+                /// # Panics
+                ///
+                /// - If `at > self.len()`
+                /// - If `at` is not on a UTF-8 character boundary
+                #[inline]
+                #[must_use = "use `.truncate()` if you don't need the other half"]
+                pub fn split_off(&mut self, at: usize) -> Self {
+                    let len = self.len();
+                    assert!(at <= len, "index out of bounds");
+                    assert!(self.is_char_boundary(at), "index is not a char boundary");
+
+                    let mut other = Self::new();
+                    other.data[..len - at].copy_from_slice(&self.data[at..len]);
+                    other.len = (len - at) as $len;
+                    self.len = at as $len;
+                    other
+                }
+
+                /// Resolves `range` against the string, checking its bounds and UTF-8 character boundaries.
+                #[inline]
+                fn try_char_range<R>(&self, range: R) -> Result<ops::Range<usize>, InsertError>
+                where
+                    R: ops::RangeBounds<usize>,
+                {
+                    let len = self.len();
+                    let start = match range.start_bound() {
+                        ops::Bound::Included(&idx) => idx,
+                        ops::Bound::Excluded(&idx) => idx
+                            .checked_add(1)
+                            .ok_or(InsertError::OutOfBounds { idx, len })?,
+                        ops::Bound::Unbounded => 0,
+                    };
+                    let end = match range.end_bound() {
+                        ops::Bound::Included(&idx) => idx
+                            .checked_add(1)
+                            .ok_or(InsertError::OutOfBounds { idx, len })?,
+                        ops::Bound::Excluded(&idx) => idx,
+                        ops::Bound::Unbounded => len,
+                    };
+
+                    if end > len {
+                        return Err(InsertError::OutOfBounds { idx: end, len });
+                    }
+                    if start > end {
+                        return Err(InsertError::OutOfBounds {
+                            idx: start,
+                            len: end,
+                        });
+                    }
+                    for idx in [start, end] {
+                        if !self.is_char_boundary(idx) {
+                            return Err(InsertError::NotCharBoundary { idx });
+                        }
+                    }
+                    Ok(start..end)
+                }
+            }
+
+            // -- Common traits ------------------------------------------------------------------------
+
+            impl<const CAPACITY: usize> Default for $name<CAPACITY> {
+                #[inline(always)]
+                fn default() -> Self {
+                    let _ = Self::CAPACITY;
+
+                    Self::new()
+                }
+            }
+
+            impl<T: ops::Deref<Target = str> + ?Sized, const CAPACITY: usize> PartialEq<T>
+                for $name<CAPACITY>
+            {
+                #[inline(always)]
+                fn eq(&self, other: &T) -> bool {
+                    self.as_str().eq(other.deref())
+                }
+            }
+
+            $crate::macros::impl_reverse_eq_for_str_types!([const CAPACITY: usize] $name<CAPACITY>; str);
+            #[cfg(feature = "alloc")]
+            $crate::macros::impl_reverse_eq_for_str_types!(
+                [const CAPACITY: usize] $name<CAPACITY>;
+                alloc::string::String,
+                alloc::borrow::Cow<'_, str>,
+                alloc::boxed::Box<str>,
+                alloc::rc::Rc<str>,
+                alloc::sync::Arc<str>
+            );
+
+            impl<const CAPACITY: usize> Eq for $name<CAPACITY> {}
+
+            impl<T: ops::Deref<Target = str>, const CAPACITY: usize> PartialOrd<T> for $name<CAPACITY> {
+                #[inline(always)]
+                fn partial_cmp(&self, other: &T) -> Option<cmp::Ordering> {
+                    self.as_str().partial_cmp(other.deref())
+                }
+            }
+
+            impl<const CAPACITY: usize> Ord for $name<CAPACITY> {
+                #[inline(always)]
+                fn cmp(&self, other: &Self) -> cmp::Ordering {
+                    self.as_str().cmp(other.as_str())
+                }
+            }
+
+            impl<const CAPACITY: usize> Hash for $name<CAPACITY> {
+                #[inline(always)]
+                fn hash<H: Hasher>(&self, state: &mut H) {
+                    self.as_str().hash(state)
+                }
+            }
+
+            // -- Formatting ---------------------------------------------------------------------------
+
+            impl<const CAPACITY: usize> fmt::Debug for $name<CAPACITY> {
+                #[inline(always)]
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    let name: $crate::PascalString<39> =
+                        format_args!(concat!(stringify!($name), "<{}>"), CAPACITY)
+                            .try_to_fmt()
+                            .unwrap_or_else(|_| concat!(stringify!($name), "<?>").to_fmt());
+                    f.debug_tuple(&name).field(&self.as_str()).finish()
+                }
+            }
+
+            impl<const CAPACITY: usize> fmt::Display for $name<CAPACITY> {
+                #[inline(always)]
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    self.as_str().fmt(f)
+                }
+            }
+
+            // -- Reference ----------------------------------------------------------------------------
+
+            impl<const CAPACITY: usize> ops::Deref for $name<CAPACITY> {
+                type Target = str;
+
+                #[inline(always)]
+                fn deref(&self) -> &Self::Target {
+                    // SAFETY: the string maintains its length invariant.
+                    let bytes = unsafe { self.data.get_unchecked(..self.len()) };
+                    // SAFETY: the string maintains its utf8 invariant.
+                    unsafe { from_utf8_unchecked(bytes) }
+                }
+            }
+
+            impl<const CAPACITY: usize> ops::DerefMut for $name<CAPACITY> {
+                #[inline(always)]
+                fn deref_mut(&mut self) -> &mut Self::Target {
+                    let len = self.len();
+                    // SAFETY: the string maintains its length invariant.
+                    let bytes = unsafe { self.data.get_unchecked_mut(..len) };
+                    // SAFETY: the string maintains its utf8 invariant.
+                    unsafe { from_utf8_unchecked_mut(bytes) }
+                }
+            }
+
+            impl<const CAPACITY: usize> Borrow<str> for $name<CAPACITY> {
+                #[inline(always)]
+                fn borrow(&self) -> &str {
+                    self
+                }
+            }
+
+            impl<const CAPACITY: usize> AsRef<str> for $name<CAPACITY> {
+                #[inline(always)]
+                fn as_ref(&self) -> &str {
+                    self
+                }
+            }
+
+            impl<const CAPACITY: usize> AsRef<[u8]> for $name<CAPACITY> {
+                #[inline(always)]
+                fn as_ref(&self) -> &[u8] {
+                    self.as_bytes()
+                }
+            }
+
+            impl<const CAPACITY: usize> AsMut<str> for $name<CAPACITY> {
+                #[inline(always)]
+                fn as_mut(&mut self) -> &mut str {
+                    self
+                }
+            }
+
+            impl<const CAPACITY: usize> BorrowMut<str> for $name<CAPACITY> {
+                #[inline(always)]
+                fn borrow_mut(&mut self) -> &mut str {
+                    self
+                }
+            }
+
+            // -- Conversion ---------------------------------------------------------------------------
+
+            impl<'a, const CAPACITY: usize> TryFrom<&'a [u8]> for $name<CAPACITY> {
+                type Error = TryFromBytesError;
+
+                #[inline]
+                fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+                    let _ = Self::CAPACITY;
+
+                    let string = core::str::from_utf8(bytes)?;
+                    Ok(Self::try_from(string)?)
+                }
+            }
+
+            impl<'a, const CAPACITY: usize> TryFrom<&'a mut str> for $name<CAPACITY> {
+                type Error = TryFromStrError;
+
+                #[inline]
+                fn try_from(value: &'a mut str) -> Result<Self, Self::Error> {
+                    Self::try_from(&*value)
+                }
+            }
+
+            impl<'a, const CAPACITY: usize> TryFrom<&'a str> for $name<CAPACITY> {
+                type Error = TryFromStrError;
+
+                #[inline]
+                fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+                    let _ = Self::CAPACITY;
+
+                    let bytes = value.as_bytes();
+                    let len = bytes.len();
+
+                    if len > CAPACITY {
+                        return Err(TryFromStrError::TooLong);
+                    }
+
+                    let data = match <[u8; CAPACITY]>::try_from(bytes).ok() {
+                        Some(data) => data,
+                        None => {
+                            let mut data = [0; CAPACITY];
+                            data[..len].copy_from_slice(bytes);
+                            data
+                        }
+                    };
+
+                    let len = len as $len;
+
+                    Ok(Self { len, data })
+                }
+            }
+
+            impl<const CAPACITY: usize> TryFrom<char> for $name<CAPACITY> {
+                type Error = TryFromStrError;
+
+                #[inline]
+                fn try_from(value: char) -> Result<Self, Self::Error> {
+                    let _ = Self::CAPACITY;
+
+                    Self::try_from(value.encode_utf8(&mut [0; 4]))
+                }
+            }
+
+            impl<const CAPACITY: usize> core::str::FromStr for $name<CAPACITY> {
+                type Err = TryFromStrError;
+
+                #[inline]
+                fn from_str(s: &str) -> Result<Self, Self::Err> {
+                    Self::try_from(s)
+                }
+            }
+
+            // -- IO -----------------------------------------------------------------------------------
+
+            impl<const CAPACITY: usize> fmt::Write for $name<CAPACITY> {
+                #[inline]
+                fn write_str(&mut self, s: &str) -> fmt::Result {
+                    self.try_push_str(s).map_err(|_| fmt::Error)
+                }
+            }
+        };
+    };
+}
+
+pub(crate) use define_pascal_string;
@@ -1,4 +1,5 @@
 use core::fmt;
+use core::marker::PhantomData;
 
 use serde::de::Error;
 use serde::de::Unexpected;
@@ -10,15 +11,20 @@ use serde::Serializer;
 
 use crate::pascal_string::TryFromStrError;
 use crate::PascalString;
+use crate::PascalStringLong;
 
-struct StringVisitor<const CAPACITY: usize>;
+/// Visits a string into `T`, a `PascalString<CAPACITY>` or a `PascalStringLong<CAPACITY>`.
+struct StringVisitor<T, const CAPACITY: usize>(PhantomData<T>);
 
-struct StringInPlaceVisitor<'a, const CAPACITY: usize>(&'a mut PascalString<CAPACITY>);
+struct StringInPlaceVisitor<'a, T, const CAPACITY: usize>(&'a mut T);
 
 // -------------------------------------------------------------------------------------------------
 
-impl<'de, const CAPACITY: usize> Visitor<'de> for StringVisitor<CAPACITY> {
-    type Value = PascalString<CAPACITY>;
+impl<'de, T, const CAPACITY: usize> Visitor<'de> for StringVisitor<T, CAPACITY>
+where
+    T: for<'s> TryFrom<&'s str, Error = TryFromStrError>,
+{
+    type Value = T;
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -31,8 +37,7 @@ impl<'de, const CAPACITY: usize> Visitor<'de> for StringVisitor<CAPACITY> {
     where
         E: Error,
     {
-        PascalString::try_from(v)
-            .map_err(|TryFromStrError::TooLong| Error::invalid_length(v.len(), &self))
+        T::try_from(v).map_err(|TryFromStrError::TooLong| Error::invalid_length(v.len(), &self))
     }
 
     fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
@@ -46,7 +51,10 @@ impl<'de, const CAPACITY: usize> Visitor<'de> for StringVisitor<CAPACITY> {
     }
 }
 
-impl<'a, 'de, const CAPACITY: usize> Visitor<'de> for StringInPlaceVisitor<'a, CAPACITY> {
+impl<'a, 'de, T, const CAPACITY: usize> Visitor<'de> for StringInPlaceVisitor<'a, T, CAPACITY>
+where
+    T: for<'s> TryFrom<&'s str, Error = TryFromStrError>,
+{
     type Value = ();
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
@@ -60,9 +68,7 @@ impl<'a, 'de, const CAPACITY: usize> Visitor<'de> for StringInPlaceVisitor<'a, C
     where
         E: Error,
     {
-        self.0.clear();
-        self.0
-            .try_push_str(v)
+        *self.0 = T::try_from(v)
             .map_err(|TryFromStrError::TooLong| Error::invalid_length(v.len(), &self))?;
         Ok(())
     }
@@ -80,27 +86,35 @@ impl<'a, 'de, const CAPACITY: usize> Visitor<'de> for StringInPlaceVisitor<'a, C
 
 // -------------------------------------------------------------------------------------------------
 
-impl<const CAPACITY: usize> Serialize for PascalString<CAPACITY> {
-    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        serializer.serialize_str(self)
-    }
+macro_rules! impl_serde {
+    ($($name:ident),*) => {
+        $(
+            impl<const CAPACITY: usize> Serialize for $name<CAPACITY> {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.serialize_str(self)
+                }
+            }
+
+            impl<'de, const CAPACITY: usize> Deserialize<'de> for $name<CAPACITY> {
+                fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+                where
+                    D: Deserializer<'de>,
+                {
+                    deserializer.deserialize_string(StringVisitor::<Self, CAPACITY>(PhantomData))
+                }
+
+                fn deserialize_in_place<D>(deserializer: D, place: &mut Self) -> Result<(), D::Error>
+                where
+                    D: Deserializer<'de>,
+                {
+                    deserializer.deserialize_string(StringInPlaceVisitor::<Self, CAPACITY>(place))
+                }
+            }
+        )*
+    };
 }
 
-impl<'de, const CAPACITY: usize> Deserialize<'de> for PascalString<CAPACITY> {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        deserializer.deserialize_string(StringVisitor)
-    }
-
-    fn deserialize_in_place<D>(deserializer: D, place: &mut Self) -> Result<(), D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        deserializer.deserialize_string(StringInPlaceVisitor(place))
-    }
-}
+impl_serde!(PascalString, PascalStringLong);
 
 #[cfg(test)]
 mod tests {
@@ -123,6 +137,24 @@ mod tests {
         assert!(err.to_string().contains("invalid length"));
     }
 
+    #[test]
+    fn test_roundtrip_above_255_bytes() {
+        let long = "€".repeat(100); // 300 bytes
+        let ps = PascalStringLong::<512>::try_from(long.as_str()).unwrap();
+
+        let json = serde_json::to_string(&ps).unwrap();
+        let back: PascalStringLong<512> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, long);
+
+        let mut place = PascalStringLong::<512>::try_from("zz").unwrap();
+        let mut de = serde_json::Deserializer::from_str(&json);
+        PascalStringLong::deserialize_in_place(&mut de, &mut place).unwrap();
+        assert_eq!(place, long);
+
+        let err = serde_json::from_str::<PascalStringLong<299>>(&json).unwrap_err();
+        assert!(err.to_string().contains("invalid length"));
+    }
+
     #[test]
     fn test_deserialize_in_place_overwrites_existing_value() {
         let mut place = PascalString::<4>::try_from("zzzz").unwrap();
@@ -0,0 +1,90 @@
+use ufmt::uDebug;
+use ufmt::uDisplay;
+use ufmt::uWrite;
+use ufmt::Formatter;
+
+use crate::display_ext::write_debug_str;
+use crate::pascal_string::TryFromStrError;
+use crate::PascalString;
+use crate::PascalStringLong;
+
+macro_rules! impl_ufmt {
+    ($($name:ident),*) => {
+        $(
+            impl<const CAPACITY: usize> uWrite for $name<CAPACITY> {
+                type Error = TryFromStrError;
+
+                #[inline]
+                fn write_str(&mut self, s: &str) -> Result<(), Self::Error> {
+                    self.try_push_str(s)
+                }
+
+                #[inline]
+                fn write_char(&mut self, c: char) -> Result<(), Self::Error> {
+                    self.try_push(c)
+                }
+            }
+
+            impl<const CAPACITY: usize> uDisplay for $name<CAPACITY> {
+                #[inline]
+                fn fmt<W: uWrite + ?Sized>(&self, f: &mut Formatter<'_, W>) -> Result<(), W::Error> {
+                    f.write_str(self)
+                }
+            }
+
+            impl<const CAPACITY: usize> uDebug for $name<CAPACITY> {
+                fn fmt<W: uWrite + ?Sized>(&self, f: &mut Formatter<'_, W>) -> Result<(), W::Error> {
+                    f.write_str(concat!(stringify!($name), "<"))?;
+                    uDisplay::fmt(&CAPACITY, f)?;
+                    f.write_str(">(")?;
+                    write_debug_str(self, f)?;
+                    f.write_str(")")
+                }
+            }
+        )*
+    };
+}
+
+impl_ufmt!(PascalString, PascalStringLong);
+
+#[cfg(test)]
+mod tests {
+    use ufmt::uwrite;
+
+    use super::*;
+    use crate::UDisplayExt;
+
+    #[test]
+    fn test_uwrite() {
+        let mut ps = PascalString::<8>::new();
+        uwrite!(ps, "{}-{}", 12_u8, "ab").unwrap();
+        assert_eq!(ps, "12-ab");
+
+        assert_eq!(uwrite!(ps, "€€"), Err(TryFromStrError::TooLong));
+        assert_eq!(ps, "12-ab");
+    }
+
+    #[test]
+    fn test_udisplay_and_udebug() {
+        let ps = PascalString::<8>::try_from("a\"€").unwrap();
+
+        let display: PascalString<16> = ps.to_ufmt();
+        assert_eq!(display, "a\"€");
+
+        let mut debug = PascalString::<32>::new();
+        ufmt::uwrite!(debug, "{:?}", ps).unwrap();
+        assert_eq!(debug, format!("{ps:?}"));
+    }
+
+    #[test]
+    fn test_pascal_string_long() {
+        let mut ps = PascalStringLong::<300>::new();
+        uwrite!(ps, "{}-{}", 12_u8, "ab").unwrap();
+        assert_eq!(ps, "12-ab");
+
+        let mut debug = PascalStringLong::<300>::new();
+        ufmt::uwrite!(debug, "{:?}", ps).unwrap();
+        assert_eq!(debug, format!("{ps:?}"));
+        assert_eq!(debug, r#"PascalStringLong<300>("12-ab")"#);
+    }
+}
@@ -0,0 +1,233 @@
+use crate::pascal_string::define_pascal_string;
+pub use crate::pascal_string::Drain;
+pub use crate::pascal_string::InsertError;
+pub use crate::pascal_string::RemoveError;
+pub use crate::pascal_string::TryFromBytesError;
+pub use crate::pascal_string::TryFromStrError;
+use crate::PascalString;
+
+define_pascal_string!(
+    /// A fixed-capacity inline string with a `u32` length prefix.
+    ///
+    /// This is the counterpart of [`PascalString`] for capacities above 255 bytes (up to `u32::MAX`).
+    /// It shares the same API surface and error types.
+    ///
+    /// There is deliberately no `u16`-prefixed variant: above 255 bytes, it would save at most 3 bytes per value.
+    PascalStringLong,
+    u32,
+    max = 4294967295
+);
+
+impl<const M: usize, const N: usize> PartialEq<PascalStringLong<N>> for &PascalString<M> {
+    #[inline(always)]
+    fn eq(&self, other: &PascalStringLong<N>) -> bool {
+        let a: &str = self.as_ref();
+        let b = other.as_str();
+        a.eq(b)
+    }
+}
+
+impl<const M: usize, const N: usize> PartialEq<PascalStringLong<N>> for &mut PascalString<M> {
+    #[inline(always)]
+    fn eq(&self, other: &PascalStringLong<N>) -> bool {
+        let a: &str = self.as_ref();
+        let b = other.as_str();
+        a.eq(b)
+    }
+}
+
+impl<const M: usize, const CAPACITY: usize> TryFrom<PascalString<M>>
+    for PascalStringLong<CAPACITY>
+{
+    type Error = TryFromStrError;
+
+    #[inline]
+    fn try_from(value: PascalString<M>) -> Result<PascalStringLong<CAPACITY>, Self::Error> {
+        Self::try_from(value.as_str())
+    }
+}
+
+// -- Tests ----------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::mem;
+
+    use super::*;
+    use crate::DisplayExt;
+
+    #[test]
+    fn test_eq() {
+        use std::fmt::Write;
+
+        let s = String::from("abc");
+        let ps = PascalStringLong::<4>::try_from("abc").unwrap();
+
+        assert_eq!(ps, s);
+        #[cfg(feature = "alloc")]
+        assert_eq!(s, ps);
+        assert_eq!(ps, PascalString::<4>::try_from("abc").unwrap());
+        assert_eq!(&PascalString::<4>::try_from("abc").unwrap(), ps);
+
+        let s = String::from("abcd");
+        let mut ps = PascalStringLong::<4>::new();
+        write!(&mut ps, "abcd").unwrap();
+
+        assert_eq!(ps, s);
+    }
+
+    #[test]
+    fn test_ord() {
+        let ps1 = PascalStringLong::<4>::try_from("abc").unwrap();
+        let ps2 = PascalStringLong::<4>::try_from("abcd").unwrap();
+
+        assert!(ps1 < ps2);
+        assert!(ps1 <= ps2);
+        assert!(ps2 > ps1);
+        assert!(ps2 >= ps1);
+    }
+
+    #[test]
+    fn test_size() {
+        assert_eq!(mem::size_of::<PascalStringLong<0>>(), 4);
+        assert_eq!(mem::size_of::<PascalStringLong<1>>(), 8);
+        assert_eq!(mem::size_of::<PascalStringLong<4>>(), 8);
+        assert_eq!(mem::size_of::<PascalStringLong<5>>(), 12);
+        assert_eq!(mem::size_of::<PascalStringLong<4092>>(), 4096);
+    }
+
+    #[test]
+    fn test_capacity_above_255() {
+        let line = "x".repeat(1000);
+
+        let mut ps = PascalStringLong::<1024>::try_from(line.as_str()).unwrap();
+        assert_eq!(ps.len(), 1000);
+        assert_eq!(ps.capacity(), 1024);
+        assert_eq!(ps.as_str(), line);
+
+        ps.try_push_str(&"y".repeat(24)).unwrap();
+        assert_eq!(ps.len(), 1024);
+        assert_eq!(ps.try_push('z'), Err(TryFromStrError::TooLong));
+
+        ps.truncate(300);
+        assert_eq!(ps.len(), 300);
+        assert_eq!(ps.as_str(), &line[..300]);
+    }
+
+    #[test]
+    fn test_range_methods_above_255() {
+        let line = "ab€".repeat(100); // 500 bytes
+
+        let mut ps = PascalStringLong::<600>::try_from(line.as_str()).unwrap();
+        let tail = ps.split_off(300);
+        assert_eq!((ps.len(), tail.len()), (300, 200));
+
+        ps.try_replace_range(..5, "€€").unwrap();
+        assert_eq!(ps.len(), 301);
+        assert_eq!(ps.drain(11..).count(), 58 * 3);
+        assert_eq!(ps, "€€ab€");
+
+        ps.retain(|ch| ch != '€');
+        assert_eq!(ps, "ab");
+    }
+
+    #[test]
+    fn test_try_push_str_too_long_does_not_modify() {
+        let mut ps = PascalStringLong::<4>::try_from("ab").unwrap();
+
+        let err = ps.try_push_str("cde").unwrap_err();
+        assert_eq!(err, TryFromStrError::TooLong);
+        assert_eq!(ps.as_str(), "ab");
+    }
+
+    #[test]
+    fn test_push_str_truncated_respects_utf8_boundary() {
+        let mut ps = PascalStringLong::<4>::new();
+
+        // "€" is 3 bytes. "€a" is 4 bytes. "€ab" is 5 bytes.
+        let remainder = ps.push_str_truncated("€ab");
+        assert_eq!(ps.as_str(), "€a");
+        assert_eq!(remainder, "b");
+
+        let ps = PascalStringLong::<4>::from_str_truncated("€ab");
+        assert_eq!(ps.as_str(), "€a");
+    }
+
+    #[test]
+    fn test_truncate_requires_char_boundary() {
+        let ps = PascalStringLong::<4>::try_from("€a").unwrap();
+
+        // 1 is in the middle of the 3-byte UTF-8 sequence for '€'.
+        let result = std::panic::catch_unwind(move || {
+            let mut ps = ps;
+            ps.truncate(1);
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_insert_str_and_remove_unicode_boundaries() {
+        let mut ps = PascalStringLong::<8>::try_from("ab").unwrap();
+        ps.insert_str_expect_capacity(1, "€"); // 3 bytes
+        assert_eq!(ps.as_str(), "a€b");
+
+        assert_eq!(
+            ps.try_insert_str(2, "x"),
+            Err(InsertError::NotCharBoundary { idx: 2 })
+        );
+        assert_eq!(
+            ps.try_remove(9),
+            Err(RemoveError::OutOfBounds { idx: 9, len: 5 })
+        );
+
+        let removed = ps.remove(1);
+        assert_eq!(removed, '€');
+        assert_eq!(ps.as_str(), "ab");
+        assert_eq!(ps.pop(), Some('b'));
+        assert_eq!(ps.as_str(), "a");
+    }
+
+    #[test]
+    fn test_try_from_bytes() {
+        let err = PascalStringLong::<8>::try_from(&[0xff_u8][..]).unwrap_err();
+        assert!(matches!(err, TryFromBytesError::Utf8Error(_)));
+
+        let err = PascalStringLong::<2>::try_from(&b"abc"[..]).unwrap_err();
+        assert_eq!(err, TryFromBytesError::TooLong);
+    }
+
+    #[test]
+    fn test_try_from_pascal_string() {
+        let ps = PascalString::<4>::try_from("abcd").unwrap();
+        assert_eq!(PascalStringLong::<300>::try_from(ps).unwrap(), "abcd");
+        assert_eq!(
+            PascalStringLong::<3>::try_from(ps).unwrap_err(),
+            TryFromStrError::TooLong
+        );
+    }
+
+    #[test]
+    fn test_to_fmt() {
+        let ps: PascalStringLong<300> = format_args!("{:0>280}", 1).to_fmt();
+        assert_eq!(ps.len(), 280);
+        assert!(format_args!("{:0>301}", 1)
+            .try_to_fmt::<PascalStringLong<300>>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_debug() {
+        let ps = PascalStringLong::<1024>::try_from("ab").unwrap();
+        assert_eq!(format!("{ps:?}"), r#"PascalStringLong<1024>("ab")"#);
+    }
+
+    #[test]
+    fn test_try_from_str_const() {
+        const PS: Option<PascalStringLong<4>> = PascalStringLong::<4>::try_from_str_const("ab");
+        assert_eq!(PS.unwrap().as_str(), "ab");
+
+        const TOO_LONG: Option<PascalStringLong<2>> =
+            PascalStringLong::<2>::try_from_str_const("abc");
+        assert!(TOO_LONG.is_none());
+    }
+}
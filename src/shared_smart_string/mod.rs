@@ -0,0 +1,464 @@
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use core::borrow::Borrow;
+use core::cmp;
+use core::convert::Infallible;
+use core::fmt;
+use core::hash::Hash;
+use core::hash::Hasher;
+use core::mem;
+use core::ops;
+
+use crate::macros::impl_reverse_eq_for_str_types;
+use crate::pascal_string;
+use crate::smart_string::DemotionPolicy;
+use crate::DisplayExt;
+use crate::PascalString;
+use crate::PascalStringLong;
+use crate::SmartString;
+
+#[cfg(feature = "serde")]
+mod with_serde;
+
+/// The largest stack capacity that keeps `SharedSmartString` as small as `Arc<str>` plus one word
+/// (22 bytes on 64-bit platforms, i.e. a 24-byte value).
+pub const DEFAULT_CAPACITY: usize = mem::size_of::<Arc<str>>() + mem::size_of::<usize>() - 2;
+
+/// An immutable-by-default string that stores short values on the stack and longer values in a shared `Arc<str>`.
+///
+/// Cloning is O(1): a stack-stored value is copied, a heap-stored one only bumps the reference count.
+/// Mutation goes through [`make_mut`](Self::make_mut), which copies a shared heap buffer on write.
+#[derive(Clone)]
+pub struct SharedSmartString<const N: usize = DEFAULT_CAPACITY> {
+    repr: Repr<N>,
+}
+
+#[derive(Clone)]
+enum Repr<const N: usize> {
+    Heap(Arc<str>),
+    Stack(PascalString<N>),
+}
+
+impl<const N: usize> SharedSmartString<N> {
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            repr: Repr::Stack(PascalString::new()),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.as_str().len()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.as_str().is_empty()
+    }
+
+    #[inline]
+    pub fn is_heap(&self) -> bool {
+        matches!(self.repr, Repr::Heap(_))
+    }
+
+    #[inline]
+    pub fn is_stack(&self) -> bool {
+        matches!(self.repr, Repr::Stack(_))
+    }
+
+    /// Returns `true` if both values share the same heap buffer.
+    ///
+    /// Stack-stored values never share anything, so this is `false` for them.
+    #[inline]
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.repr, &other.repr) {
+            (Repr::Heap(a), Repr::Heap(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    /// Returns a mutable reference to the contents, copying a shared heap buffer first (copy-on-write).
+    ///
+    /// The length can't change through `&mut str`; convert into `SmartString` or `String` to grow or shrink.
+    #[inline]
+    pub fn make_mut(&mut self) -> &mut str {
+        match &mut self.repr {
+            Repr::Stack(s) => s.as_mut_str(),
+            Repr::Heap(arc) => {
+                if Arc::get_mut(arc).is_none() {
+                    *arc = Arc::from(&**arc);
+                }
+                Arc::get_mut(arc).expect("just made unique")
+            }
+        }
+    }
+
+    /// Returns the shared heap buffer, or a new one for a stack-stored value.
+    #[inline]
+    #[must_use]
+    pub fn into_arc(self) -> Arc<str> {
+        match self.repr {
+            Repr::Heap(s) => s,
+            Repr::Stack(s) => Arc::from(s.as_str()),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn into_string(self) -> String {
+        self.as_str().to_string()
+    }
+}
+
+// -- Common traits --------------------------------------------------------------------------------
+
+impl<const N: usize> Default for SharedSmartString<N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: ops::Deref<Target = str> + ?Sized, const CAPACITY: usize> PartialEq<T>
+    for SharedSmartString<CAPACITY>
+{
+    #[inline(always)]
+    fn eq(&self, other: &T) -> bool {
+        self.as_str().eq(other.deref())
+    }
+}
+
+impl_reverse_eq_for_str_types!(
+    [const N: usize] SharedSmartString<N>;
+    String, str, Cow<'_, str>, Box<str>, Rc<str>, Arc<str>
+);
+
+impl<const N: usize> Eq for SharedSmartString<N> {}
+
+impl<T: ops::Deref<Target = str>, const N: usize> PartialOrd<T> for SharedSmartString<N> {
+    #[inline(always)]
+    fn partial_cmp(&self, other: &T) -> Option<cmp::Ordering> {
+        self.as_str().partial_cmp(other.deref())
+    }
+}
+
+impl<const N: usize> Ord for SharedSmartString<N> {
+    #[inline(always)]
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl<const N: usize> Hash for SharedSmartString<N> {
+    #[inline(always)]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+// -- Formatting -----------------------------------------------------------------------------------
+
+impl<const N: usize> fmt::Debug for SharedSmartString<N> {
+    #[inline(always)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name: PascalString<39> = format_args!("SharedSmartString<{N}>")
+            .try_to_fmt()
+            .unwrap_or_else(|_| "SharedSmartString<?>".to_fmt());
+        f.debug_tuple(&name).field(&self.as_str()).finish()
+    }
+}
+
+impl<const N: usize> fmt::Display for SharedSmartString<N> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+// -- Reference ------------------------------------------------------------------------------------
+
+impl<const N: usize> ops::Deref for SharedSmartString<N> {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        match &self.repr {
+            Repr::Heap(s) => s,
+            Repr::Stack(s) => s,
+        }
+    }
+}
+
+impl<const N: usize> Borrow<str> for SharedSmartString<N> {
+    #[inline(always)]
+    fn borrow(&self) -> &str {
+        self
+    }
+}
+
+impl<const N: usize> AsRef<str> for SharedSmartString<N> {
+    #[inline(always)]
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for SharedSmartString<N> {
+    #[inline(always)]
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+// -- Conversion -----------------------------------------------------------------------------------
+
+impl<const N: usize> From<&str> for SharedSmartString<N> {
+    #[inline]
+    fn from(s: &str) -> Self {
+        let repr = PascalString::try_from(s)
+            .map(Repr::Stack)
+            .unwrap_or_else(|pascal_string::TryFromStrError::TooLong| Repr::Heap(Arc::from(s)));
+        Self { repr }
+    }
+}
+
+impl<const N: usize> From<Arc<str>> for SharedSmartString<N> {
+    /// Stores a short value on the stack; keeps sharing the buffer of a longer one.
+    #[inline]
+    fn from(s: Arc<str>) -> Self {
+        let repr = match PascalString::try_from(s.as_ref()) {
+            Ok(stack) => Repr::Stack(stack),
+            Err(pascal_string::TryFromStrError::TooLong) => Repr::Heap(s),
+        };
+        Self { repr }
+    }
+}
+
+impl<const N: usize> From<&Arc<str>> for SharedSmartString<N> {
+    #[inline]
+    fn from(s: &Arc<str>) -> Self {
+        Self::from(Arc::clone(s))
+    }
+}
+
+impl<const N: usize> From<Rc<str>> for SharedSmartString<N> {
+    #[inline]
+    fn from(s: Rc<str>) -> Self {
+        Self::from(s.as_ref())
+    }
+}
+
+impl<const N: usize> From<&Rc<str>> for SharedSmartString<N> {
+    #[inline]
+    fn from(s: &Rc<str>) -> Self {
+        Self::from(s.as_ref())
+    }
+}
+
+impl<const N: usize> From<String> for SharedSmartString<N> {
+    #[inline]
+    fn from(s: String) -> Self {
+        Self::from(s.as_str())
+    }
+}
+
+impl<const N: usize> From<&String> for SharedSmartString<N> {
+    #[inline]
+    fn from(s: &String) -> Self {
+        Self::from(s.as_str())
+    }
+}
+
+impl<const N: usize> From<Box<str>> for SharedSmartString<N> {
+    #[inline]
+    fn from(s: Box<str>) -> Self {
+        Self::from(s.as_ref())
+    }
+}
+
+impl<const N: usize> From<Cow<'_, str>> for SharedSmartString<N> {
+    #[inline]
+    fn from(s: Cow<'_, str>) -> Self {
+        Self::from(s.as_ref())
+    }
+}
+
+impl<const N: usize> From<char> for SharedSmartString<N> {
+    #[inline]
+    fn from(ch: char) -> Self {
+        Self::from(ch.encode_utf8(&mut [0; 4]) as &str)
+    }
+}
+
+impl<const M: usize, const N: usize> From<PascalString<M>> for SharedSmartString<N> {
+    #[inline]
+    fn from(s: PascalString<M>) -> Self {
+        Self::from(s.as_str())
+    }
+}
+
+impl<const M: usize, const N: usize> From<PascalStringLong<M>> for SharedSmartString<N> {
+    #[inline]
+    fn from(s: PascalStringLong<M>) -> Self {
+        Self::from(s.as_str())
+    }
+}
+
+impl<const M: usize, const N: usize, P: DemotionPolicy> From<SmartString<M, P>>
+    for SharedSmartString<N>
+{
+    #[inline]
+    fn from(s: SmartString<M, P>) -> Self {
+        Self::from(s.as_str())
+    }
+}
+
+impl<const M: usize, const N: usize, P: DemotionPolicy> From<SharedSmartString<M>>
+    for SmartString<N, P>
+{
+    #[inline]
+    fn from(s: SharedSmartString<M>) -> Self {
+        Self::from(s.as_str())
+    }
+}
+
+impl<const N: usize> From<SharedSmartString<N>> for Arc<str> {
+    #[inline]
+    fn from(s: SharedSmartString<N>) -> Self {
+        s.into_arc()
+    }
+}
+
+impl<const N: usize> From<SharedSmartString<N>> for String {
+    #[inline]
+    fn from(s: SharedSmartString<N>) -> Self {
+        s.into_string()
+    }
+}
+
+impl<const N: usize> core::str::FromStr for SharedSmartString<N> {
+    type Err = Infallible;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(s))
+    }
+}
+
+// -- Tests ----------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn test_size() {
+        assert_eq!(DEFAULT_CAPACITY, 22);
+        assert_eq!(mem::size_of::<SharedSmartString>(), 24);
+        assert_eq!(mem::size_of::<SharedSmartString<23>>(), 32);
+    }
+
+    #[test]
+    fn test_from_str_picks_stack_or_heap() {
+        let s = SharedSmartString::<4>::from("abcd");
+        assert!(s.is_stack());
+
+        let s = SharedSmartString::<4>::from("abcde");
+        assert!(s.is_heap());
+        assert_eq!(s, "abcde");
+    }
+
+    #[test]
+    fn test_clone_shares_heap_buffer() {
+        let a = SharedSmartString::<4>::from("abcde");
+        let b = a.clone();
+        assert!(a.ptr_eq(&b));
+        assert_eq!(a, b);
+
+        let a = SharedSmartString::<4>::from("abc");
+        let b = a.clone();
+        assert!(!a.ptr_eq(&b));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_from_arc_keeps_sharing() {
+        let arc: Arc<str> = Arc::from("abcde");
+        let s = SharedSmartString::<4>::from(&arc);
+        assert!(s.is_heap());
+        assert_eq!(Arc::strong_count(&arc), 2);
+
+        let back: Arc<str> = s.into();
+        assert!(Arc::ptr_eq(&arc, &back));
+
+        let arc: Arc<str> = Arc::from("abc");
+        let s = SharedSmartString::<4>::from(arc);
+        assert!(s.is_stack());
+    }
+
+    #[test]
+    fn test_make_mut_copies_on_write() {
+        let mut a = SharedSmartString::<4>::from("abcde");
+        let b = a.clone();
+
+        a.make_mut().make_ascii_uppercase();
+        assert_eq!(a, "ABCDE");
+        assert_eq!(b, "abcde");
+        assert!(!a.ptr_eq(&b));
+
+        // Unique now: no more copies.
+        let ptr = a.as_ptr();
+        a.make_mut().make_ascii_lowercase();
+        assert_eq!(a.as_ptr(), ptr);
+
+        let mut s = SharedSmartString::<4>::from("ab");
+        s.make_mut().make_ascii_uppercase();
+        assert_eq!(s, "AB");
+    }
+
+    #[test]
+    fn test_hash_eq_ord() {
+        let set: HashSet<SharedSmartString<4>> =
+            ["ab", "abcde"].iter().map(|&s| s.into()).collect();
+        assert!(set.contains("ab"));
+        assert!(set.contains("abcde"));
+
+        let a = SharedSmartString::<4>::from("ab");
+        let b = SharedSmartString::<4>::from("abcde");
+        assert!(a < b);
+        assert_eq!("ab", a);
+        assert_eq!(String::from("abcde"), b);
+    }
+
+    #[test]
+    fn test_smart_string_roundtrip() {
+        let s = SmartString::<4>::from("abcde");
+        let shared = SharedSmartString::<4>::from(s);
+        let s: SmartString<4> = shared.into();
+        assert_eq!(s, "abcde");
+    }
+
+    #[test]
+    fn test_debug() {
+        let s = SharedSmartString::<4>::from("ab");
+        assert_eq!(format!("{s:?}"), r#"SharedSmartString<4>("ab")"#);
+        assert_eq!(format!("{s}"), "ab");
+    }
+}
@@ -0,0 +1,80 @@
+use core::fmt;
+
+use serde::de::Error;
+use serde::de::Unexpected;
+use serde::de::Visitor;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+
+use crate::SharedSmartString;
+
+struct StringVisitor<const CAPACITY: usize>;
+
+// -------------------------------------------------------------------------------------------------
+
+impl<'de, const CAPACITY: usize> Visitor<'de> for StringVisitor<CAPACITY> {
+    type Value = SharedSmartString<CAPACITY>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(SharedSmartString::from(v))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        match core::str::from_utf8(v) {
+            Ok(s) => self.visit_str(s),
+            Err(_) => Err(Error::invalid_value(Unexpected::Bytes(v), &self)),
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+impl<const CAPACITY: usize> Serialize for SharedSmartString<CAPACITY> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self)
+    }
+}
+
+impl<'de, const CAPACITY: usize> Deserialize<'de> for SharedSmartString<CAPACITY> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(StringVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_as_string() {
+        let s = SharedSmartString::<4>::from("abcde");
+        let json = serde_json::to_string(&s).unwrap();
+        assert_eq!(json, r#""abcde""#);
+    }
+
+    #[test]
+    fn test_deserialize_picks_stack_or_heap() {
+        let s: SharedSmartString<4> = serde_json::from_str(r#""abcd""#).unwrap();
+        assert!(s.is_stack());
+        assert_eq!(s.as_str(), "abcd");
+
+        let s: SharedSmartString<4> = serde_json::from_str(r#""abcde""#).unwrap();
+        assert!(s.is_heap());
+        assert_eq!(s.as_str(), "abcde");
+    }
+}
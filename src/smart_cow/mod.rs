@@ -0,0 +1,361 @@
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::sync::Arc;
+use core::borrow::Borrow;
+use core::cmp;
+use core::fmt;
+use core::hash::Hash;
+use core::hash::Hasher;
+use core::ops;
+
+use crate::macros::impl_reverse_eq_for_str_types;
+use crate::smart_string::DemotionPolicy;
+use crate::smart_string::NeverDemote;
+use crate::smart_string::DEFAULT_CAPACITY;
+use crate::SmartString;
+
+#[cfg(feature = "serde")]
+mod with_serde;
+
+/// A string that borrows a `&'a str` without copying, or owns a [`SmartString`].
+///
+/// Meant for values that are mostly read as-is (e.g. static lookup tables): constructing a `SmartCow` from a
+/// `&'a str` is free, and the contents get copied into stack or heap storage only on the first mutation
+/// (see [`to_mut`](Self::to_mut)).
+pub enum SmartCow<'a, const N: usize = DEFAULT_CAPACITY, P = NeverDemote> {
+    Borrowed(&'a str),
+    Owned(SmartString<N, P>),
+}
+
+impl<'a, const N: usize, P> SmartCow<'a, N, P> {
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self::Borrowed("")
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn borrowed(s: &'a str) -> Self {
+        Self::Borrowed(s)
+    }
+
+    #[inline]
+    pub const fn is_borrowed(&self) -> bool {
+        matches!(self, Self::Borrowed(_))
+    }
+
+    #[inline]
+    pub const fn is_owned(&self) -> bool {
+        !self.is_borrowed()
+    }
+}
+
+impl<'a, const N: usize, P: DemotionPolicy> SmartCow<'a, N, P> {
+    #[inline]
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.as_str().len()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.as_str().is_empty()
+    }
+
+    /// Returns the owned string, copying the borrowed one first if needed.
+    #[inline]
+    pub fn to_mut(&mut self) -> &mut SmartString<N, P> {
+        if let Self::Borrowed(s) = *self {
+            *self = Self::Owned(SmartString::from(s));
+        }
+        match self {
+            Self::Owned(s) => s,
+            Self::Borrowed(_) => unreachable!(),
+        }
+    }
+
+    /// Extracts the owned string, copying the borrowed one if needed.
+    #[inline]
+    #[must_use]
+    pub fn into_owned(self) -> SmartString<N, P> {
+        match self {
+            Self::Borrowed(s) => SmartString::from(s),
+            Self::Owned(s) => s,
+        }
+    }
+
+    #[inline]
+    pub fn push_str(&mut self, string: &str) {
+        self.to_mut().push_str(string);
+    }
+
+    #[inline]
+    pub fn push(&mut self, ch: char) {
+        self.to_mut().push(ch);
+    }
+}
+
+// -- Common traits --------------------------------------------------------------------------------
+
+impl<const N: usize, P: DemotionPolicy> Clone for SmartCow<'_, N, P> {
+    #[inline]
+    fn clone(&self) -> Self {
+        match self {
+            Self::Borrowed(s) => Self::Borrowed(s),
+            Self::Owned(s) => Self::Owned(s.clone()),
+        }
+    }
+}
+
+impl<const N: usize, P> Default for SmartCow<'_, N, P> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: ops::Deref<Target = str> + ?Sized, const N: usize, P: DemotionPolicy> PartialEq<T>
+    for SmartCow<'_, N, P>
+{
+    #[inline(always)]
+    fn eq(&self, other: &T) -> bool {
+        self.as_str().eq(other.deref())
+    }
+}
+
+impl_reverse_eq_for_str_types!(
+    [const N: usize, P: DemotionPolicy] SmartCow<'_, N, P>;
+    String, str, Cow<'_, str>, Box<str>, Rc<str>, Arc<str>
+);
+
+impl<const N: usize, P: DemotionPolicy> Eq for SmartCow<'_, N, P> {}
+
+impl<T: ops::Deref<Target = str>, const N: usize, P: DemotionPolicy> PartialOrd<T>
+    for SmartCow<'_, N, P>
+{
+    #[inline(always)]
+    fn partial_cmp(&self, other: &T) -> Option<cmp::Ordering> {
+        self.as_str().partial_cmp(other.deref())
+    }
+}
+
+impl<const N: usize, P: DemotionPolicy> Ord for SmartCow<'_, N, P> {
+    #[inline(always)]
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl<const N: usize, P: DemotionPolicy> Hash for SmartCow<'_, N, P> {
+    #[inline(always)]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+// -- Formatting -----------------------------------------------------------------------------------
+
+impl<const N: usize, P: DemotionPolicy> fmt::Debug for SmartCow<'_, N, P> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Borrowed(s) => f.debug_tuple("Borrowed").field(s).finish(),
+            Self::Owned(s) => f.debug_tuple("Owned").field(s).finish(),
+        }
+    }
+}
+
+impl<const N: usize, P: DemotionPolicy> fmt::Display for SmartCow<'_, N, P> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize, P: DemotionPolicy> fmt::Write for SmartCow<'_, N, P> {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+}
+
+// -- Reference ------------------------------------------------------------------------------------
+
+impl<const N: usize, P: DemotionPolicy> ops::Deref for SmartCow<'_, N, P> {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Borrowed(s) => s,
+            Self::Owned(s) => s,
+        }
+    }
+}
+
+impl<const N: usize, P: DemotionPolicy> Borrow<str> for SmartCow<'_, N, P> {
+    #[inline(always)]
+    fn borrow(&self) -> &str {
+        self
+    }
+}
+
+impl<const N: usize, P: DemotionPolicy> AsRef<str> for SmartCow<'_, N, P> {
+    #[inline(always)]
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl<const N: usize, P: DemotionPolicy> AsRef<[u8]> for SmartCow<'_, N, P> {
+    #[inline(always)]
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+// -- Conversion -----------------------------------------------------------------------------------
+
+impl<'a, const N: usize, P> From<&'a str> for SmartCow<'a, N, P> {
+    #[inline]
+    fn from(s: &'a str) -> Self {
+        Self::Borrowed(s)
+    }
+}
+
+impl<'a, const N: usize, P> From<&'a String> for SmartCow<'a, N, P> {
+    #[inline]
+    fn from(s: &'a String) -> Self {
+        Self::Borrowed(s)
+    }
+}
+
+impl<const N: usize, P> From<SmartString<N, P>> for SmartCow<'_, N, P> {
+    #[inline]
+    fn from(s: SmartString<N, P>) -> Self {
+        Self::Owned(s)
+    }
+}
+
+impl<const N: usize, P: DemotionPolicy> From<String> for SmartCow<'_, N, P> {
+    #[inline]
+    fn from(s: String) -> Self {
+        Self::Owned(SmartString::from(s))
+    }
+}
+
+impl<'a, const N: usize, P: DemotionPolicy> From<Cow<'a, str>> for SmartCow<'a, N, P> {
+    #[inline]
+    fn from(s: Cow<'a, str>) -> Self {
+        match s {
+            Cow::Borrowed(s) => Self::Borrowed(s),
+            Cow::Owned(s) => Self::from(s),
+        }
+    }
+}
+
+impl<const N: usize, P: DemotionPolicy> From<SmartCow<'_, N, P>> for SmartString<N, P> {
+    #[inline]
+    fn from(s: SmartCow<'_, N, P>) -> Self {
+        s.into_owned()
+    }
+}
+
+impl<const N: usize, P: DemotionPolicy> From<SmartCow<'_, N, P>> for String {
+    #[inline]
+    fn from(s: SmartCow<'_, N, P>) -> Self {
+        match s {
+            SmartCow::Borrowed(s) => String::from(s),
+            SmartCow::Owned(s) => s.into_string(),
+        }
+    }
+}
+
+// -- Tests ----------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use core::fmt::Write;
+
+    use super::*;
+
+    static TABLE: [SmartCow<'static, 4>; 2] = [
+        SmartCow::borrowed("zero"),
+        SmartCow::borrowed("one or more"),
+    ];
+
+    #[test]
+    fn test_borrowed_does_not_copy() {
+        let s = "a fairly long string";
+        let cow = SmartCow::<4>::from(s);
+        assert!(cow.is_borrowed());
+        assert_eq!(cow.as_ptr(), s.as_ptr());
+        assert_eq!(cow, s);
+
+        assert!(TABLE.iter().all(SmartCow::is_borrowed));
+        assert_eq!(TABLE[1], "one or more");
+    }
+
+    #[test]
+    fn test_first_mutation_promotes() {
+        let mut cow = TABLE[0].clone();
+        cow.push('!');
+        assert!(cow.is_owned());
+        assert_eq!(cow, "zero!");
+        assert!(cow.to_mut().is_heap());
+
+        let mut cow = SmartCow::<4>::borrowed("ab");
+        write!(cow, "{}", 1).unwrap();
+        assert!(cow.is_owned());
+        assert!(cow.to_mut().is_stack());
+        assert_eq!(cow, "ab1");
+    }
+
+    #[test]
+    fn test_into_owned() {
+        let s = SmartCow::<4>::borrowed("abc").into_owned();
+        assert!(s.is_stack());
+        assert_eq!(s, "abc");
+
+        let owned = SmartString::<4>::from("abcde");
+        let ptr = owned.as_ptr();
+        let s = SmartCow::from(owned).into_owned();
+        assert_eq!(s.as_ptr(), ptr);
+    }
+
+    #[test]
+    fn test_from_cow() {
+        let cow = SmartCow::<4>::from(Cow::Borrowed("abc"));
+        assert!(cow.is_borrowed());
+
+        let cow = SmartCow::<4>::from(Cow::<str>::Owned(String::from("abc")));
+        assert!(cow.is_owned());
+    }
+
+    #[test]
+    fn test_eq_ord_hash_debug() {
+        let a = SmartCow::<4>::borrowed("ab");
+        let b = SmartCow::<4>::from(SmartString::from("ab"));
+        assert_eq!(a, b);
+        assert!(a < SmartCow::<4>::borrowed("b"));
+        assert_eq!("ab", a);
+        assert_eq!(format!("{a:?}"), r#"Borrowed("ab")"#);
+        assert_eq!(format!("{b:?}"), r#"Owned(SmartString<4>("ab"))"#);
+        assert_eq!(format!("{a}"), "ab");
+
+        let set: std::collections::HashSet<SmartCow<4>> = [a, b].into_iter().collect();
+        assert_eq!(set.len(), 1);
+        assert!(set.contains("ab"));
+    }
+}
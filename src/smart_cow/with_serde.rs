@@ -0,0 +1,107 @@
+use core::fmt;
+use core::marker::PhantomData;
+
+use serde::de::Error;
+use serde::de::Unexpected;
+use serde::de::Visitor;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+
+use crate::smart_string::DemotionPolicy;
+use crate::SmartCow;
+use crate::SmartString;
+
+struct StringVisitor<'a, const CAPACITY: usize, P>(PhantomData<(&'a str, P)>);
+
+// -------------------------------------------------------------------------------------------------
+
+impl<'de: 'a, 'a, const CAPACITY: usize, P: DemotionPolicy> Visitor<'de>
+    for StringVisitor<'a, CAPACITY, P>
+{
+    type Value = SmartCow<'a, CAPACITY, P>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string")
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(SmartCow::Borrowed(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(SmartCow::Owned(SmartString::from(v)))
+    }
+
+    fn visit_borrowed_bytes<E>(self, v: &'de [u8]) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        match core::str::from_utf8(v) {
+            Ok(s) => self.visit_borrowed_str(s),
+            Err(_) => Err(Error::invalid_value(Unexpected::Bytes(v), &self)),
+        }
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        match core::str::from_utf8(v) {
+            Ok(s) => self.visit_str(s),
+            Err(_) => Err(Error::invalid_value(Unexpected::Bytes(v), &self)),
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+impl<const CAPACITY: usize, P: DemotionPolicy> Serialize for SmartCow<'_, CAPACITY, P> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self)
+    }
+}
+
+/// Borrows from the input when the deserializer can lend a `&'de str`, and copies otherwise.
+impl<'de: 'a, 'a, const CAPACITY: usize, P: DemotionPolicy> Deserialize<'de>
+    for SmartCow<'a, CAPACITY, P>
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(StringVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_as_string() {
+        let s = SmartCow::<4>::borrowed("abcde");
+        let json = serde_json::to_string(&s).unwrap();
+        assert_eq!(json, r#""abcde""#);
+    }
+
+    #[test]
+    fn test_deserialize_borrows_when_possible() {
+        let json = String::from(r#""abcde""#);
+        let s: SmartCow<4> = serde_json::from_str(&json).unwrap();
+        assert!(s.is_borrowed());
+        assert_eq!(s, "abcde");
+
+        // Escapes can't be borrowed.
+        let s: SmartCow<4> = serde_json::from_str(r#""a\nb""#).unwrap();
+        assert!(s.is_owned());
+        assert_eq!(s, "a\nb");
+    }
+}
@@ -0,0 +1,184 @@
+use core::fmt;
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+use core::str::Chars;
+
+use crate::allocator::Allocator;
+use crate::allocator::Global;
+use crate::smart_string::DemotionPolicy;
+use crate::smart_string::NeverDemote;
+use crate::SmartString;
+
+/// A draining iterator for `SmartString`.
+///
+/// This struct is created by the [`drain`] method on [`SmartString`]. See its documentation for more.
+///
+/// It is `Send` and `Sync` only if the string is, since dropping it may free memory through the allocator:
+///
+/// ```compile_fail
+/// use smart_string::smart_string::DemotionPolicy;
+/// use smart_string::SmartString;
+///
+/// // `!Send`, like the string it parametrizes.
+/// struct LocalPolicy(*const ());
+///
+/// impl DemotionPolicy for LocalPolicy {
+///     fn demote_on_shrink(_: usize, _: usize) -> bool { true }
+///     fn demote_on_shrink_to(_: usize, _: usize) -> bool { true }
+/// }
+///
+/// fn assert_send<T: Send>(_: T) {}
+///
+/// let mut s = SmartString::<8, LocalPolicy>::new();
+/// assert_send(s.drain(..));
+/// ```
+///
+/// [`drain`]: SmartString::drain
+pub struct Drain<'a, const N: usize, P: DemotionPolicy = NeverDemote, A: Allocator = Global> {
+    /// Will be used as `&'a mut SmartString` in the destructor.
+    string: *mut SmartString<N, P, A>,
+    /// Start of part to remove.
+    start: usize,
+    /// End of part to remove.
+    end: usize,
+    /// Current remaining range to remove.
+    iter: Chars<'a>,
+    marker: PhantomData<&'a mut SmartString<N, P, A>>,
+}
+
+// SAFETY: `Drain` is semantically a `&'a mut SmartString<N, P, A>` plus a `Chars<'a>`; the former is `Send` when the
+// string is (its destructor may free memory through `A` on the thread that holds the drain), the latter always is.
+unsafe impl<const N: usize, P: DemotionPolicy, A: Allocator> Send for Drain<'_, N, P, A> where
+    SmartString<N, P, A>: Send
+{
+}
+// SAFETY: `Drain` is semantically a `&'a mut SmartString<N, P, A>` plus a `Chars<'a>`; the former is `Sync` when the
+// string is, the latter always is.
+unsafe impl<const N: usize, P: DemotionPolicy, A: Allocator> Sync for Drain<'_, N, P, A> where
+    SmartString<N, P, A>: Sync
+{
+}
+
+impl<'a, const N: usize, P: DemotionPolicy, A: Allocator> Drain<'a, N, P, A> {
+    /// # Safety
+    ///
+    /// `start..end` must be a valid range on char boundaries of `string`.
+    #[inline]
+    pub(super) unsafe fn new(
+        string: &'a mut SmartString<N, P, A>,
+        start: usize,
+        end: usize,
+    ) -> Self {
+        let string: *mut SmartString<N, P, A> = string;
+        // SAFETY: the caller guarantees the range is valid; the buffer is neither moved nor mutated
+        // until `Drain` is dropped, since `string` stays mutably borrowed for `'a`.
+        let iter = (*string).as_str().get_unchecked(start..end).chars();
+        Self {
+            string,
+            start,
+            end,
+            iter,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns the remaining (sub)string of this iterator as a slice.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        self.iter.as_str()
+    }
+}
+
+impl<const N: usize, P: DemotionPolicy, A: Allocator> Drop for Drain<'_, N, P, A> {
+    fn drop(&mut self) {
+        // SAFETY: `string` was a `&'a mut SmartString<N, P, A>` and `iter` is not used anymore.
+        let string = unsafe { &mut *self.string };
+        string.remove_range(self.start, self.end);
+    }
+}
+
+impl<const N: usize, P: DemotionPolicy, A: Allocator> fmt::Debug for Drain<'_, N, P, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Drain").field(&self.as_str()).finish()
+    }
+}
+
+impl<const N: usize, P: DemotionPolicy, A: Allocator> AsRef<str> for Drain<'_, N, P, A> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize, P: DemotionPolicy, A: Allocator> AsRef<[u8]> for Drain<'_, N, P, A> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_str().as_bytes()
+    }
+}
+
+impl<const N: usize, P: DemotionPolicy, A: Allocator> Iterator for Drain<'_, N, P, A> {
+    type Item = char;
+
+    #[inline]
+    fn next(&mut self) -> Option<char> {
+        self.iter.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+
+    #[inline]
+    fn last(mut self) -> Option<char> {
+        self.next_back()
+    }
+}
+
+impl<const N: usize, P: DemotionPolicy, A: Allocator> DoubleEndedIterator for Drain<'_, N, P, A> {
+    #[inline]
+    fn next_back(&mut self) -> Option<char> {
+        self.iter.next_back()
+    }
+}
+
+impl<const N: usize, P: DemotionPolicy, A: Allocator> FusedIterator for Drain<'_, N, P, A> {}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use super::*;
+
+    #[test]
+    fn test_drain_removes_range_on_drop() {
+        let mut s = SmartString::<8>::from("a€bc");
+        let drained: String = s.drain(1..4).collect();
+        assert_eq!(drained, "€");
+        assert_eq!(s, "abc");
+    }
+
+    #[test]
+    fn test_drain_removes_range_if_not_exhausted() {
+        let mut s = SmartString::<4>::from("abcdef");
+        {
+            let mut drain = s.drain(..3);
+            assert_eq!(drain.next(), Some('a'));
+            assert_eq!(drain.next_back(), Some('c'));
+            assert_eq!(drain.as_str(), "b");
+        }
+        assert_eq!(s, "def");
+    }
+
+    #[test]
+    fn test_drain_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Drain<'static, 8>>();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_drain_panics_on_char_boundary() {
+        let mut s = SmartString::<8>::from("a€");
+        s.drain(..2);
+    }
+}
@@ -0,0 +1,232 @@
+use alloc::string::String;
+use core::ops;
+use core::str::from_utf8_unchecked;
+use core::str::from_utf8_unchecked_mut;
+
+use crate::allocator::from_vec;
+use crate::allocator::into_vec;
+use crate::allocator::Allocator;
+use crate::allocator::Global;
+use crate::allocator::TryReserveError;
+use crate::allocator::VecIn;
+
+/// The heap storage of `SmartString`: a `String` that allocates through `A`.
+///
+/// Only the `String` methods `SmartString` delegates to are mirrored; they panic exactly where `String`'s do.
+///
+/// Invariant: `buf` is always valid UTF-8.
+pub(super) struct HeapString<A: Allocator> {
+    buf: VecIn<u8, A>,
+}
+
+impl<A: Allocator> HeapString<A> {
+    #[inline]
+    pub fn try_with_capacity_in(capacity: usize, alloc: A) -> Result<Self, TryReserveError> {
+        let mut buf = VecIn::new_in(alloc);
+        buf.try_reserve_exact(capacity)?;
+        Ok(Self { buf })
+    }
+
+    #[inline]
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+        Self {
+            buf: VecIn::with_capacity_in(capacity, alloc),
+        }
+    }
+
+    /// # Safety
+    ///
+    /// The raw parts must come from [`into_raw_parts`](Self::into_raw_parts) of a `HeapString` whose allocator is
+    /// `alloc` (or refers to the same allocator).
+    #[inline]
+    pub unsafe fn from_raw_parts_in(ptr: *mut u8, len: usize, capacity: usize, alloc: A) -> Self {
+        Self {
+            buf: VecIn::from_raw_parts_in(ptr, len, capacity, alloc),
+        }
+    }
+
+    /// Decomposes the string into `(ptr, len, capacity)`, dropping the allocator; the caller takes over the buffer.
+    #[inline]
+    pub fn into_raw_parts(self) -> (*mut u8, usize, usize) {
+        let (ptr, len, capacity, _) = self.buf.into_raw_parts_with_alloc();
+        (ptr, len, capacity)
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        // SAFETY: `buf` is always valid UTF-8.
+        unsafe { from_utf8_unchecked(&self.buf) }
+    }
+
+    #[inline]
+    pub fn as_mut_str(&mut self) -> &mut str {
+        // SAFETY: `buf` is always valid UTF-8.
+        unsafe { from_utf8_unchecked_mut(&mut self.buf) }
+    }
+
+    #[inline]
+    pub fn push_str(&mut self, string: &str) {
+        self.buf.extend_from_slice(string.as_bytes());
+    }
+
+    #[inline]
+    pub fn push(&mut self, ch: char) {
+        self.push_str(ch.encode_utf8(&mut [0; 4]));
+    }
+
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.buf.reserve(additional);
+    }
+
+    #[inline]
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.buf.reserve_exact(additional);
+    }
+
+    #[inline]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.buf.try_reserve(additional)
+    }
+
+    #[inline]
+    pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.buf.try_reserve_exact(additional)
+    }
+
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.buf.shrink_to_fit();
+    }
+
+    #[inline]
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.buf.shrink_to(min_capacity);
+    }
+
+    #[inline]
+    pub fn truncate(&mut self, new_len: usize) {
+        if new_len <= self.len() {
+            assert!(self.is_char_boundary(new_len));
+            self.buf.truncate(new_len);
+        }
+    }
+
+    #[inline]
+    pub fn pop(&mut self) -> Option<char> {
+        let ch = self.chars().next_back()?;
+        let len = self.len() - ch.len_utf8();
+        self.buf.truncate(len);
+        Some(ch)
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.buf.clear();
+    }
+
+    #[inline]
+    pub fn insert(&mut self, idx: usize, ch: char) {
+        self.insert_str(idx, ch.encode_utf8(&mut [0; 4]));
+    }
+
+    #[inline]
+    pub fn insert_str(&mut self, idx: usize, string: &str) {
+        assert!(self.is_char_boundary(idx));
+        self.buf.splice(idx..idx, string.as_bytes().iter().copied());
+    }
+
+    #[inline]
+    pub fn remove(&mut self, idx: usize) -> char {
+        let ch = match self[idx..].chars().next() {
+            Some(ch) => ch,
+            None => panic!("cannot remove a char from the end of a string"),
+        };
+        self.buf.drain(idx..idx + ch.len_utf8());
+        ch
+    }
+
+    /// Replaces the range, which must be on char boundaries.
+    #[inline]
+    pub fn replace_range(&mut self, range: ops::Range<usize>, replace_with: &str) {
+        assert!(self.is_char_boundary(range.start));
+        assert!(self.is_char_boundary(range.end));
+        self.buf
+            .splice(range, replace_with.as_bytes().iter().copied());
+    }
+
+    /// If `f` panics, the string is left with the characters retained so far (like `String::retain`).
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(char) -> bool,
+    {
+        let len = self.len();
+        let base = self.buf.as_mut_ptr();
+        let mut guard = SetLenOnDrop {
+            buf: &mut self.buf,
+            len: 0,
+        };
+        let mut read = 0;
+        while read < len {
+            // SAFETY: `buf[..len]` is initialized and valid UTF-8, and `read` is on a char boundary
+            // (it advances by whole chars).
+            let ch = unsafe {
+                let tail = core::slice::from_raw_parts(base.add(read), len - read);
+                from_utf8_unchecked(tail).chars().next().unwrap_unchecked()
+            };
+            let ch_len = ch.len_utf8();
+            if f(ch) {
+                // SAFETY: `guard.len <= read`, both within the initialized part of the allocation.
+                unsafe { core::ptr::copy(base.add(read), base.add(guard.len), ch_len) };
+                guard.len += ch_len;
+            }
+            read += ch_len;
+        }
+    }
+}
+
+/// Truncates the buffer compacted by `HeapString::retain` to the retained chars, even if the predicate panics.
+struct SetLenOnDrop<'a, A: Allocator> {
+    buf: &'a mut VecIn<u8, A>,
+    /// Length of the retained chars, compacted to the front.
+    len: usize,
+}
+
+impl<A: Allocator> Drop for SetLenOnDrop<'_, A> {
+    #[inline]
+    fn drop(&mut self) {
+        // SAFETY: `buf[..len]` is a concatenation of whole chars of the original contents.
+        unsafe { self.buf.set_len(self.len) };
+    }
+}
+
+impl HeapString<Global> {
+    #[inline]
+    pub fn from_string(string: String) -> Self {
+        Self {
+            buf: from_vec(string.into_bytes()),
+        }
+    }
+
+    #[inline]
+    pub fn into_string(self) -> String {
+        // SAFETY: `buf` is always valid UTF-8.
+        unsafe { String::from_utf8_unchecked(into_vec(self.buf)) }
+    }
+}
+
+impl<A: Allocator> ops::Deref for HeapString<A> {
+    type Target = str;
+
+    #[inline(always)]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<A: Allocator> ops::DerefMut for HeapString<A> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut str {
+        self.as_mut_str()
+    }
+}
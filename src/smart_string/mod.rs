@@ -1,73 +1,118 @@
-use std::borrow::Borrow;
-use std::borrow::BorrowMut;
-use std::borrow::Cow;
-use std::cmp;
-use std::convert::Infallible;
-use std::fmt;
-use std::hash::Hash;
-use std::hash::Hasher;
-use std::ops;
-use std::rc::Rc;
-use std::string::FromUtf16Error;
-use std::string::FromUtf8Error;
-use std::sync::Arc;
-
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::string::FromUtf16Error;
+use alloc::string::FromUtf8Error;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::borrow::BorrowMut;
+use core::cmp;
+use core::convert::Infallible;
+use core::fmt;
+use core::hash::Hash;
+use core::hash::Hasher;
+use core::marker::PhantomData;
+use core::ops;
+
+use crate::allocator::Allocator;
+use crate::allocator::Global;
+use crate::allocator::TryReserveError;
+use crate::macros::impl_reverse_eq_for_str_types;
 use crate::pascal_string;
 use crate::DisplayExt;
 use crate::PascalString;
+use crate::PascalStringLong;
 
+mod drain;
+mod heap;
+mod policy;
+mod repr;
 #[cfg(feature = "serde")]
 mod with_serde;
-
-pub const DEFAULT_CAPACITY: usize = 30;
+#[cfg(feature = "ufmt")]
+mod with_ufmt;
+
+pub use drain::Drain;
+pub use policy::DemoteBelow;
+pub use policy::DemoteOnShrinkToFit;
+pub use policy::DemotionPolicy;
+pub use policy::NeverDemote;
+use repr::HeapMut;
+use repr::Repr;
+use repr::StorageMut;
+use repr::StorageRef;
+
+/// The largest stack capacity that doesn't grow `SmartString` beyond its heap representation:
+/// 23 bytes on 64-bit platforms, where `SmartString` is then as small as `String`.
+pub const DEFAULT_CAPACITY: usize = repr::INLINE_CAPACITY;
 
 /// A string that stores short values on the stack and longer values on the heap.
 ///
+/// ### Layout
+///
+/// The stack and heap representations share the same memory, told apart by the first byte: the length of the inline
+/// `PascalString<N>`, or a marker value no inline length can take. Hence the stack capacity is limited to 254 bytes,
+/// and `SmartString<N>` for `N <= DEFAULT_CAPACITY` has the same size as `String`.
+///
 /// ### Storage semantics (explicit conversions)
 ///
 /// This type may **promote** from stack to heap during mutating operations (e.g. `push_str`, `reserve`) when the stack
 /// capacity is exceeded.
 ///
-/// It does **not** automatically demote from heap to stack when the contents become shorter (including during
-/// in-place deserialization). This is intentional: implicit demotion can introduce surprising realloc/dealloc churn in
-/// real workloads (e.g. shorten → re-grow). If you want to attempt a demotion, call `try_into_stack`.
-#[derive(Clone)]
-pub enum SmartString<const N: usize = DEFAULT_CAPACITY> {
-    Heap(String),
-    Stack(PascalString<N>),
+/// Demotion from heap to stack is governed by the [`DemotionPolicy`] `P`. By default ([`NeverDemote`]) it does
+/// **not** automatically demote when the contents become shorter (including during in-place deserialization).
+/// This is intentional: implicit demotion can introduce surprising realloc/dealloc churn in real workloads
+/// (e.g. shorten → re-grow). If you want to attempt a demotion, call `try_into_stack`, or pick another policy:
+/// [`DemoteOnShrinkToFit`] or [`DemoteBelow`].
+///
+/// ### Allocator
+///
+/// The heap buffer is allocated through `A`, the global allocator by default; see the [`allocator`](crate::allocator)
+/// module. Conversions from and into the std types (`String`, `Box<str>`, ...) are available for `A = Global`.
+pub struct SmartString<const N: usize = DEFAULT_CAPACITY, P = NeverDemote, A: Allocator = Global> {
+    repr: Repr<N, A>,
+    policy: PhantomData<P>,
 }
 
-impl<const N: usize> SmartString<N> {
+// NOTE: const fns with trait bounds need Rust 1.61, hence a separate impl block without `P: DemotionPolicy`,
+// and only for the `Global` allocator.
+impl<const N: usize, P> SmartString<N, P> {
     #[inline]
-    fn ensure_heap_mut(&mut self) -> &mut String {
-        if let Self::Stack(s) = self {
-            *self = Self::Heap(s.to_string());
-        }
-        match self {
-            Self::Heap(s) => s,
-            Self::Stack(_) => unreachable!("just promoted to heap"),
+    const fn from_stack(s: PascalString<N>) -> Self {
+        Self {
+            repr: Repr::from_stack_global(s),
+            policy: PhantomData,
         }
     }
 
     #[inline]
     #[must_use]
     pub const fn new() -> Self {
-        Self::Stack(PascalString::new())
+        Self::from_stack(PascalString::new())
+    }
+}
+
+impl<const N: usize, P: DemotionPolicy> SmartString<N, P> {
+    #[inline]
+    fn from_heap(s: String) -> Self {
+        Self {
+            repr: Repr::from_string(s),
+            policy: PhantomData,
+        }
     }
 
     #[inline]
     #[must_use]
     pub fn with_capacity(capacity: usize) -> Self {
-        if capacity <= N {
-            Self::new()
-        } else {
-            Self::Heap(String::with_capacity(capacity))
-        }
+        Self::with_capacity_in(capacity, Global)
     }
 
     #[inline]
     pub fn from_utf8(vec: Vec<u8>) -> Result<Self, FromUtf8Error> {
-        String::from_utf8(vec).map(Self::Heap)
+        String::from_utf8(vec).map(Self::from_heap)
     }
 
     // TBD What to do with this?
@@ -82,13 +127,207 @@ impl<const N: usize> SmartString<N> {
     // }
 
     pub fn from_utf16(v: &[u16]) -> Result<Self, FromUtf16Error> {
-        String::from_utf16(v).map(Self::Heap)
+        String::from_utf16(v).map(Self::from_heap)
     }
 
     #[must_use]
     #[inline]
     pub fn from_utf16_lossy(v: &[u16]) -> Self {
-        Self::Heap(String::from_utf16_lossy(v))
+        Self::from_heap(String::from_utf16_lossy(v))
+    }
+
+    /// Fallible `From<&str>`: stores `s` on the stack if it fits, otherwise tries to allocate an exact-size heap buffer.
+    #[inline]
+    pub fn try_from_str(s: &str) -> Result<Self, TryReserveError> {
+        Self::try_from_str_in(s, Global)
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn into_string(self) -> String {
+        self.into()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.into_string().into_bytes()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn into_boxed_str(self) -> Box<str> {
+        self.into_string().into_boxed_str()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn leak<'a>(self) -> &'a mut str {
+        // Not `String::leak`, which needs Rust 1.72.
+        Box::leak(self.into_boxed_str())
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn from_utf8_lossy(v: &[u8]) -> Cow<'_, str> {
+        String::from_utf8_lossy(v)
+    }
+}
+
+impl<const N: usize, P: DemotionPolicy, A: Allocator> SmartString<N, P, A> {
+    #[inline]
+    fn from_stack_in(s: PascalString<N>, alloc: A) -> Self {
+        Self {
+            repr: Repr::from_stack(s, alloc),
+            policy: PhantomData,
+        }
+    }
+
+    /// Moves the stack-stored contents to the heap, into a buffer of `capacity` bytes (at least).
+    #[inline]
+    fn promote(&mut self, capacity: usize) {
+        if let StorageRef::Stack(s) = self.repr.storage() {
+            let stack = *s;
+            self.repr.set_heap(capacity, &[&stack]);
+        }
+    }
+
+    #[inline]
+    fn ensure_heap_mut(&mut self) -> HeapMut<'_, A> {
+        self.promote(self.len());
+        match self.repr.storage_mut() {
+            StorageMut::Heap(s) => s,
+            StorageMut::Stack(_) => unreachable!("just promoted to heap"),
+        }
+    }
+
+    /// Resolves `range` against the string, panicking if it is out of bounds or not on char boundaries
+    /// (like `String` does).
+    #[inline]
+    fn char_range<R>(&self, range: R) -> ops::Range<usize>
+    where
+        R: ops::RangeBounds<usize>,
+    {
+        let start = match range.start_bound() {
+            ops::Bound::Included(&n) => n,
+            ops::Bound::Excluded(&n) => n.checked_add(1).expect("range start overflow"),
+            ops::Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            ops::Bound::Included(&n) => n.checked_add(1).expect("range end overflow"),
+            ops::Bound::Excluded(&n) => n,
+            ops::Bound::Unbounded => self.len(),
+        };
+        // Panics on invalid ranges exactly like `String` slicing.
+        let _ = &self.as_str()[start..end];
+        start..end
+    }
+
+    /// Removes the byte range `start..end`, which must be on char boundaries.
+    #[inline]
+    fn remove_range(&mut self, start: usize, end: usize) {
+        match self.repr.storage_mut() {
+            StorageMut::Heap(mut s) => s.replace_range(start..end, ""),
+            StorageMut::Stack(s) => s.replace_range_expect_capacity(start..end, ""),
+        }
+        self.after_shrink();
+    }
+
+    /// Replaces the contents, reusing the heap buffer if any, and applies the demotion policy.
+    #[inline]
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    fn set_str(&mut self, string: &str) {
+        match self.repr.storage_mut() {
+            StorageMut::Heap(mut s) => {
+                s.clear();
+                s.push_str(string);
+            }
+            StorageMut::Stack(s) => {
+                if let Ok(new) = PascalString::try_from(string) {
+                    *s = new;
+                    return;
+                }
+            }
+        }
+        if self.is_heap() {
+            self.after_shrink();
+        } else {
+            self.repr.set_heap(string.len(), &[string]);
+        }
+    }
+
+    /// Applies the demotion policy after a mutation that may have shortened the string.
+    #[inline]
+    fn after_shrink(&mut self) {
+        let len = self.len();
+        if self.is_heap() && len <= N && P::demote_on_shrink(len, N) {
+            self.demote();
+        }
+    }
+
+    /// Moves the contents to the stack if they fit.
+    #[inline]
+    fn demote(&mut self) {
+        if let Ok(s) = PascalString::try_from(self.as_str()) {
+            self.repr.set_stack(s);
+        }
+    }
+
+    /// Applies the demotion policy on `shrink_to_fit` / `shrink_to`; returns whether the string got demoted.
+    #[inline]
+    fn demote_on_shrink_to(&mut self, min_capacity: usize) -> bool {
+        let len = self.len();
+        let demote =
+            self.is_heap() && len <= N && min_capacity <= N && P::demote_on_shrink_to(len, N);
+        if demote {
+            self.demote();
+        }
+        demote
+    }
+
+    /// Creates an empty string that allocates through `alloc` once it outgrows the stack.
+    #[inline]
+    #[must_use]
+    pub fn new_in(alloc: A) -> Self {
+        Self::from_stack_in(PascalString::new(), alloc)
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+        let mut this = Self::new_in(alloc);
+        if capacity > N {
+            this.repr.set_heap(capacity, &[]);
+        }
+        this
+    }
+
+    /// Creates a string holding `s`, allocating through `alloc` if it doesn't fit on the stack.
+    #[inline]
+    #[must_use]
+    pub fn from_str_in(s: &str, alloc: A) -> Self {
+        let mut this = Self::new_in(alloc);
+        this.set_str(s);
+        this
+    }
+
+    /// Fallible [`from_str_in`](Self::from_str_in).
+    #[inline]
+    pub fn try_from_str_in(s: &str, alloc: A) -> Result<Self, TryReserveError> {
+        let mut this = Self::new_in(alloc);
+        match PascalString::try_from(s) {
+            Ok(stack) => this.repr.set_stack(stack),
+            Err(pascal_string::TryFromStrError::TooLong) => {
+                this.repr.try_set_heap(s.len(), &[s])?
+            }
+        }
+        Ok(this)
+    }
+
+    /// Returns a reference to the underlying allocator.
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        self.repr.allocator()
     }
 
     #[inline]
@@ -117,238 +356,268 @@ impl<const N: usize> SmartString<N> {
 
     #[inline]
     pub fn is_heap(&self) -> bool {
-        matches!(self, Self::Heap(_))
+        self.repr.is_heap()
     }
 
     #[inline]
     pub fn is_stack(&self) -> bool {
-        matches!(self, Self::Stack(_))
+        !self.repr.is_heap()
     }
 
     #[inline]
     #[must_use]
-    pub fn into_heap(self) -> Self {
-        Self::Heap(match self {
-            Self::Stack(s) => s.to_string(),
-            Self::Heap(s) => s,
-        })
+    pub fn into_heap(mut self) -> Self {
+        self.promote(self.len());
+        self
     }
 
     #[inline]
     #[must_use]
-    pub fn try_into_stack(self) -> Self {
-        match self {
-            Self::Stack(s) => Self::Stack(s),
-            Self::Heap(s) => match PascalString::try_from(s.as_str()) {
-                Ok(s) => Self::Stack(s),
-                Err(pascal_string::TryFromStrError::TooLong) => Self::Heap(s),
-            },
-        }
+    pub fn try_into_stack(mut self) -> Self {
+        self.demote();
+        self
     }
 
     #[inline]
     pub fn push_str(&mut self, string: &str) {
-        match self {
-            Self::Heap(s) => s.push_str(string),
-            Self::Stack(s) => match s.try_push_str(string) {
-                Ok(()) => (),
-                Err(pascal_string::TryFromStrError::TooLong) => {
-                    let mut new = String::with_capacity(s.len() + string.len());
-                    new.push_str(s.as_str());
-                    new.push_str(string);
-                    *self = Self::Heap(new);
-                }
+        let stack = match self.repr.storage_mut() {
+            StorageMut::Heap(mut s) => return s.push_str(string),
+            StorageMut::Stack(s) => match s.try_push_str(string) {
+                Ok(()) => return,
+                Err(pascal_string::TryFromStrError::TooLong) => *s,
             },
-        }
+        };
+        let capacity = stack
+            .len()
+            .checked_add(string.len())
+            .expect("capacity overflow");
+        self.repr.set_heap(capacity, &[&stack, string]);
     }
 
     #[inline]
     pub fn capacity(&self) -> usize {
-        match self {
-            Self::Heap(s) => s.capacity(),
-            Self::Stack(s) => s.capacity(),
-        }
+        self.repr.capacity()
     }
 
     #[inline]
     pub fn reserve(&mut self, additional: usize) {
-        match self {
-            Self::Heap(s) => s.reserve(additional),
-            Self::Stack(s) => {
-                if s.capacity() - s.len() < additional {
-                    let mut new = String::with_capacity(s.len() + additional);
-                    new.push_str(s.as_str());
-                    *self = Self::Heap(new);
+        let stack = match self.repr.storage_mut() {
+            StorageMut::Heap(mut s) => return s.reserve(additional),
+            StorageMut::Stack(s) => {
+                if s.capacity() - s.len() >= additional {
+                    return;
                 }
+                *s
             }
-        }
+        };
+        let capacity = stack
+            .len()
+            .checked_add(additional)
+            .expect("capacity overflow");
+        self.repr.set_heap(capacity, &[&stack]);
     }
 
     pub fn reserve_exact(&mut self, additional: usize) {
-        match self {
-            Self::Heap(s) => s.reserve_exact(additional),
-            Self::Stack(s) => {
-                if s.capacity() - s.len() < additional {
-                    let mut new = String::new();
-                    new.reserve_exact(s.len() + additional);
-                    new.push_str(s.as_str());
-                    *self = Self::Heap(new);
+        // Promotion always allocates an exact-size buffer.
+        let stack = match self.repr.storage_mut() {
+            StorageMut::Heap(mut s) => return s.reserve_exact(additional),
+            StorageMut::Stack(s) => {
+                if s.capacity() - s.len() >= additional {
+                    return;
                 }
+                *s
             }
-        }
-    }
-
-    #[rustversion::since(1.57)]
-    pub fn try_reserve(
-        &mut self,
-        additional: usize,
-    ) -> Result<(), std::collections::TryReserveError> {
-        match self {
-            Self::Heap(s) => s.try_reserve(additional),
-            Self::Stack(s) => {
-                if s.capacity() - s.len() < additional {
-                    let mut new = String::new();
-                    new.try_reserve(s.len() + additional)?;
-                    new.push_str(s.as_str());
-                    *self = Self::Heap(new);
+        };
+        let capacity = stack
+            .len()
+            .checked_add(additional)
+            .expect("capacity overflow");
+        self.repr.set_heap(capacity, &[&stack]);
+    }
+
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let stack = match self.repr.storage_mut() {
+            StorageMut::Heap(mut s) => return s.try_reserve(additional),
+            StorageMut::Stack(s) => {
+                if s.capacity() - s.len() >= additional {
+                    return Ok(());
                 }
-                Ok(())
+                *s
             }
-        }
-    }
-
-    #[rustversion::since(1.57)]
-    pub fn try_reserve_exact(
-        &mut self,
-        additional: usize,
-    ) -> Result<(), std::collections::TryReserveError> {
-        match self {
-            Self::Heap(s) => s.try_reserve_exact(additional),
-            Self::Stack(s) => {
-                if s.capacity() - s.len() < additional {
-                    let mut new = String::new();
-                    new.try_reserve_exact(s.len() + additional)?;
-                    new.push_str(s.as_str());
-                    *self = Self::Heap(new);
+        };
+        // An overflowing capacity saturates to one that can't be allocated, failing with `CapacityOverflow`.
+        let capacity = stack.len().saturating_add(additional);
+        self.repr.try_set_heap(capacity, &[&stack])
+    }
+
+    pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let stack = match self.repr.storage_mut() {
+            StorageMut::Heap(mut s) => return s.try_reserve_exact(additional),
+            StorageMut::Stack(s) => {
+                if s.capacity() - s.len() >= additional {
+                    return Ok(());
                 }
-                Ok(())
+                *s
             }
-        }
+        };
+        let capacity = stack.len().saturating_add(additional);
+        self.repr.try_set_heap(capacity, &[&stack])
     }
 
+    /// Fallible `push_str`: returns an error instead of aborting if promotion or growth fails to allocate.
+    ///
+    /// On error, the string is left unchanged.
     #[inline]
-    pub fn shrink_to_fit(&mut self) {
-        match self {
-            Self::Heap(s) => s.shrink_to_fit(),
-            Self::Stack(_) => (),
-        }
+    pub fn try_push_str(&mut self, string: &str) -> Result<(), TryReserveError> {
+        self.try_reserve(string.len())?;
+        self.push_str(string);
+        Ok(())
     }
 
+    /// Fallible `push`: returns an error instead of aborting if promotion or growth fails to allocate.
+    ///
+    /// On error, the string is left unchanged.
     #[inline]
-    pub fn shrink_to(&mut self, min_capacity: usize) {
-        match self {
-            Self::Heap(s) => s.shrink_to(min_capacity),
-            Self::Stack(_) => (),
-        }
+    pub fn try_push(&mut self, ch: char) -> Result<(), TryReserveError> {
+        self.try_reserve(ch.len_utf8())?;
+        self.push(ch);
+        Ok(())
     }
 
-    pub fn push(&mut self, ch: char) {
-        match self {
-            Self::Heap(s) => s.push(ch),
-            Self::Stack(s) => match s.try_push(ch) {
-                Ok(()) => (),
-                Err(pascal_string::TryFromStrError::TooLong) => {
-                    let mut new = String::with_capacity(s.len() + ch.len_utf8());
-                    new.push_str(s.as_str());
-                    new.push(ch);
-                    *self = Self::Heap(new);
-                }
-            },
-        }
+    /// Fallible `insert_str`: returns an error instead of aborting if promotion or growth fails to allocate.
+    ///
+    /// On error, the string is left unchanged.
+    /// Panics if `idx` is out of bounds or not on a UTF-8 boundary (matches `String` semantics).
+    #[inline]
+    pub fn try_insert_str(&mut self, idx: usize, string: &str) -> Result<(), TryReserveError> {
+        assert!(self.is_char_boundary(idx), "invalid index or char boundary");
+        self.try_reserve(string.len())?;
+        self.insert_str(idx, string);
+        Ok(())
     }
 
+    /// Fallible `insert`; see [`try_insert_str`](Self::try_insert_str).
     #[inline]
-    pub fn truncate(&mut self, new_len: usize) {
-        match self {
-            Self::Heap(s) => s.truncate(new_len),
-            Self::Stack(s) => s.truncate(new_len),
+    pub fn try_insert(&mut self, idx: usize, ch: char) -> Result<(), TryReserveError> {
+        self.try_insert_str(idx, ch.encode_utf8(&mut [0; 4]))
+    }
+
+    /// Fallible `Extend`: appends every item, stopping at the first allocation failure.
+    ///
+    /// On error, the items appended before the failure are kept.
+    pub fn try_extend<I>(&mut self, iter: I) -> Result<(), TryReserveError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        for s in iter {
+            self.try_push_str(s.as_ref())?;
         }
+        Ok(())
     }
 
+    /// Fallible `clone`: a heap-stored value is copied into an exact-size buffer, which may fail to allocate.
     #[inline]
-    pub fn pop(&mut self) -> Option<char> {
-        match self {
-            Self::Heap(s) => s.pop(),
-            Self::Stack(s) => s.pop(),
-        }
+    pub fn try_clone(&self) -> Result<Self, TryReserveError>
+    where
+        A: Clone,
+    {
+        Ok(Self {
+            repr: self.repr.try_clone()?,
+            policy: PhantomData,
+        })
     }
 
     #[inline]
-    pub fn clear(&mut self) {
-        match self {
-            Self::Heap(s) => s.clear(),
-            Self::Stack(s) => s.clear(),
+    pub fn shrink_to_fit(&mut self) {
+        if self.demote_on_shrink_to(0) {
+            return;
+        }
+        match self.repr.storage_mut() {
+            StorageMut::Heap(mut s) => s.shrink_to_fit(),
+            StorageMut::Stack(_) => (),
         }
     }
 
-    // --- String-like APIs that require heap delegation -------------------------------------------
-
     #[inline]
-    #[must_use]
-    pub fn into_string(self) -> String {
-        self.into()
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        if self.demote_on_shrink_to(min_capacity) {
+            return;
+        }
+        match self.repr.storage_mut() {
+            StorageMut::Heap(mut s) => s.shrink_to(min_capacity),
+            StorageMut::Stack(_) => (),
+        }
     }
 
-    #[inline]
-    #[must_use]
-    pub fn into_bytes(self) -> Vec<u8> {
-        self.into_string().into_bytes()
+    pub fn push(&mut self, ch: char) {
+        let stack = match self.repr.storage_mut() {
+            StorageMut::Heap(mut s) => return s.push(ch),
+            StorageMut::Stack(s) => match s.try_push(ch) {
+                Ok(()) => return,
+                Err(pascal_string::TryFromStrError::TooLong) => *s,
+            },
+        };
+        self.repr.set_heap(
+            stack.len() + ch.len_utf8(),
+            &[&stack, ch.encode_utf8(&mut [0; 4])],
+        );
     }
 
     #[inline]
-    #[must_use]
-    pub fn into_boxed_str(self) -> Box<str> {
-        self.into_string().into_boxed_str()
+    pub fn truncate(&mut self, new_len: usize) {
+        match self.repr.storage_mut() {
+            StorageMut::Heap(mut s) => s.truncate(new_len),
+            StorageMut::Stack(s) => s.truncate(new_len),
+        }
+        self.after_shrink();
     }
 
     #[inline]
-    #[must_use]
-    pub fn leak<'a>(self) -> &'a mut str {
-        self.into_string().leak()
+    pub fn pop(&mut self) -> Option<char> {
+        let ch = match self.repr.storage_mut() {
+            StorageMut::Heap(mut s) => s.pop(),
+            StorageMut::Stack(s) => s.pop(),
+        };
+        self.after_shrink();
+        ch
     }
 
     #[inline]
-    #[must_use]
-    pub fn from_utf8_lossy(v: &[u8]) -> Cow<'_, str> {
-        String::from_utf8_lossy(v)
+    pub fn clear(&mut self) {
+        match self.repr.storage_mut() {
+            StorageMut::Heap(mut s) => s.clear(),
+            StorageMut::Stack(s) => s.clear(),
+        }
+        self.after_shrink();
     }
 
+    // --- String-like APIs that require heap delegation -------------------------------------------
+
     #[inline]
     pub fn insert(&mut self, idx: usize, ch: char) {
-        match self {
-            Self::Heap(s) => s.insert(idx, ch),
-            Self::Stack(s) => match s.try_insert(idx, ch) {
-                Ok(()) => (),
-                Err(pascal_string::InsertError::TooLong) => {
-                    self.ensure_heap_mut().insert(idx, ch)
-                }
+        match self.repr.storage_mut() {
+            StorageMut::Heap(mut s) => return s.insert(idx, ch),
+            StorageMut::Stack(s) => match s.try_insert(idx, ch) {
+                Ok(()) => return,
+                Err(pascal_string::InsertError::TooLong) => (),
                 Err(_) => panic!("invalid index or char boundary"),
             },
         }
+        self.ensure_heap_mut().insert(idx, ch);
     }
 
     #[inline]
     pub fn insert_str(&mut self, idx: usize, string: &str) {
-        match self {
-            Self::Heap(s) => s.insert_str(idx, string),
-            Self::Stack(s) => match s.try_insert_str(idx, string) {
-                Ok(()) => (),
-                Err(pascal_string::InsertError::TooLong) => {
-                    self.ensure_heap_mut().insert_str(idx, string)
-                }
+        match self.repr.storage_mut() {
+            StorageMut::Heap(mut s) => return s.insert_str(idx, string),
+            StorageMut::Stack(s) => match s.try_insert_str(idx, string) {
+                Ok(()) => return,
+                Err(pascal_string::InsertError::TooLong) => (),
                 Err(_) => panic!("invalid index or char boundary"),
             },
         }
+        self.ensure_heap_mut().insert_str(idx, string);
     }
 
     /// Inserts a string slice, truncating when stored on stack; returns the remainder that did not fit.
@@ -372,8 +641,8 @@ impl<const N: usize> SmartString<N> {
         idx: usize,
         string: &'s str,
     ) -> Result<&'s str, pascal_string::InsertError> {
-        match self {
-            Self::Heap(s) => {
+        match self.repr.storage_mut() {
+            StorageMut::Heap(mut s) => {
                 let len = s.len();
                 if idx > len {
                     return Err(pascal_string::InsertError::OutOfBounds { idx, len });
@@ -384,60 +653,129 @@ impl<const N: usize> SmartString<N> {
                 s.insert_str(idx, string);
                 Ok("")
             }
-            Self::Stack(s) => s.try_insert_str_truncated(idx, string),
+            StorageMut::Stack(s) => s.try_insert_str_truncated(idx, string),
         }
     }
 
     #[inline]
     pub fn remove(&mut self, idx: usize) -> char {
-        match self {
-            Self::Heap(s) => s.remove(idx),
-            Self::Stack(s) => s.remove(idx),
-        }
+        let ch = match self.repr.storage_mut() {
+            StorageMut::Heap(mut s) => s.remove(idx),
+            StorageMut::Stack(s) => s.remove(idx),
+        };
+        self.after_shrink();
+        ch
     }
 
+    /// Retains only the characters specified by the predicate.
+    ///
+    /// If `f` panics, the string is left with the characters retained so far (like `String::retain`).
     #[inline]
     pub fn retain<F>(&mut self, f: F)
     where
         F: FnMut(char) -> bool,
     {
-        self.ensure_heap_mut().retain(f);
+        match self.repr.storage_mut() {
+            StorageMut::Heap(mut s) => s.retain(f),
+            StorageMut::Stack(s) => s.retain(f),
+        }
+        self.after_shrink();
     }
 
+    /// Removes the specified range from the string in bulk, returning all removed characters as an iterator.
+    ///
+    /// The range is removed even if the iterator is not consumed until the end.
+    ///
+    /// Panics if the starting point or end point do not lie on a char boundary, or if they're out of bounds
+    /// (matches `String` semantics).
     #[inline]
-    pub fn drain<R>(&mut self, range: R) -> std::string::Drain<'_>
+    pub fn drain<R>(&mut self, range: R) -> Drain<'_, N, P, A>
     where
-        R: std::ops::RangeBounds<usize>,
+        R: ops::RangeBounds<usize>,
     {
-        self.ensure_heap_mut().drain(range)
+        let ops::Range { start, end } = self.char_range(range);
+
+        // SAFETY: the range was just checked to be in bounds and on char boundaries.
+        unsafe { Drain::new(self, start, end) }
     }
 
+    /// Splits the string into two at the given byte index.
+    ///
+    /// The returned value is stored on the stack if it fits.
     #[inline]
-    pub fn split_off(&mut self, at: usize) -> Self {
-        let other = self.ensure_heap_mut().split_off(at);
-        SmartString::from(other).try_into_stack()
+    #[must_use = "use `.truncate()` if you don't need the other half"]
+    pub fn split_off(&mut self, at: usize) -> Self
+    where
+        A: Clone,
+    {
+        let alloc = self.allocator().clone();
+        let other = match self.repr.storage_mut() {
+            StorageMut::Heap(mut s) => {
+                assert!(s.is_char_boundary(at));
+                let other = Self::from_str_in(&s[at..], alloc);
+                s.truncate(at);
+                other
+            }
+            StorageMut::Stack(s) => Self::from_stack_in(s.split_off(at), alloc),
+        };
+        self.after_shrink();
+        other
     }
 
+    /// Replaces the specified range with the given string, promoting to heap only if the result overflows the stack.
     #[inline]
     pub fn replace_range<R>(&mut self, range: R, replace_with: &str)
     where
-        R: std::ops::RangeBounds<usize>,
+        R: ops::RangeBounds<usize>,
     {
-        self.ensure_heap_mut().replace_range(range, replace_with);
+        let ops::Range { start, end } = self.char_range(range);
+        let stack = match self.repr.storage_mut() {
+            StorageMut::Heap(mut s) => {
+                s.replace_range(start..end, replace_with);
+                None
+            }
+            StorageMut::Stack(s) => match s.try_replace_range(start..end, replace_with) {
+                Ok(()) => return,
+                Err(pascal_string::InsertError::TooLong) => Some(*s),
+                Err(_) => unreachable!("the range is checked"),
+            },
+        };
+        match stack {
+            Some(s) => {
+                let capacity = s.len() - (end - start) + replace_with.len();
+                self.repr
+                    .set_heap(capacity, &[&s[..start], replace_with, &s[end..]]);
+            }
+            None => self.after_shrink(),
+        }
     }
 }
 
 // -- Common traits --------------------------------------------------------------------------------
 
-impl<const N: usize> Default for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy, A: Allocator + Clone> Clone for SmartString<N, P, A> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            repr: self.repr.clone(),
+            policy: PhantomData,
+        }
+    }
+}
+
+impl<const N: usize, P: DemotionPolicy, A: Allocator + Default> Default for SmartString<N, P, A> {
     #[inline]
     fn default() -> Self {
-        Self::new()
+        Self::new_in(A::default())
     }
 }
 
-impl<T: ops::Deref<Target = str> + ?Sized, const CAPACITY: usize> PartialEq<T>
-    for SmartString<CAPACITY>
+impl<
+        T: ops::Deref<Target = str> + ?Sized,
+        const CAPACITY: usize,
+        P: DemotionPolicy,
+        A: Allocator,
+    > PartialEq<T> for SmartString<CAPACITY, P, A>
 {
     #[inline(always)]
     fn eq(&self, other: &T) -> bool {
@@ -445,76 +783,74 @@ impl<T: ops::Deref<Target = str> + ?Sized, const CAPACITY: usize> PartialEq<T>
     }
 }
 
-macro_rules! impl_reverse_eq_for_str_types {
-    ($($t:ty),*) => {
-        $(
-            impl<const N: usize> PartialEq<SmartString<N>> for $t {
-                #[inline(always)]
-                fn eq(&self, other: &SmartString<N>) -> bool {
-                    let a: &str = self.as_ref();
-                    let b = other.as_str();
-                    a.eq(b)
-                }
-            }
-
-            impl<const N: usize> PartialEq<SmartString<N>> for &$t {
-                #[inline(always)]
-                fn eq(&self, other: &SmartString<N>) -> bool {
-                    let a: &str = self.as_ref();
-                    let b = other.as_str();
-                    a.eq(b)
-                }
-            }
+impl_reverse_eq_for_str_types!(
+    [const N: usize, P: DemotionPolicy, A: Allocator] SmartString<N, P, A>;
+    String, str, Cow<'_, str>, Box<str>, Rc<str>, Arc<str>
+);
 
-            impl<const N: usize> PartialEq<SmartString<N>> for &mut $t {
-                #[inline(always)]
-                fn eq(&self, other: &SmartString<N>) -> bool {
-                    let a: &str = self.as_ref();
-                    let b = other.as_str();
-                    a.eq(b)
-                }
-            }
-        )*
-    };
+impl<const M: usize, const N: usize, P: DemotionPolicy, A: Allocator>
+    PartialEq<SmartString<N, P, A>> for &PascalString<M>
+{
+    #[inline(always)]
+    fn eq(&self, other: &SmartString<N, P, A>) -> bool {
+        let a: &str = self.as_ref();
+        let b = other.as_str();
+        a.eq(b)
+    }
 }
 
-impl_reverse_eq_for_str_types!(String, str, Cow<'_, str>, Box<str>, Rc<str>, Arc<str>);
+impl<const M: usize, const N: usize, P: DemotionPolicy, A: Allocator>
+    PartialEq<SmartString<N, P, A>> for &mut PascalString<M>
+{
+    #[inline(always)]
+    fn eq(&self, other: &SmartString<N, P, A>) -> bool {
+        let a: &str = self.as_ref();
+        let b = other.as_str();
+        a.eq(b)
+    }
+}
 
-impl<const M: usize, const N: usize> PartialEq<SmartString<N>> for &PascalString<M> {
+impl<const M: usize, const N: usize, P: DemotionPolicy, A: Allocator>
+    PartialEq<SmartString<N, P, A>> for &PascalStringLong<M>
+{
     #[inline(always)]
-    fn eq(&self, other: &SmartString<N>) -> bool {
+    fn eq(&self, other: &SmartString<N, P, A>) -> bool {
         let a: &str = self.as_ref();
         let b = other.as_str();
         a.eq(b)
     }
 }
 
-impl<const M: usize, const N: usize> PartialEq<SmartString<N>> for &mut PascalString<M> {
+impl<const M: usize, const N: usize, P: DemotionPolicy, A: Allocator>
+    PartialEq<SmartString<N, P, A>> for &mut PascalStringLong<M>
+{
     #[inline(always)]
-    fn eq(&self, other: &SmartString<N>) -> bool {
+    fn eq(&self, other: &SmartString<N, P, A>) -> bool {
         let a: &str = self.as_ref();
         let b = other.as_str();
         a.eq(b)
     }
 }
 
-impl<const N: usize> Eq for SmartString<N> {}
+impl<const N: usize, P: DemotionPolicy, A: Allocator> Eq for SmartString<N, P, A> {}
 
-impl<T: ops::Deref<Target = str>, const N: usize> PartialOrd<T> for SmartString<N> {
+impl<T: ops::Deref<Target = str>, const N: usize, P: DemotionPolicy, A: Allocator> PartialOrd<T>
+    for SmartString<N, P, A>
+{
     #[inline(always)]
     fn partial_cmp(&self, other: &T) -> Option<cmp::Ordering> {
         self.as_str().partial_cmp(other.deref())
     }
 }
 
-impl<const N: usize> Ord for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy, A: Allocator> Ord for SmartString<N, P, A> {
     #[inline(always)]
     fn cmp(&self, other: &Self) -> cmp::Ordering {
         self.as_str().cmp(other.as_str())
     }
 }
 
-impl<const N: usize> Hash for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy, A: Allocator> Hash for SmartString<N, P, A> {
     #[inline(always)]
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.as_str().hash(state)
@@ -523,7 +859,7 @@ impl<const N: usize> Hash for SmartString<N> {
 
 // -- Formatting -----------------------------------------------------------------------------------
 
-impl<const N: usize> fmt::Debug for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy, A: Allocator> fmt::Debug for SmartString<N, P, A> {
     #[inline(always)]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let name: PascalString<39> = format_args!("SmartString<{N}>")
@@ -533,69 +869,63 @@ impl<const N: usize> fmt::Debug for SmartString<N> {
     }
 }
 
-impl<const N: usize> fmt::Display for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy, A: Allocator> fmt::Display for SmartString<N, P, A> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::Heap(s) => s.fmt(f),
-            Self::Stack(s) => s.fmt(f),
+        match self.repr.storage() {
+            StorageRef::Heap(s) => s.fmt(f),
+            StorageRef::Stack(s) => s.fmt(f),
         }
     }
 }
 
 // -- Reference ------------------------------------------------------------------------------------
 
-impl<const N: usize> ops::Deref for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy, A: Allocator> ops::Deref for SmartString<N, P, A> {
     type Target = str;
 
     #[inline]
     fn deref(&self) -> &Self::Target {
-        match self {
-            Self::Heap(s) => s.deref(),
-            Self::Stack(s) => s.deref(),
-        }
+        self.repr.as_str()
     }
 }
 
-impl<const N: usize> ops::DerefMut for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy, A: Allocator> ops::DerefMut for SmartString<N, P, A> {
     #[inline]
     fn deref_mut(&mut self) -> &mut Self::Target {
-        match self {
-            Self::Heap(s) => s.deref_mut(),
-            Self::Stack(s) => s.deref_mut(),
-        }
+        self.repr.as_mut_str()
     }
 }
 
-impl<const N: usize> Borrow<str> for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy, A: Allocator> Borrow<str> for SmartString<N, P, A> {
     #[inline(always)]
     fn borrow(&self) -> &str {
         self
     }
 }
 
-impl<const N: usize> AsRef<str> for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy, A: Allocator> AsRef<str> for SmartString<N, P, A> {
     #[inline(always)]
     fn as_ref(&self) -> &str {
         self
     }
 }
 
-impl<const N: usize> AsRef<[u8]> for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy, A: Allocator> AsRef<[u8]> for SmartString<N, P, A> {
     #[inline(always)]
     fn as_ref(&self) -> &[u8] {
         self.as_bytes()
     }
 }
 
-impl<const N: usize> AsMut<str> for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy, A: Allocator> AsMut<str> for SmartString<N, P, A> {
     #[inline(always)]
     fn as_mut(&mut self) -> &mut str {
         self
     }
 }
 
-impl<const N: usize> BorrowMut<str> for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy, A: Allocator> BorrowMut<str> for SmartString<N, P, A> {
     #[inline(always)]
     fn borrow_mut(&mut self) -> &mut str {
         self
@@ -604,24 +934,21 @@ impl<const N: usize> BorrowMut<str> for SmartString<N> {
 
 // -- Conversion -----------------------------------------------------------------------------------
 
-impl<const N: usize> From<String> for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy> From<String> for SmartString<N, P> {
     #[inline]
     fn from(s: String) -> Self {
-        Self::Heap(s)
+        Self::from_heap(s)
     }
 }
 
-impl<const N: usize> From<SmartString<N>> for String {
+impl<const N: usize, P: DemotionPolicy> From<SmartString<N, P>> for String {
     #[inline]
-    fn from(s: SmartString<N>) -> Self {
-        match s {
-            SmartString::Heap(s) => s,
-            SmartString::Stack(s) => s.to_string(),
-        }
+    fn from(s: SmartString<N, P>) -> Self {
+        s.repr.into_string()
     }
 }
 
-impl<const N: usize> std::str::FromStr for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy> core::str::FromStr for SmartString<N, P> {
     type Err = Infallible;
 
     #[inline]
@@ -630,25 +957,40 @@ impl<const N: usize> std::str::FromStr for SmartString<N> {
     }
 }
 
-impl<const M: usize, const N: usize> From<PascalString<M>> for SmartString<N> {
+impl<const M: usize, const N: usize, P: DemotionPolicy> From<PascalString<M>>
+    for SmartString<N, P>
+{
     #[inline]
     fn from(s: PascalString<M>) -> Self {
         PascalString::try_from(s.as_str())
-            .map(Self::Stack)
-            .unwrap_or_else(|pascal_string::TryFromStrError::TooLong| Self::Heap(s.to_string()))
+            .map(Self::from_stack)
+            .unwrap_or_else(|pascal_string::TryFromStrError::TooLong| {
+                Self::from_heap(s.to_string())
+            })
     }
 }
 
-impl<const N: usize> From<&str> for SmartString<N> {
+impl<const M: usize, const N: usize, P: DemotionPolicy> From<PascalStringLong<M>>
+    for SmartString<N, P>
+{
+    #[inline]
+    fn from(s: PascalStringLong<M>) -> Self {
+        Self::from(s.as_str())
+    }
+}
+
+impl<const N: usize, P: DemotionPolicy> From<&str> for SmartString<N, P> {
     #[inline]
     fn from(s: &str) -> Self {
         PascalString::try_from(s)
-            .map(Self::Stack)
-            .unwrap_or_else(|pascal_string::TryFromStrError::TooLong| Self::Heap(String::from(s)))
+            .map(Self::from_stack)
+            .unwrap_or_else(|pascal_string::TryFromStrError::TooLong| {
+                Self::from_heap(String::from(s))
+            })
     }
 }
 
-impl<const N: usize> From<char> for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy> From<char> for SmartString<N, P> {
     #[inline]
     fn from(ch: char) -> Self {
         let mut s = Self::new();
@@ -657,80 +999,80 @@ impl<const N: usize> From<char> for SmartString<N> {
     }
 }
 
-impl<const N: usize> From<&String> for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy> From<&String> for SmartString<N, P> {
     #[inline]
     fn from(s: &String) -> Self {
         Self::from(s.as_str())
     }
 }
 
-impl<const N: usize> From<&mut str> for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy> From<&mut str> for SmartString<N, P> {
     #[inline]
     fn from(s: &mut str) -> Self {
         Self::from(&*s)
     }
 }
 
-impl<const N: usize> From<Box<str>> for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy> From<Box<str>> for SmartString<N, P> {
     #[inline]
     fn from(s: Box<str>) -> Self {
         Self::from(s.as_ref())
     }
 }
 
-impl<const N: usize> From<&Box<str>> for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy> From<&Box<str>> for SmartString<N, P> {
     #[inline]
     fn from(s: &Box<str>) -> Self {
         Self::from(s.as_ref())
     }
 }
 
-impl<const N: usize> From<Rc<str>> for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy> From<Rc<str>> for SmartString<N, P> {
     #[inline]
     fn from(s: Rc<str>) -> Self {
         Self::from(s.as_ref())
     }
 }
 
-impl<const N: usize> From<&Rc<str>> for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy> From<&Rc<str>> for SmartString<N, P> {
     #[inline]
     fn from(s: &Rc<str>) -> Self {
         Self::from(s.as_ref())
     }
 }
 
-impl<const N: usize> From<Arc<str>> for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy> From<Arc<str>> for SmartString<N, P> {
     #[inline]
     fn from(s: Arc<str>) -> Self {
         Self::from(s.as_ref())
     }
 }
 
-impl<const N: usize> From<&Arc<str>> for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy> From<&Arc<str>> for SmartString<N, P> {
     #[inline]
     fn from(s: &Arc<str>) -> Self {
         Self::from(s.as_ref())
     }
 }
 
-impl<const N: usize> From<Cow<'_, str>> for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy> From<Cow<'_, str>> for SmartString<N, P> {
     #[inline]
     fn from(s: Cow<'_, str>) -> Self {
         match s {
             Cow::Borrowed(s) => Self::from(s),
-            Cow::Owned(s) => Self::Heap(s),
+            Cow::Owned(s) => Self::from_heap(s),
         }
     }
 }
 
-impl<const N: usize> From<&Cow<'_, str>> for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy> From<&Cow<'_, str>> for SmartString<N, P> {
     #[inline]
     fn from(s: &Cow<'_, str>) -> Self {
         Self::from(s.as_ref())
     }
 }
 
-impl<const N: usize> FromIterator<char> for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy> FromIterator<char> for SmartString<N, P> {
     fn from_iter<T: IntoIterator<Item = char>>(iter: T) -> Self {
         let mut s = Self::new();
         s.extend(iter);
@@ -738,7 +1080,7 @@ impl<const N: usize> FromIterator<char> for SmartString<N> {
     }
 }
 
-impl<'a, const N: usize> FromIterator<&'a str> for SmartString<N> {
+impl<'a, const N: usize, P: DemotionPolicy> FromIterator<&'a str> for SmartString<N, P> {
     fn from_iter<T: IntoIterator<Item = &'a str>>(iter: T) -> Self {
         let mut s = Self::new();
         s.extend(iter);
@@ -746,7 +1088,7 @@ impl<'a, const N: usize> FromIterator<&'a str> for SmartString<N> {
     }
 }
 
-impl<const N: usize> Extend<char> for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy, A: Allocator> Extend<char> for SmartString<N, P, A> {
     #[inline]
     fn extend<T: IntoIterator<Item = char>>(&mut self, iter: T) {
         for ch in iter {
@@ -755,7 +1097,7 @@ impl<const N: usize> Extend<char> for SmartString<N> {
     }
 }
 
-impl<'a, const N: usize> Extend<&'a str> for SmartString<N> {
+impl<'a, const N: usize, P: DemotionPolicy, A: Allocator> Extend<&'a str> for SmartString<N, P, A> {
     #[inline]
     fn extend<T: IntoIterator<Item = &'a str>>(&mut self, iter: T) {
         for s in iter {
@@ -764,7 +1106,9 @@ impl<'a, const N: usize> Extend<&'a str> for SmartString<N> {
     }
 }
 
-impl<'a, const N: usize> Extend<&'a char> for SmartString<N> {
+impl<'a, const N: usize, P: DemotionPolicy, A: Allocator> Extend<&'a char>
+    for SmartString<N, P, A>
+{
     #[inline]
     fn extend<T: IntoIterator<Item = &'a char>>(&mut self, iter: T) {
         for ch in iter {
@@ -773,7 +1117,7 @@ impl<'a, const N: usize> Extend<&'a char> for SmartString<N> {
     }
 }
 
-impl<const N: usize> Extend<String> for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy, A: Allocator> Extend<String> for SmartString<N, P, A> {
     #[inline]
     fn extend<T: IntoIterator<Item = String>>(&mut self, iter: T) {
         for s in iter {
@@ -782,7 +1126,9 @@ impl<const N: usize> Extend<String> for SmartString<N> {
     }
 }
 
-impl<'a, const N: usize> Extend<&'a String> for SmartString<N> {
+impl<'a, const N: usize, P: DemotionPolicy, A: Allocator> Extend<&'a String>
+    for SmartString<N, P, A>
+{
     #[inline]
     fn extend<T: IntoIterator<Item = &'a String>>(&mut self, iter: T) {
         for s in iter {
@@ -793,7 +1139,7 @@ impl<'a, const N: usize> Extend<&'a String> for SmartString<N> {
 
 // -- IO -------------------------------------------------------------------------------------------
 
-impl<const N: usize> fmt::Write for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy, A: Allocator> fmt::Write for SmartString<N, P, A> {
     #[inline]
     fn write_str(&mut self, s: &str) -> fmt::Result {
         self.push_str(s);
@@ -801,23 +1147,23 @@ impl<const N: usize> fmt::Write for SmartString<N> {
     }
 }
 
-impl<const N: usize> From<SmartString<N>> for Box<str> {
+impl<const N: usize, P: DemotionPolicy> From<SmartString<N, P>> for Box<str> {
     #[inline]
-    fn from(s: SmartString<N>) -> Self {
+    fn from(s: SmartString<N, P>) -> Self {
         s.into_boxed_str()
     }
 }
 
-impl<const N: usize> From<SmartString<N>> for Vec<u8> {
+impl<const N: usize, P: DemotionPolicy> From<SmartString<N, P>> for Vec<u8> {
     #[inline]
-    fn from(s: SmartString<N>) -> Self {
+    fn from(s: SmartString<N, P>) -> Self {
         s.into_bytes()
     }
 }
 
-impl<const N: usize> From<SmartString<N>> for Rc<str> {
+impl<const N: usize, P: DemotionPolicy> From<SmartString<N, P>> for Rc<str> {
     #[inline]
-    fn from(s: SmartString<N>) -> Self {
+    fn from(s: SmartString<N, P>) -> Self {
         // NOTE: converting an owned string into Rc/Arc necessarily allocates an Rc/Arc-managed buffer.
         // We go through `String` here for correctness and std-like ergonomics; if this turns out hot,
         // we can evaluate alternative paths and document the cost model.
@@ -825,9 +1171,9 @@ impl<const N: usize> From<SmartString<N>> for Rc<str> {
     }
 }
 
-impl<const N: usize> From<SmartString<N>> for Arc<str> {
+impl<const N: usize, P: DemotionPolicy> From<SmartString<N, P>> for Arc<str> {
     #[inline]
-    fn from(s: SmartString<N>) -> Self {
+    fn from(s: SmartString<N, P>) -> Self {
         // See note on `Rc<str>` above.
         Arc::from(s.into_string())
     }
@@ -835,7 +1181,9 @@ impl<const N: usize> From<SmartString<N>> for Arc<str> {
 
 // -- ops ------------------------------------------------------------------------------------------
 
-impl<const N: usize, T: ops::Deref<Target = str>> ops::Add<T> for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy, A: Allocator, T: ops::Deref<Target = str>> ops::Add<T>
+    for SmartString<N, P, A>
+{
     type Output = Self;
 
     #[inline]
@@ -845,7 +1193,9 @@ impl<const N: usize, T: ops::Deref<Target = str>> ops::Add<T> for SmartString<N>
     }
 }
 
-impl<const N: usize, T: ops::Deref<Target = str>> ops::AddAssign<T> for SmartString<N> {
+impl<const N: usize, P: DemotionPolicy, A: Allocator, T: ops::Deref<Target = str>> ops::AddAssign<T>
+    for SmartString<N, P, A>
+{
     #[inline]
     fn add_assign(&mut self, rhs: T) {
         self.push_str(rhs.deref());
@@ -860,43 +1210,71 @@ mod tests {
 
     use super::*;
 
+    #[cfg(target_pointer_width = "64")]
     #[test]
     fn test_size() {
-        // Default stack capacity is 30 bytes, corresponding to 32 bytes of the enum.
-        assert_eq!(mem::size_of::<SmartString>(), 32);
-
-        // NOTE: the enum layout for very small capacities depends on rustc version.
-        // Newer compilers can represent these as 24 bytes on 64-bit platforms, while older compilers
-        // (including our MSRV) may use 32 bytes. Both are acceptable; the important property is that
-        // the default type stays small (32 bytes) and larger capacities grow in pointer-sized steps.
-        let small_sizes = [
-            mem::size_of::<SmartString<0>>(),
-            mem::size_of::<SmartString<1>>(),
-            mem::size_of::<SmartString<15>>(),
-        ];
-        for size in small_sizes {
-            assert!(
-                size == 24 || size == 32,
-                "unexpected SmartString small size: {size}"
-            );
-        }
+        // The default stack capacity keeps the size equal to `String`'s.
+        assert_eq!(DEFAULT_CAPACITY, 23);
+        assert_eq!(mem::size_of::<SmartString>(), mem::size_of::<String>());
+        assert_eq!(mem::size_of::<SmartString>(), 24);
+
+        // Smaller capacities don't make it smaller than the heap representation.
+        assert_eq!(mem::size_of::<SmartString<0>>(), 24);
+        assert_eq!(mem::size_of::<SmartString<1>>(), 24);
+        assert_eq!(mem::size_of::<SmartString<15>>(), 24);
+        assert_eq!(mem::size_of::<SmartString<23>>(), 24);
+
+        // Additional bytes of capacity increases the size by size of a pointer
+        // (8 bytes on 64-bit platforms) by steps of size of a pointer.
+        assert_eq!(mem::size_of::<SmartString<24>>(), 32);
+        assert_eq!(mem::size_of::<SmartString<31>>(), 32);
+        assert_eq!(mem::size_of::<SmartString<32>>(), 40);
+        assert_eq!(mem::size_of::<SmartString<39>>(), 40);
+        assert_eq!(mem::size_of::<SmartString<46>>(), 48);
+        assert_eq!(mem::size_of::<SmartString<254>>(), 256);
+
+        // The demotion policy is a zero-sized type parameter.
+        assert_eq!(mem::size_of::<SmartString<23, DemoteBelow<8>>>(), 24);
+    }
+
+    #[test]
+    fn test_max_stack_capacity() {
+        let s = SmartString::<254>::from("x".repeat(254).as_str());
+        assert!(s.is_stack());
+        assert_eq!(s.len(), 254);
 
-        // It is unclear why the size of the enum grows to 32 bytes
-        // starting from 17 bytes of size for the stack variant.
-        assert_eq!(mem::size_of::<SmartString<16>>(), 32);
-        assert_eq!(mem::size_of::<SmartString<22>>(), 32);
+        let mut s = s;
+        s.push('x');
+        assert!(s.is_heap());
+        assert_eq!(s.len(), 255);
+    }
 
-        // The size of the enum is expected to be 32 bytes for the following capacities.
-        assert_eq!(mem::size_of::<SmartString<23>>(), 32);
-        assert_eq!(mem::size_of::<SmartString<30>>(), 32);
+    #[test]
+    fn test_heap_roundtrip_keeps_capacity() {
+        let mut string = String::with_capacity(100);
+        string.push_str("abc");
+        let ptr = string.as_ptr();
 
-        // Additional bytes of capacity increases the size of the enum by size of a pointer
-        // (8 bytes on 64-bit platforms) by steps of size of a pointer.
-        assert_eq!(mem::size_of::<SmartString<31>>(), 40);
-        assert_eq!(mem::size_of::<SmartString<38>>(), 40);
+        let mut s = SmartString::<4>::from(string);
+        assert!(s.is_heap());
+        assert_eq!(s.capacity(), 100);
 
-        assert_eq!(mem::size_of::<SmartString<39>>(), 48);
-        assert_eq!(mem::size_of::<SmartString<46>>(), 48);
+        s.push_str("defgh");
+        assert_eq!(s.as_str(), "abcdefgh");
+        assert_eq!(s.capacity(), 100);
+
+        let string = s.into_string();
+        assert_eq!(string.as_ptr(), ptr);
+        assert_eq!(string.capacity(), 100);
+    }
+
+    #[test]
+    fn test_clone_and_drop_heap() {
+        let s = SmartString::<4>::from("abcdefgh");
+        let mut t = s.clone();
+        t.push_str("ij");
+        drop(s);
+        assert_eq!(t.as_str(), "abcdefghij");
     }
 
     #[test]
@@ -1000,7 +1378,94 @@ mod tests {
         assert!(s.is_stack());
     }
 
-    #[rustversion::since(1.57)]
+    #[test]
+    fn test_demote_on_shrink_to_fit() {
+        let mut s = SmartString::<4, DemoteOnShrinkToFit>::from("abcde");
+        s.truncate(2);
+        assert!(s.is_heap());
+
+        // The requested capacity doesn't fit the stack.
+        s.shrink_to(5);
+        assert!(s.is_heap());
+        assert!(s.capacity() >= 5);
+
+        s.shrink_to(4);
+        assert!(s.is_stack());
+        assert_eq!(s.as_str(), "ab");
+
+        let mut s = SmartString::<4, DemoteOnShrinkToFit>::from("abcde");
+        s.shrink_to_fit();
+        assert!(s.is_heap());
+        s.pop();
+        s.shrink_to_fit();
+        assert!(s.is_stack());
+        assert_eq!(s.as_str(), "abcd");
+    }
+
+    #[test]
+    fn test_never_demote_ignores_shrink_to_fit() {
+        let mut s = SmartString::<4>::from("abcde");
+        s.clear();
+        s.shrink_to_fit();
+        assert!(s.is_heap());
+    }
+
+    #[test]
+    fn test_demote_below_hysteresis() {
+        let mut s = SmartString::<4, DemoteBelow<3>>::from("abcde");
+        assert!(s.is_heap());
+
+        // Lengths 3..=4 are within the hysteresis band: no demotion.
+        assert_eq!(s.pop(), Some('e'));
+        assert!(s.is_heap());
+        s.truncate(3);
+        assert!(s.is_heap());
+
+        assert_eq!(s.pop(), Some('c'));
+        assert!(s.is_stack());
+        assert_eq!(s.as_str(), "ab");
+
+        // Growing within the stack capacity doesn't promote.
+        s.push_str("cd");
+        assert!(s.is_stack());
+        s.push('e');
+        assert!(s.is_heap());
+
+        s.clear();
+        assert!(s.is_stack());
+        assert_eq!(s.as_str(), "");
+    }
+
+    #[test]
+    fn test_demote_below_applies_to_all_shrinking_ops() {
+        type S = SmartString<4, DemoteBelow<4>>;
+
+        let mut s = S::from("abcdef");
+        assert_eq!(s.drain(1..4).collect::<String>(), "bcd");
+        assert!(s.is_stack());
+        assert_eq!(s.as_str(), "aef");
+
+        let mut s = S::from("abcdef");
+        s.retain(|ch| ch != 'b' && ch != 'c' && ch != 'd');
+        assert!(s.is_stack());
+
+        let mut s = S::from("abcdef");
+        assert_eq!(s.split_off(3).as_str(), "def");
+        assert!(s.is_stack());
+
+        let mut s = S::from("abcdef");
+        s.replace_range(..4, "");
+        assert!(s.is_stack());
+        assert_eq!(s.as_str(), "ef");
+
+        let mut s = S::from("abcd€");
+        assert_eq!(s.remove(4), '€');
+        assert!(s.is_heap()); // 4 is not below the threshold
+        assert_eq!(s.remove(0), 'a');
+        assert!(s.is_stack());
+        assert_eq!(s.as_str(), "bcd");
+    }
+
     #[test]
     fn test_try_reserve_transitions_stack_to_heap() {
         let mut s = SmartString::<4>::from("ab");
@@ -1016,7 +1481,6 @@ mod tests {
         assert_eq!(s.as_str(), "ab");
     }
 
-    #[rustversion::since(1.57)]
     #[test]
     fn test_try_reserve_exact_transitions_stack_to_heap() {
         let mut s = SmartString::<4>::from("ab");
@@ -1028,6 +1492,68 @@ mod tests {
         assert_eq!(s.as_str(), "ab");
     }
 
+    #[test]
+    fn test_try_push_transitions_stack_to_heap() {
+        let mut s = SmartString::<4>::new();
+        s.try_push_str("abc").unwrap();
+        s.try_push('d').unwrap();
+        assert!(s.is_stack());
+
+        s.try_push('€').unwrap();
+        assert!(s.is_heap());
+        s.try_push_str("fg").unwrap();
+        assert_eq!(s, "abcd€fg");
+    }
+
+    #[test]
+    fn test_try_insert_transitions_stack_to_heap() {
+        let mut s = SmartString::<4>::from("ad");
+        s.try_insert_str(1, "b").unwrap();
+        s.try_insert(2, 'c').unwrap();
+        assert!(s.is_stack());
+        assert_eq!(s, "abcd");
+
+        s.try_insert_str(0, "__").unwrap();
+        assert!(s.is_heap());
+        assert_eq!(s, "__abcd");
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid index or char boundary")]
+    fn test_try_insert_str_panics_on_char_boundary() {
+        let mut s = SmartString::<4>::from("€");
+        let _ = s.try_insert_str(1, "a");
+    }
+
+    #[test]
+    fn test_try_extend_and_try_from_str() {
+        let mut s = SmartString::<4>::try_from_str("ab").unwrap();
+        assert!(s.is_stack());
+        s.try_extend(["c", "d"]).unwrap();
+        assert!(s.is_stack());
+        s.try_extend([String::from("ef")]).unwrap();
+        assert!(s.is_heap());
+        assert_eq!(s, "abcdef");
+
+        let s = SmartString::<4>::try_from_str("abcde").unwrap();
+        assert!(s.is_heap());
+        assert_eq!(s.capacity(), 5);
+    }
+
+    #[test]
+    fn test_try_clone_keeps_storage() {
+        let s = SmartString::<4>::from("ab");
+        let clone = s.try_clone().unwrap();
+        assert!(clone.is_stack());
+        assert_eq!(clone, s);
+
+        let s = SmartString::<4>::from("abcde");
+        let clone = s.try_clone().unwrap();
+        assert!(clone.is_heap());
+        assert_ne!(clone.as_ptr(), s.as_ptr());
+        assert_eq!(clone, s);
+    }
+
     #[test]
     fn test_extend_str_transitions_stack_to_heap() {
         let mut s = SmartString::<4>::new();
@@ -1109,6 +1635,16 @@ mod tests {
         let other = s.split_off(5);
         assert_eq!(s.as_str(), "hello");
         assert_eq!(other.as_str(), "!");
+        assert!(s.is_stack());
+        assert!(other.is_stack());
+
+        let mut s = SmartString::<4>::from("hello!");
+        assert!(s.is_heap());
+
+        let other = s.split_off(2);
+        assert_eq!(s.as_str(), "he");
+        assert_eq!(other.as_str(), "llo!");
+        assert!(s.is_heap());
         assert!(other.is_stack());
     }
 
@@ -1119,6 +1655,61 @@ mod tests {
         assert_eq!(s.as_str(), "acdb");
     }
 
+    #[test]
+    fn test_replace_range_promotes_only_on_overflow() {
+        let mut s = SmartString::<4>::from("a€");
+        s.replace_range(1.., "bcd");
+        assert!(s.is_stack());
+        assert_eq!(s.as_str(), "abcd");
+
+        s.replace_range(1..3, "€");
+        assert!(s.is_heap());
+        assert_eq!(s.as_str(), "a€d");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_replace_range_panics_on_char_boundary() {
+        let mut s = SmartString::<4>::from("a€");
+        s.replace_range(..2, "");
+    }
+
+    #[test]
+    fn test_retain_stays_on_stack() {
+        let mut s = SmartString::<8>::from("a€b€");
+        s.retain(|ch| ch != '€');
+        assert!(s.is_stack());
+        assert_eq!(s.as_str(), "ab");
+
+        let mut s = SmartString::<4>::from("a€b€");
+        s.retain(|ch| ch != '€');
+        assert!(s.is_heap());
+        assert_eq!(s.as_str(), "ab");
+    }
+
+    #[test]
+    fn test_retain_panic_keeps_retained_prefix() {
+        let mut s = SmartString::<4>::from("ab€cdef");
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            s.retain(|ch| match ch {
+                'd' => panic!("boom"),
+                ch => ch != 'b',
+            });
+        }));
+        assert!(result.is_err());
+        assert!(s.is_heap());
+        assert_eq!(s.as_str(), "a€c");
+    }
+
+    #[test]
+    fn test_drain_stays_on_stack() {
+        let mut s = SmartString::<8>::from("a€bc");
+        let drained: String = s.drain(..4).collect();
+        assert!(s.is_stack());
+        assert_eq!(drained, "a€");
+        assert_eq!(s.as_str(), "bc");
+    }
+
     #[test]
     fn test_len_and_is_empty() {
         let s = SmartString::<4>::new();
@@ -1205,6 +1796,19 @@ mod tests {
         assert_eq!(SmartString::<4>::from(&owned).as_str(), "ab");
     }
 
+    #[test]
+    fn test_from_pascal_string_long_picks_stack_or_heap() {
+        let ps = PascalStringLong::<300>::try_from("abcd").unwrap();
+        let s = SmartString::<4>::from(ps);
+        assert!(s.is_stack());
+        assert_eq!(&ps, s);
+
+        let ps = PascalStringLong::<300>::try_from("x".repeat(300).as_str()).unwrap();
+        let s = SmartString::<4>::from(ps);
+        assert!(s.is_heap());
+        assert_eq!(s.len(), 300);
+    }
+
     #[test]
     fn test_into_vec_u8_rc_arc_str() {
         let bytes: Vec<u8> = SmartString::<4>::from("ab").into();
@@ -0,0 +1,67 @@
+/// Decides when a heap-stored `SmartString` moves back to the stack.
+///
+/// Promotion (stack → heap) always happens when the stack capacity is exceeded; demotion is up to the policy.
+/// The policy is consulted only when the contents fit the stack: `len <= stack_capacity`.
+pub trait DemotionPolicy {
+    /// Whether to demote after a mutation that may have shortened the string: `truncate`, `clear`, `pop`, `remove`,
+    /// `retain`, `drain`, `split_off`, `replace_range`, and in-place deserialization.
+    fn demote_on_shrink(len: usize, stack_capacity: usize) -> bool;
+
+    /// Whether to demote on `shrink_to_fit` or `shrink_to` (only if the requested capacity fits the stack, too).
+    fn demote_on_shrink_to(len: usize, stack_capacity: usize) -> bool;
+}
+
+/// Never demotes implicitly; call `SmartString::try_into_stack` to demote explicitly.
+///
+/// This is the default policy: implicit demotion can introduce surprising realloc/dealloc churn in real workloads
+/// (e.g. shorten → re-grow).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct NeverDemote;
+
+/// Demotes on `shrink_to_fit` / `shrink_to`, i.e. only when explicitly asked to release memory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct DemoteOnShrinkToFit;
+
+/// Demotes as soon as the length drops below `THRESHOLD`, and on `shrink_to_fit` / `shrink_to`.
+///
+/// With `THRESHOLD` smaller than the stack capacity `N`, the lengths `THRESHOLD..=N` form a hysteresis band:
+/// a string that got promoted stays on the heap there, so lengths oscillating around `N` don't move the string
+/// back and forth.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct DemoteBelow<const THRESHOLD: usize>;
+
+impl DemotionPolicy for NeverDemote {
+    #[inline(always)]
+    fn demote_on_shrink(_len: usize, _stack_capacity: usize) -> bool {
+        false
+    }
+
+    #[inline(always)]
+    fn demote_on_shrink_to(_len: usize, _stack_capacity: usize) -> bool {
+        false
+    }
+}
+
+impl DemotionPolicy for DemoteOnShrinkToFit {
+    #[inline(always)]
+    fn demote_on_shrink(_len: usize, _stack_capacity: usize) -> bool {
+        false
+    }
+
+    #[inline(always)]
+    fn demote_on_shrink_to(_len: usize, _stack_capacity: usize) -> bool {
+        true
+    }
+}
+
+impl<const THRESHOLD: usize> DemotionPolicy for DemoteBelow<THRESHOLD> {
+    #[inline(always)]
+    fn demote_on_shrink(len: usize, _stack_capacity: usize) -> bool {
+        len < THRESHOLD
+    }
+
+    #[inline(always)]
+    fn demote_on_shrink_to(_len: usize, _stack_capacity: usize) -> bool {
+        true
+    }
+}
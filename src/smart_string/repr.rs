@@ -0,0 +1,420 @@
+use alloc::string::String;
+use alloc::string::ToString;
+use core::mem;
+use core::mem::ManuallyDrop;
+use core::ops;
+use core::ptr;
+use core::slice;
+use core::str::from_utf8_unchecked;
+use core::str::from_utf8_unchecked_mut;
+
+use super::heap::HeapString;
+use crate::allocator::Allocator;
+use crate::allocator::Global;
+use crate::allocator::TryReserveError;
+use crate::PascalString;
+
+/// The value of the first byte of a heap-backed representation.
+///
+/// The first byte of a stack-backed representation is `PascalString::len` (it is `repr(C)` with `len` first),
+/// which never exceeds `N < HEAP_MARKER`, so the marker unambiguously tells the variants apart.
+const HEAP_MARKER: u8 = u8::MAX;
+
+/// The capacity that fits into the inline buffer without growing the representation beyond the heap one.
+pub(super) const INLINE_CAPACITY: usize = mem::size_of::<HeapRepr>() - 1;
+
+/// Compact storage of `SmartString`: a union of an inline `PascalString<N>` and the raw parts of a heap buffer,
+/// plus the allocator of the latter.
+///
+/// The size of the union is `max(size_of::<HeapRepr>(), N + 1)` rounded up to the pointer alignment, so for
+/// `N <= INLINE_CAPACITY` it is as big as the heap representation itself (24 bytes on 64-bit targets);
+/// a zero-sized allocator such as `Global` adds nothing.
+///
+/// Invariants:
+///
+/// - the first byte of `raw` is `HEAP_MARKER` iff the `heap` field is active;
+/// - an active `heap` field holds the raw parts of a `HeapString` allocated by `alloc`, owned by this value.
+pub(super) struct Repr<const N: usize, A: Allocator> {
+    raw: RawRepr<N>,
+    alloc: A,
+}
+
+#[repr(C)]
+union RawRepr<const N: usize> {
+    heap: HeapRepr,
+    stack: PascalString<N>,
+}
+
+/// The raw parts of a `HeapString`, with the first byte set to `HEAP_MARKER`.
+#[cfg(target_pointer_width = "64")]
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct HeapRepr {
+    /// Little-endian `capacity << 8 | HEAP_MARKER`, so the marker is the first byte in memory.
+    tagged_capacity: usize,
+    ptr: *mut u8,
+    len: usize,
+}
+
+/// The raw parts of a `HeapString`, with the first byte set to `HEAP_MARKER`.
+///
+/// On targets narrower than 64 bits a capacity stripped of 8 bits would be too limiting,
+/// so the marker takes a word of its own.
+#[cfg(not(target_pointer_width = "64"))]
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct HeapRepr {
+    /// Little-endian `HEAP_MARKER`, so the marker is the first byte in memory.
+    tag: usize,
+    capacity: usize,
+    ptr: *mut u8,
+    len: usize,
+}
+
+pub(super) enum StorageRef<'a, const N: usize> {
+    Heap(&'a str),
+    Stack(&'a PascalString<N>),
+}
+
+pub(super) enum StorageMut<'a, const N: usize, A: Allocator> {
+    Heap(HeapMut<'a, A>),
+    Stack(&'a mut PascalString<N>),
+}
+
+/// Gives access to a heap-backed representation as a `HeapString`, writing its raw parts back on drop.
+pub(super) struct HeapMut<'a, A: Allocator> {
+    heap: &'a mut HeapRepr,
+    string: ManuallyDrop<HeapString<&'a A>>,
+}
+
+// SAFETY: `Repr` owns its heap buffer exclusively, exactly like `String` does.
+unsafe impl<const N: usize, A: Allocator + Send> Send for Repr<N, A> {}
+// SAFETY: `Repr` owns its heap buffer exclusively, exactly like `String` does.
+unsafe impl<const N: usize, A: Allocator + Sync> Sync for Repr<N, A> {}
+
+impl HeapRepr {
+    #[cfg(target_pointer_width = "64")]
+    const MAX_CAPACITY: usize = usize::MAX >> 8;
+
+    /// Takes the raw parts of `string`, leaving the ownership of its buffer to the caller.
+    #[inline]
+    fn from_heap_string<A: Allocator>(string: HeapString<A>) -> Self {
+        let (ptr, len, capacity) = string.into_raw_parts();
+        Self::new(ptr, len, capacity)
+    }
+
+    /// Appends `parts` to `string`, which has room for them, and takes its raw parts.
+    #[inline]
+    fn from_parts<A: Allocator>(mut string: HeapString<A>, parts: &[&str]) -> Self {
+        for part in parts {
+            string.push_str(part);
+        }
+        Self::from_heap_string(string)
+    }
+
+    #[cfg(target_pointer_width = "64")]
+    #[inline]
+    fn new(ptr: *mut u8, len: usize, capacity: usize) -> Self {
+        // Unreachable in practice: user-space address spaces are narrower than 56 bits.
+        assert!(
+            capacity <= Self::MAX_CAPACITY,
+            "SmartString capacity overflow"
+        );
+        Self {
+            tagged_capacity: (capacity << 8 | HEAP_MARKER as usize).to_le(),
+            ptr,
+            len,
+        }
+    }
+
+    #[cfg(not(target_pointer_width = "64"))]
+    #[inline]
+    fn new(ptr: *mut u8, len: usize, capacity: usize) -> Self {
+        Self {
+            tag: (HEAP_MARKER as usize).to_le(),
+            capacity,
+            ptr,
+            len,
+        }
+    }
+
+    #[cfg(target_pointer_width = "64")]
+    #[inline]
+    fn capacity(&self) -> usize {
+        usize::from_le(self.tagged_capacity) >> 8
+    }
+
+    #[cfg(not(target_pointer_width = "64"))]
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// # Safety
+    ///
+    /// `self` must hold the raw parts of a live `HeapString`.
+    #[inline]
+    unsafe fn as_str<'a>(&self) -> &'a str {
+        // SAFETY: the caller guarantees `ptr[..len]` is an initialized UTF-8 buffer of a live `HeapString`.
+        from_utf8_unchecked(slice::from_raw_parts(self.ptr, self.len))
+    }
+
+    /// # Safety
+    ///
+    /// `self` must hold the raw parts of a live `HeapString`.
+    #[inline]
+    unsafe fn as_mut_str<'a>(&mut self) -> &'a mut str {
+        // SAFETY: the caller guarantees `ptr[..len]` is an initialized UTF-8 buffer of a live `HeapString`.
+        from_utf8_unchecked_mut(slice::from_raw_parts_mut(self.ptr, self.len))
+    }
+
+    /// # Safety
+    ///
+    /// `self` must hold the raw parts of a live `HeapString` allocated by `alloc`; the caller takes its ownership.
+    #[inline]
+    unsafe fn into_heap_string<A: Allocator>(self, alloc: A) -> HeapString<A> {
+        // SAFETY: the caller guarantees these are the raw parts of a live `HeapString` allocated by `alloc`.
+        HeapString::from_raw_parts_in(self.ptr, self.len, self.capacity(), alloc)
+    }
+}
+
+impl<const N: usize> RawRepr<N> {
+    const STACK_CAPACITY: usize = {
+        assert!(
+            N < HEAP_MARKER as usize,
+            "SmartString max stack capacity is 254"
+        );
+        N
+    };
+
+    #[inline]
+    const fn from_stack(stack: PascalString<N>) -> Self {
+        // This line triggers a compile time error, if N > 254.
+        let _ = Self::STACK_CAPACITY;
+
+        Self { stack }
+    }
+
+    #[inline(always)]
+    fn is_heap(&self) -> bool {
+        // SAFETY: the first byte is initialized in both variants: it's either `PascalString::len`
+        // or the first byte of the heap tag word.
+        let tag = unsafe { ptr::read(self as *const Self as *const u8) };
+        tag == HEAP_MARKER
+    }
+}
+
+// NOTE: const fns with trait bounds need Rust 1.61, hence the const constructor is only for `Global`.
+impl<const N: usize> Repr<N, Global> {
+    #[inline]
+    pub const fn from_stack_global(stack: PascalString<N>) -> Self {
+        Self {
+            raw: RawRepr::from_stack(stack),
+            alloc: Global,
+        }
+    }
+
+    #[inline]
+    pub fn from_string(string: String) -> Self {
+        Self::from_heap(HeapString::from_string(string), Global)
+    }
+
+    #[inline]
+    pub fn into_string(self) -> String {
+        let this = ManuallyDrop::new(self);
+        if this.is_heap() {
+            // SAFETY: the `heap` field is active; the ownership of the buffer moves out of `this`,
+            // which is never dropped (and `Global` has nothing to drop).
+            unsafe { this.raw.heap.into_heap_string(Global) }.into_string()
+        } else {
+            // SAFETY: the `stack` field is active.
+            unsafe { this.raw.stack.to_string() }
+        }
+    }
+}
+
+impl<const N: usize, A: Allocator> Repr<N, A> {
+    #[inline]
+    pub fn from_stack(stack: PascalString<N>, alloc: A) -> Self {
+        Self {
+            raw: RawRepr::from_stack(stack),
+            alloc,
+        }
+    }
+
+    /// Takes over the buffer of `string`, which must have been allocated by `alloc` (or a reference to it).
+    #[inline]
+    fn from_heap<B: Allocator>(string: HeapString<B>, alloc: A) -> Self {
+        Self {
+            raw: RawRepr {
+                heap: HeapRepr::from_heap_string(string),
+            },
+            alloc,
+        }
+    }
+
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    #[inline(always)]
+    pub fn is_heap(&self) -> bool {
+        self.raw.is_heap()
+    }
+
+    /// Frees the heap buffer, if any, leaving `raw` in an unspecified state.
+    #[inline]
+    fn drop_heap(&mut self) {
+        if self.is_heap() {
+            // SAFETY: the `heap` field is active and holds the raw parts of a `HeapString` allocated by `alloc`.
+            drop(unsafe { self.raw.heap.into_heap_string(&self.alloc) });
+        }
+    }
+
+    /// Replaces the contents with `stack`, freeing the heap buffer, if any.
+    #[inline]
+    pub fn set_stack(&mut self, stack: PascalString<N>) {
+        self.drop_heap();
+        self.raw = RawRepr::from_stack(stack);
+    }
+
+    /// Moves to a new heap buffer of `capacity` bytes (at least), holding the concatenation of `parts`.
+    pub fn try_set_heap(&mut self, capacity: usize, parts: &[&str]) -> Result<(), TryReserveError> {
+        let len = parts.iter().map(|s| s.len()).sum();
+        let string = HeapString::try_with_capacity_in(Ord::max(capacity, len), &self.alloc)?;
+        let heap = HeapRepr::from_parts(string, parts);
+        self.drop_heap();
+        self.raw = RawRepr { heap };
+        Ok(())
+    }
+
+    /// Infallible variant of [`try_set_heap`](Self::try_set_heap).
+    #[inline]
+    pub fn set_heap(&mut self, capacity: usize, parts: &[&str]) {
+        let len = parts.iter().map(|s| s.len()).sum();
+        let string = HeapString::with_capacity_in(Ord::max(capacity, len), &self.alloc);
+        let heap = HeapRepr::from_parts(string, parts);
+        self.drop_heap();
+        self.raw = RawRepr { heap };
+    }
+
+    #[inline]
+    pub fn storage(&self) -> StorageRef<'_, N> {
+        if self.is_heap() {
+            // SAFETY: the `heap` field is active and holds the raw parts of a `HeapString` owned by `self`.
+            StorageRef::Heap(unsafe { self.raw.heap.as_str() })
+        } else {
+            // SAFETY: the `stack` field is active.
+            StorageRef::Stack(unsafe { &self.raw.stack })
+        }
+    }
+
+    #[inline]
+    pub fn storage_mut(&mut self) -> StorageMut<'_, N, A> {
+        if self.is_heap() {
+            // SAFETY: the `heap` field is active.
+            let heap = unsafe { &mut self.raw.heap };
+            // SAFETY: `heap` holds the raw parts of a `HeapString` allocated by `alloc` and owned by `self`;
+            // `HeapMut` writes them back on drop, and `self` is mutably borrowed meanwhile, so nobody can observe
+            // the stale parts.
+            let string = ManuallyDrop::new(unsafe { heap.into_heap_string(&self.alloc) });
+            StorageMut::Heap(HeapMut { heap, string })
+        } else {
+            // SAFETY: the `stack` field is active.
+            StorageMut::Stack(unsafe { &mut self.raw.stack })
+        }
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        match self.storage() {
+            StorageRef::Heap(s) => s,
+            StorageRef::Stack(s) => s.as_str(),
+        }
+    }
+
+    #[inline]
+    pub fn as_mut_str(&mut self) -> &mut str {
+        if self.is_heap() {
+            // SAFETY: the `heap` field is active and holds the raw parts of a `HeapString` owned by `self`.
+            unsafe { self.raw.heap.as_mut_str() }
+        } else {
+            // SAFETY: the `stack` field is active.
+            unsafe { self.raw.stack.as_mut_str() }
+        }
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        if self.is_heap() {
+            // SAFETY: the `heap` field is active.
+            unsafe { self.raw.heap.capacity() }
+        } else {
+            N
+        }
+    }
+}
+
+impl<const N: usize, A: Allocator + Clone> Repr<N, A> {
+    /// Clones the value; a heap-stored one is copied into an exact-size buffer.
+    #[inline]
+    pub fn try_clone(&self) -> Result<Self, TryReserveError> {
+        let alloc = self.alloc.clone();
+        match self.storage() {
+            StorageRef::Heap(s) => {
+                let mut new = Self::from_stack(PascalString::new(), alloc);
+                new.try_set_heap(s.len(), &[s])?;
+                Ok(new)
+            }
+            StorageRef::Stack(s) => Ok(Self::from_stack(*s, alloc)),
+        }
+    }
+}
+
+impl<const N: usize, A: Allocator + Clone> Clone for Repr<N, A> {
+    #[inline]
+    fn clone(&self) -> Self {
+        let alloc = self.alloc.clone();
+        match self.storage() {
+            StorageRef::Heap(s) => {
+                let mut new = Self::from_stack(PascalString::new(), alloc);
+                new.set_heap(s.len(), &[s]);
+                new
+            }
+            StorageRef::Stack(s) => Self::from_stack(*s, alloc),
+        }
+    }
+}
+
+impl<const N: usize, A: Allocator> Drop for Repr<N, A> {
+    #[inline]
+    fn drop(&mut self) {
+        self.drop_heap();
+    }
+}
+
+impl<'a, A: Allocator> ops::Deref for HeapMut<'a, A> {
+    type Target = HeapString<&'a A>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.string
+    }
+}
+
+impl<A: Allocator> ops::DerefMut for HeapMut<'_, A> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.string
+    }
+}
+
+impl<A: Allocator> Drop for HeapMut<'_, A> {
+    #[inline]
+    fn drop(&mut self) {
+        // SAFETY: `string` is never used again.
+        let string = unsafe { ManuallyDrop::take(&mut self.string) };
+        *self.heap = HeapRepr::from_heap_string(string);
+    }
+}
@@ -1,4 +1,5 @@
 use core::fmt;
+use core::marker::PhantomData;
 
 use serde::de::Error;
 use serde::de::Unexpected;
@@ -8,16 +9,22 @@ use serde::Deserializer;
 use serde::Serialize;
 use serde::Serializer;
 
+use crate::allocator::Allocator;
+use crate::smart_string::DemotionPolicy;
 use crate::SmartString;
 
-struct StringVisitor<const CAPACITY: usize>;
+struct StringVisitor<const CAPACITY: usize, P, A>(PhantomData<(P, A)>);
 
-struct StringInPlaceVisitor<'a, const CAPACITY: usize>(&'a mut SmartString<CAPACITY>);
+struct StringInPlaceVisitor<'a, const CAPACITY: usize, P, A: Allocator>(
+    &'a mut SmartString<CAPACITY, P, A>,
+);
 
 // -------------------------------------------------------------------------------------------------
 
-impl<'de, const CAPACITY: usize> Visitor<'de> for StringVisitor<CAPACITY> {
-    type Value = SmartString<CAPACITY>;
+impl<'de, const CAPACITY: usize, P: DemotionPolicy, A: Allocator + Default> Visitor<'de>
+    for StringVisitor<CAPACITY, P, A>
+{
+    type Value = SmartString<CAPACITY, P, A>;
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         formatter.write_str("a string")
@@ -27,7 +34,7 @@ impl<'de, const CAPACITY: usize> Visitor<'de> for StringVisitor<CAPACITY> {
     where
         E: Error,
     {
-        Ok(SmartString::from(v))
+        Ok(SmartString::from_str_in(v, A::default()))
     }
 
     fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
@@ -41,7 +48,9 @@ impl<'de, const CAPACITY: usize> Visitor<'de> for StringVisitor<CAPACITY> {
     }
 }
 
-impl<'a, 'de, const CAPACITY: usize> Visitor<'de> for StringInPlaceVisitor<'a, CAPACITY> {
+impl<'a, 'de, const CAPACITY: usize, P: DemotionPolicy, A: Allocator> Visitor<'de>
+    for StringInPlaceVisitor<'a, CAPACITY, P, A>
+{
     type Value = ();
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
@@ -52,8 +61,7 @@ impl<'a, 'de, const CAPACITY: usize> Visitor<'de> for StringInPlaceVisitor<'a, C
     where
         E: Error,
     {
-        self.0.clear();
-        self.0.push_str(v);
+        self.0.set_str(v);
         Ok(())
     }
 
@@ -70,18 +78,22 @@ impl<'a, 'de, const CAPACITY: usize> Visitor<'de> for StringInPlaceVisitor<'a, C
 
 // -------------------------------------------------------------------------------------------------
 
-impl<const CAPACITY: usize> Serialize for SmartString<CAPACITY> {
+impl<const CAPACITY: usize, P: DemotionPolicy, A: Allocator> Serialize
+    for SmartString<CAPACITY, P, A>
+{
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         serializer.serialize_str(self)
     }
 }
 
-impl<'de, const CAPACITY: usize> Deserialize<'de> for SmartString<CAPACITY> {
+impl<'de, const CAPACITY: usize, P: DemotionPolicy, A: Allocator + Default> Deserialize<'de>
+    for SmartString<CAPACITY, P, A>
+{
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        deserializer.deserialize_string(StringVisitor)
+        deserializer.deserialize_string(StringVisitor(PhantomData))
     }
 
     fn deserialize_in_place<D>(deserializer: D, place: &mut Self) -> Result<(), D::Error>
@@ -141,4 +153,24 @@ mod tests {
         assert_eq!(place.as_str(), "abcde");
         assert!(place.is_heap());
     }
+
+    #[test]
+    fn test_deserialize_in_place_applies_demotion_policy() {
+        use crate::smart_string::DemoteBelow;
+
+        let mut place = SmartString::<4, DemoteBelow<3>>::from("abcde");
+        assert!(place.is_heap());
+
+        // Within the hysteresis band: stays on heap.
+        let mut de = serde_json::Deserializer::from_str(r#""abc""#);
+        SmartString::deserialize_in_place(&mut de, &mut place).unwrap();
+        assert_eq!(place.as_str(), "abc");
+        assert!(place.is_heap());
+
+        // Below the threshold: demotes.
+        let mut de = serde_json::Deserializer::from_str(r#""ab""#);
+        SmartString::deserialize_in_place(&mut de, &mut place).unwrap();
+        assert_eq!(place.as_str(), "ab");
+        assert!(place.is_stack());
+    }
 }
@@ -0,0 +1,76 @@
+use core::convert::Infallible;
+
+use ufmt::uDebug;
+use ufmt::uDisplay;
+use ufmt::uWrite;
+use ufmt::Formatter;
+
+use crate::allocator::Allocator;
+use crate::display_ext::write_debug_str;
+use crate::smart_string::DemotionPolicy;
+use crate::SmartString;
+
+impl<const N: usize, P: DemotionPolicy, A: Allocator> uWrite for SmartString<N, P, A> {
+    type Error = Infallible;
+
+    #[inline]
+    fn write_str(&mut self, s: &str) -> Result<(), Self::Error> {
+        self.push_str(s);
+        Ok(())
+    }
+
+    #[inline]
+    fn write_char(&mut self, c: char) -> Result<(), Self::Error> {
+        self.push(c);
+        Ok(())
+    }
+}
+
+impl<const N: usize, P: DemotionPolicy, A: Allocator> uDisplay for SmartString<N, P, A> {
+    #[inline]
+    fn fmt<W: uWrite + ?Sized>(&self, f: &mut Formatter<'_, W>) -> Result<(), W::Error> {
+        f.write_str(self)
+    }
+}
+
+impl<const N: usize, P: DemotionPolicy, A: Allocator> uDebug for SmartString<N, P, A> {
+    fn fmt<W: uWrite + ?Sized>(&self, f: &mut Formatter<'_, W>) -> Result<(), W::Error> {
+        f.write_str("SmartString<")?;
+        uDisplay::fmt(&N, f)?;
+        f.write_str(">(")?;
+        write_debug_str(self, f)?;
+        f.write_str(")")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ufmt::uwrite;
+
+    use super::*;
+    use crate::PascalString;
+    use crate::UDisplayExt;
+
+    #[test]
+    fn test_uwrite_promotes_to_heap() {
+        let mut s = SmartString::<4>::new();
+        uwrite!(s, "{}", 1234_u16).unwrap();
+        assert!(s.is_stack());
+
+        uwrite!(s, "{}", '5').unwrap();
+        assert!(s.is_heap());
+        assert_eq!(s, "12345");
+    }
+
+    #[test]
+    fn test_udisplay_and_udebug() {
+        let s = SmartString::<4>::from("a\"b\n");
+
+        let display: SmartString<4> = s.to_ufmt();
+        assert_eq!(display, "a\"b\n");
+
+        let mut debug = PascalString::<32>::new();
+        uwrite!(debug, "{:?}", s).unwrap();
+        assert_eq!(debug, format!("{s:?}"));
+    }
+}
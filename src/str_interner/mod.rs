@@ -0,0 +1,330 @@
+//! String interning on top of [`StrSet`], with `Copy` [`Symbol`] handles.
+
+use core::fmt;
+use core::hash::BuildHasher;
+use core::iter::Enumerate;
+use std::collections::hash_map::RandomState;
+
+use crate::str_stack::StrStackIter;
+use crate::StrSet;
+
+mod sync;
+
+pub use sync::SyncStrInterner;
+
+/// A handle to a string interned in a [`StrInterner`].
+///
+/// Symbols are numbered from zero in the order the strings were first interned, so the symbols of a seed list
+/// (see [`StrInterner::from_static`]) are known in advance and can be declared as constants:
+///
+/// ```
+/// use smart_string::str_interner::Symbol;
+/// use smart_string::StrInterner;
+///
+/// const GET: Symbol = Symbol::from_u32(0);
+/// const POST: Symbol = Symbol::from_u32(1);
+///
+/// let mut methods = StrInterner::from_static(&["GET", "POST"]);
+/// assert_eq!(methods.intern("POST"), POST);
+/// assert_eq!(methods.resolve(GET), "GET");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    #[inline]
+    pub const fn from_u32(id: u32) -> Self {
+        Self(id)
+    }
+
+    #[inline]
+    pub const fn as_u32(self) -> u32 {
+        self.0
+    }
+
+    #[inline]
+    pub const fn to_usize(self) -> usize {
+        self.0 as usize
+    }
+
+    /// Converts a `StrSet` id; there are at most `u32::MAX + 1` symbols.
+    #[inline]
+    fn from_id(id: usize) -> Self {
+        match u32::try_from(id) {
+            Ok(id) => Self(id),
+            Err(_) => panic!("StrInterner symbol overflow"),
+        }
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Symbol").field(&self.0).finish()
+    }
+}
+
+/// An interner that stores each unique string once, in a single buffer, and hands out [`Symbol`]s for them.
+///
+/// ```
+/// use smart_string::StrInterner;
+///
+/// let mut interner = StrInterner::new();
+/// let foo = interner.intern("foo");
+/// let bar = interner.intern("bar");
+/// assert_eq!(interner.intern("foo"), foo);
+///
+/// assert_eq!(interner.get("bar"), Some(bar));
+/// assert_eq!(interner.get("baz"), None);
+/// assert_eq!(interner.resolve(foo), "foo");
+/// ```
+///
+/// See [`SyncStrInterner`] for a variant that can be shared between threads.
+#[derive(Clone, Default)]
+pub struct StrInterner<S = RandomState> {
+    set: StrSet<S>,
+}
+
+impl StrInterner {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty interner with room for at least `entries` strings of `bytes` bytes in total.
+    #[inline]
+    pub fn with_capacity(entries: usize, bytes: usize) -> Self {
+        Self::with_capacity_and_hasher(entries, bytes, RandomState::new())
+    }
+
+    /// Creates an interner pre-seeded with `strings`; the symbols follow the order of the list, duplicates aside.
+    pub fn from_static(strings: &[&'static str]) -> Self {
+        let bytes = strings.iter().map(|s| s.len()).sum();
+        let mut interner = Self::with_capacity(strings.len(), bytes);
+        interner.extend(strings.iter().copied());
+        interner
+    }
+}
+
+impl<S> StrInterner<S> {
+    #[inline]
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self {
+            set: StrSet::with_hasher(hash_builder),
+        }
+    }
+
+    /// Creates an empty interner with room for at least `entries` strings of `bytes` bytes in total.
+    #[inline]
+    pub fn with_capacity_and_hasher(entries: usize, bytes: usize, hash_builder: S) -> Self {
+        Self {
+            set: StrSet::with_capacity_and_hasher(entries, bytes, hash_builder),
+        }
+    }
+
+    /// Returns the number of interned strings.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    /// Returns the string of a symbol, panicking if the symbol doesn't belong to this interner.
+    #[inline]
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        match self.try_resolve(symbol) {
+            Some(s) => s,
+            None => panic!("unknown symbol {:?}", symbol),
+        }
+    }
+
+    /// Returns the string of a symbol, or `None` if the symbol doesn't belong to this interner.
+    #[inline]
+    pub fn try_resolve(&self, symbol: Symbol) -> Option<&str> {
+        self.set.get(symbol.to_usize())
+    }
+
+    /// Returns an iterator over the symbols and their strings, in the order of the symbols.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            inner: self.set.iter().enumerate(),
+        }
+    }
+
+    #[inline]
+    pub fn as_str_set(&self) -> &StrSet<S> {
+        &self.set
+    }
+
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.set.shrink_to_fit();
+    }
+}
+
+impl<S: BuildHasher> StrInterner<S> {
+    /// Returns the symbol of the string, interning it first if it's new.
+    #[inline]
+    pub fn intern(&mut self, s: &str) -> Symbol {
+        Symbol::from_id(self.set.insert(s))
+    }
+
+    /// Returns the symbol of the string, if it has been interned.
+    #[inline]
+    pub fn get(&self, s: &str) -> Option<Symbol> {
+        self.set.get_id(s).map(Symbol::from_id)
+    }
+
+    #[inline]
+    pub fn reserve(&mut self, entries: usize, bytes: usize) {
+        self.set.reserve(entries, bytes);
+    }
+}
+
+// -- Common traits --------------------------------------------------------------------------------
+
+impl<S> fmt::Debug for StrInterner<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Interners are equal if they map the same symbols to the same strings.
+impl<S> PartialEq for StrInterner<S> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.set.as_str_stack() == other.set.as_str_stack()
+    }
+}
+
+impl<S> Eq for StrInterner<S> {}
+
+impl<S> From<StrSet<S>> for StrInterner<S> {
+    /// Uses the ids of the set as symbols.
+    #[inline]
+    fn from(set: StrSet<S>) -> Self {
+        Self { set }
+    }
+}
+
+impl<'a, S: BuildHasher> Extend<&'a str> for StrInterner<S> {
+    fn extend<T: IntoIterator<Item = &'a str>>(&mut self, iter: T) {
+        for s in iter {
+            self.intern(s);
+        }
+    }
+}
+
+impl<'a, S: BuildHasher + Default> FromIterator<&'a str> for StrInterner<S> {
+    fn from_iter<T: IntoIterator<Item = &'a str>>(iter: T) -> Self {
+        let mut interner = Self::default();
+        interner.extend(iter);
+        interner
+    }
+}
+
+impl<'a, S> IntoIterator for &'a StrInterner<S> {
+    type Item = (Symbol, &'a str);
+    type IntoIter = Iter<'a>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// -- Iterator -------------------------------------------------------------------------------------
+
+/// An iterator over the symbols of a [`StrInterner`] and their strings, created by [`StrInterner::iter`].
+pub struct Iter<'a> {
+    inner: Enumerate<StrStackIter<'a>>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (Symbol, &'a str);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(id, s)| (Symbol::from_id(id), s))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern_resolve() {
+        let mut interner = StrInterner::new();
+        assert!(interner.is_empty());
+
+        let a = interner.intern("a");
+        let b = interner.intern("b");
+        assert_ne!(a, b);
+        assert_eq!(interner.intern("a"), a);
+        assert_eq!(a.as_u32(), 0);
+        assert_eq!(b.to_usize(), 1);
+
+        assert_eq!(interner.len(), 2);
+        assert_eq!(interner.resolve(a), "a");
+        assert_eq!(interner.resolve(b), "b");
+        assert_eq!(interner.try_resolve(Symbol::from_u32(2)), None);
+        assert_eq!(interner.get("b"), Some(b));
+        assert_eq!(interner.get("c"), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown symbol Symbol(5)")]
+    fn test_resolve_unknown() {
+        StrInterner::new().resolve(Symbol::from_u32(5));
+    }
+
+    #[test]
+    fn test_from_static() {
+        const SEED: &[&str] = &["GET", "POST", "GET", "PUT"];
+        let mut interner = StrInterner::from_static(SEED);
+        assert_eq!(interner.len(), 3);
+        assert_eq!(interner.get("PUT"), Some(Symbol::from_u32(2)));
+        assert_eq!(interner.intern("DELETE"), Symbol::from_u32(3));
+        assert_eq!(
+            interner.iter().collect::<Vec<_>>(),
+            [
+                (Symbol::from_u32(0), "GET"),
+                (Symbol::from_u32(1), "POST"),
+                (Symbol::from_u32(2), "PUT"),
+                (Symbol::from_u32(3), "DELETE"),
+            ]
+        );
+        assert_eq!(interner.iter().len(), 4);
+    }
+
+    #[test]
+    fn test_debug() {
+        let interner: StrInterner = ["x", "y"].into_iter().collect();
+        assert_eq!(
+            format!("{:?}", interner),
+            r#"{Symbol(0): "x", Symbol(1): "y"}"#
+        );
+    }
+
+    #[test]
+    fn test_from_set() {
+        let set: StrSet = ["x", "y"].into_iter().collect();
+        let interner = StrInterner::from(set);
+        assert_eq!(interner.get("y"), Some(Symbol::from_u32(1)));
+        assert_eq!(interner.as_str_set().len(), 2);
+        assert_eq!(interner, StrInterner::from_static(&["x", "y"]));
+        assert_ne!(interner, StrInterner::from_static(&["y", "x"]));
+    }
+}
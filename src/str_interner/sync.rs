@@ -0,0 +1,177 @@
+use core::fmt;
+use core::hash::BuildHasher;
+use std::collections::hash_map::RandomState;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+use std::sync::RwLockReadGuard;
+
+use crate::str_interner::Symbol;
+use crate::StrInterner;
+
+/// A [`StrInterner`] behind a `RwLock`, for sharing between threads.
+///
+/// Looking up known strings only takes the read lock; the write lock is taken when a new string is interned.
+/// Since the strings live inside the lock, they are resolved with a closure ([`resolve_with`](Self::resolve_with))
+/// or through a [`read`](Self::read) guard.
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// use smart_string::SyncStrInterner;
+///
+/// let interner = Arc::new(SyncStrInterner::new());
+/// let symbol = thread::spawn({
+///     let interner = Arc::clone(&interner);
+///     move || interner.intern("foo")
+/// })
+/// .join()
+/// .unwrap();
+///
+/// assert_eq!(interner.get("foo"), Some(symbol));
+/// assert_eq!(interner.resolve_with(symbol, str::len), 3);
+/// ```
+///
+/// The interner stays usable after a panic in another thread holding the lock: every operation leaves it in a
+/// consistent state, so lock poisoning is ignored.
+#[derive(Default)]
+pub struct SyncStrInterner<S = RandomState> {
+    inner: RwLock<StrInterner<S>>,
+}
+
+impl SyncStrInterner {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an interner pre-seeded with `strings`; see [`StrInterner::from_static`].
+    #[inline]
+    pub fn from_static(strings: &[&'static str]) -> Self {
+        Self::from(StrInterner::from_static(strings))
+    }
+}
+
+impl<S> SyncStrInterner<S> {
+    #[inline]
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self::from(StrInterner::with_hasher(hash_builder))
+    }
+
+    /// Locks the interner for reading.
+    #[inline]
+    pub fn read(&self) -> RwLockReadGuard<'_, StrInterner<S>> {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+
+    /// Calls `f` with the string of a symbol, panicking if the symbol doesn't belong to this interner.
+    #[inline]
+    pub fn resolve_with<R>(&self, symbol: Symbol, f: impl FnOnce(&str) -> R) -> R {
+        f(self.read().resolve(symbol))
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> StrInterner<S> {
+        self.inner
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<S: BuildHasher> SyncStrInterner<S> {
+    /// Returns the symbol of the string, interning it first if it's new.
+    pub fn intern(&self, s: &str) -> Symbol {
+        if let Some(symbol) = self.get(s) {
+            return symbol;
+        }
+        // Another thread may have interned it in the meantime; `StrInterner::intern` handles that.
+        self.inner
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .intern(s)
+    }
+
+    /// Returns the symbol of the string, if it has been interned.
+    #[inline]
+    pub fn get(&self, s: &str) -> Option<Symbol> {
+        self.read().get(s)
+    }
+}
+
+impl<S> From<StrInterner<S>> for SyncStrInterner<S> {
+    #[inline]
+    fn from(interner: StrInterner<S>) -> Self {
+        Self {
+            inner: RwLock::new(interner),
+        }
+    }
+}
+
+impl<S> fmt::Debug for SyncStrInterner<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SyncStrInterner")
+            .field(&*self.read())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn test_intern() {
+        let interner = SyncStrInterner::from_static(&["a", "b"]);
+        assert_eq!(interner.len(), 2);
+        assert_eq!(interner.intern("b"), Symbol::from_u32(1));
+        assert_eq!(interner.intern("c"), Symbol::from_u32(2));
+        assert_eq!(interner.get("c"), Some(Symbol::from_u32(2)));
+        assert_eq!(
+            interner.resolve_with(Symbol::from_u32(0), str::to_owned),
+            "a"
+        );
+        assert_eq!(interner.read().resolve(Symbol::from_u32(2)), "c");
+        assert_eq!(
+            format!("{:?}", interner),
+            r#"SyncStrInterner({Symbol(0): "a", Symbol(1): "b", Symbol(2): "c"})"#
+        );
+        assert_eq!(interner.into_inner().len(), 3);
+    }
+
+    #[test]
+    fn test_threads() {
+        let interner = Arc::new(SyncStrInterner::new());
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let interner = Arc::clone(&interner);
+                thread::spawn(move || {
+                    (0..100)
+                        .map(|i| interner.intern(&i.to_string()))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        let results: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+        assert_eq!(interner.len(), 100);
+        for symbols in &results {
+            assert_eq!(symbols, &results[0]);
+        }
+        for (i, symbol) in results[0].iter().enumerate() {
+            assert_eq!(interner.resolve_with(*symbol, str::to_owned), i.to_string());
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use core::fmt;
+use core::hash::BuildHasher;
+use core::mem;
+
+use crate::StrMap;
+
+/// A view into a single entry of a [`StrMap`], created by [`StrMap::entry`].
+pub enum Entry<'a, 'k, V, S> {
+    Occupied(OccupiedEntry<'a, V, S>),
+    Vacant(VacantEntry<'a, 'k, V, S>),
+}
+
+/// An entry of a key that is in the map.
+pub struct OccupiedEntry<'a, V, S> {
+    map: &'a mut StrMap<V, S>,
+    hash: u64,
+    id: usize,
+}
+
+/// An entry of a key that is not in the map yet.
+pub struct VacantEntry<'a, 'k, V, S> {
+    map: &'a mut StrMap<V, S>,
+    hash: u64,
+    key: &'k str,
+}
+
+impl<'a, 'k, V, S: BuildHasher> Entry<'a, 'k, V, S> {
+    #[inline]
+    pub fn key(&self) -> &str {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    #[inline]
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    #[inline]
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    #[inline]
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    #[inline]
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, V, S: BuildHasher> OccupiedEntry<'a, V, S> {
+    #[inline]
+    pub(super) fn new(map: &'a mut StrMap<V, S>, hash: u64, id: usize) -> Self {
+        Self { map, hash, id }
+    }
+
+    #[inline]
+    pub fn key(&self) -> &str {
+        self.map.key(self.id)
+    }
+
+    #[inline]
+    pub fn get(&self) -> &V {
+        self.map.value(self.id)
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut V {
+        self.map.value_mut(self.id)
+    }
+
+    #[inline]
+    pub fn into_mut(self) -> &'a mut V {
+        self.map.value_mut(self.id)
+    }
+
+    /// Replaces the value, returning the old one.
+    #[inline]
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    #[inline]
+    pub fn remove(self) -> V {
+        self.map.remove_at(self.hash, self.id)
+    }
+}
+
+impl<'a, 'k, V, S: BuildHasher> VacantEntry<'a, 'k, V, S> {
+    #[inline]
+    pub(super) fn new(map: &'a mut StrMap<V, S>, hash: u64, key: &'k str) -> Self {
+        Self { map, hash, key }
+    }
+
+    #[inline]
+    pub fn key(&self) -> &'k str {
+        self.key
+    }
+
+    /// Inserts the key with `value`, returning a mutable reference to the value.
+    #[inline]
+    pub fn insert(self, value: V) -> &'a mut V {
+        let id = self.map.push(self.hash, self.key, value);
+        self.map.value_mut(id)
+    }
+}
+
+impl<V: fmt::Debug, S: BuildHasher> fmt::Debug for Entry<'_, '_, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Entry::Occupied(entry) => f.debug_tuple("Occupied").field(entry).finish(),
+            Entry::Vacant(entry) => f.debug_tuple("Vacant").field(entry).finish(),
+        }
+    }
+}
+
+impl<V: fmt::Debug, S: BuildHasher> fmt::Debug for OccupiedEntry<'_, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OccupiedEntry")
+            .field("key", &self.key())
+            .field("value", self.get())
+            .finish()
+    }
+}
+
+impl<V, S: BuildHasher> fmt::Debug for VacantEntry<'_, '_, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("VacantEntry").field(&self.key).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry() {
+        let mut map = StrMap::new();
+        assert_eq!(*map.entry("a").or_insert(1), 1);
+        assert_eq!(*map.entry("a").or_insert(2), 1);
+        assert_eq!(*map.entry("b").or_default(), 0);
+        map.entry("b").and_modify(|v| *v += 5).or_insert(10);
+        map.entry("c").and_modify(|v| *v += 5).or_insert(10);
+        assert_eq!(
+            map.iter().collect::<Vec<_>>(),
+            [("a", &1), ("b", &5), ("c", &10)]
+        );
+    }
+
+    #[test]
+    fn test_occupied_vacant() {
+        let mut map = StrMap::new();
+        map.insert("a", 1);
+
+        match map.entry("a") {
+            Entry::Occupied(mut entry) => {
+                assert_eq!(entry.key(), "a");
+                assert_eq!(entry.insert(2), 1);
+                assert_eq!(
+                    format!("{:?}", entry),
+                    r#"OccupiedEntry { key: "a", value: 2 }"#
+                );
+                assert_eq!(entry.remove(), 2);
+            }
+            Entry::Vacant(_) => unreachable!(),
+        }
+        assert!(map.is_empty());
+
+        let entry = map.entry("b");
+        assert_eq!(entry.key(), "b");
+        assert_eq!(format!("{:?}", entry), r#"Vacant(VacantEntry("b"))"#);
+        match entry {
+            Entry::Occupied(_) => unreachable!(),
+            Entry::Vacant(entry) => *entry.insert(3) += 1,
+        }
+        assert_eq!(map.get("b"), Some(&4));
+    }
+}
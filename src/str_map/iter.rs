@@ -0,0 +1,144 @@
+use core::slice;
+
+use crate::str_stack::StrStackIter;
+
+/// An iterator over the entries of a [`StrMap`](crate::StrMap), created by
+/// [`StrMap::iter`](crate::StrMap::iter).
+pub struct Iter<'a, V> {
+    keys: StrStackIter<'a>,
+    values: slice::Iter<'a, Option<V>>,
+    /// The number of live entries left.
+    len: usize,
+}
+
+/// A mutable iterator over the entries of a [`StrMap`](crate::StrMap), created by
+/// [`StrMap::iter_mut`](crate::StrMap::iter_mut).
+pub struct IterMut<'a, V> {
+    keys: StrStackIter<'a>,
+    values: slice::IterMut<'a, Option<V>>,
+    /// The number of live entries left.
+    len: usize,
+}
+
+/// An iterator over the keys of a [`StrMap`](crate::StrMap), created by [`StrMap::keys`](crate::StrMap::keys).
+pub struct Keys<'a, V> {
+    inner: Iter<'a, V>,
+}
+
+/// An iterator over the values of a [`StrMap`](crate::StrMap), created by
+/// [`StrMap::values`](crate::StrMap::values).
+pub struct Values<'a, V> {
+    inner: Iter<'a, V>,
+}
+
+impl<'a, V> Iter<'a, V> {
+    #[inline]
+    pub(super) fn new(keys: StrStackIter<'a>, values: &'a [Option<V>], len: usize) -> Self {
+        Self {
+            keys,
+            values: values.iter(),
+            len,
+        }
+    }
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (&'a str, &'a V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let key = self.keys.next()?;
+            if let Some(value) = self.values.next()? {
+                self.len -= 1;
+                return Some((key, value));
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<V> ExactSizeIterator for Iter<'_, V> {}
+
+impl<'a, V> IterMut<'a, V> {
+    #[inline]
+    pub(super) fn new(keys: StrStackIter<'a>, values: &'a mut [Option<V>], len: usize) -> Self {
+        Self {
+            keys,
+            values: values.iter_mut(),
+            len,
+        }
+    }
+}
+
+impl<'a, V> Iterator for IterMut<'a, V> {
+    type Item = (&'a str, &'a mut V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let key = self.keys.next()?;
+            if let Some(value) = self.values.next()? {
+                self.len -= 1;
+                return Some((key, value));
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<V> ExactSizeIterator for IterMut<'_, V> {}
+
+impl<'a, V> Keys<'a, V> {
+    #[inline]
+    pub(super) fn new(inner: Iter<'a, V>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<'a, V> Iterator for Keys<'a, V> {
+    type Item = &'a str;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, _)| key)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<V> ExactSizeIterator for Keys<'_, V> {}
+
+impl<'a, V> Values<'a, V> {
+    #[inline]
+    pub(super) fn new(inner: Iter<'a, V>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<'a, V> Iterator for Values<'a, V> {
+    type Item = &'a V;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, value)| value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<V> ExactSizeIterator for Values<'_, V> {}
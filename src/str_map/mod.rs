@@ -0,0 +1,529 @@
+//! A string-keyed map whose keys are stored back to back in a single buffer.
+
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::fmt;
+use core::hash::BuildHasher;
+use core::ops;
+use std::collections::hash_map::RandomState;
+
+use crate::str_set::index::hash_str;
+use crate::str_set::index::RawIndex;
+use crate::StrStack;
+
+mod entry;
+mod iter;
+#[cfg(feature = "serde")]
+mod with_serde;
+
+pub use entry::Entry;
+pub use entry::OccupiedEntry;
+pub use entry::VacantEntry;
+pub use iter::Iter;
+pub use iter::IterMut;
+pub use iter::Keys;
+pub use iter::Values;
+
+/// A hash map with string keys, stored in a [`StrStack`], and values in a parallel `Vec`.
+///
+/// Unlike `HashMap<String, V>`, the map doesn't allocate per key. Lookups accept any `Borrow<str>` key (`&str`,
+/// `&String`, `&SmartString`, ...). Iteration follows the insertion order.
+///
+/// ```
+/// use smart_string::StrMap;
+///
+/// let mut headers = StrMap::new();
+/// headers.insert("content-type", "text/plain");
+/// headers.insert("content-length", "42");
+/// *headers.entry("x-retries").or_insert("0") = "1";
+///
+/// assert_eq!(headers.get("content-length"), Some(&"42"));
+/// assert_eq!(headers["x-retries"], "1");
+/// assert_eq!(headers.remove("content-type"), Some("text/plain"));
+/// assert_eq!(
+///     headers.keys().collect::<Vec<_>>(),
+///     ["content-length", "x-retries"]
+/// );
+/// ```
+///
+/// Removal is lazy: the value is dropped at once, but the key bytes stay in the buffer until more than half of the
+/// entries are removed, or until [`compact`](Self::compact) is called.
+#[derive(Clone)]
+pub struct StrMap<V, S = RandomState> {
+    keys: StrStack,
+    /// The value of each key in `keys`; `None` for removed entries.
+    values: Vec<Option<V>>,
+    /// Indexes the live entries by their position in `keys`.
+    index: RawIndex,
+    hash_builder: S,
+}
+
+impl<V> StrMap<V> {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty map with room for at least `entries` entries with keys of `bytes` bytes in total.
+    #[inline]
+    pub fn with_capacity(entries: usize, bytes: usize) -> Self {
+        Self::with_capacity_and_hasher(entries, bytes, RandomState::new())
+    }
+}
+
+impl<V, S> StrMap<V, S> {
+    #[inline]
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self {
+            keys: StrStack::new(),
+            values: Vec::new(),
+            index: RawIndex::default(),
+            hash_builder,
+        }
+    }
+
+    /// Creates an empty map with room for at least `entries` entries with keys of `bytes` bytes in total.
+    #[inline]
+    pub fn with_capacity_and_hasher(entries: usize, bytes: usize, hash_builder: S) -> Self {
+        Self {
+            keys: StrStack::with_capacity(entries, bytes),
+            values: Vec::with_capacity(entries),
+            index: RawIndex::with_capacity(entries),
+            hash_builder,
+        }
+    }
+
+    #[inline]
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an iterator over the entries in insertion order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, V> {
+        Iter::new(self.keys.iter(), &self.values, self.len())
+    }
+
+    /// Returns an iterator over the entries in insertion order, with mutable references to the values.
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, V> {
+        let len = self.len();
+        IterMut::new(self.keys.iter(), &mut self.values, len)
+    }
+
+    #[inline]
+    pub fn keys(&self) -> Keys<'_, V> {
+        Keys::new(self.iter())
+    }
+
+    #[inline]
+    pub fn values(&self) -> Values<'_, V> {
+        Values::new(self.iter())
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.keys.clear();
+        self.values.clear();
+        self.index.clear();
+    }
+
+    /// Returns the value of the entry at `id`, a position in `keys`.
+    #[inline]
+    fn value(&self, id: usize) -> &V {
+        self.values[id]
+            .as_ref()
+            .expect("StrMap index points to a removed entry")
+    }
+
+    #[inline]
+    fn value_mut(&mut self, id: usize) -> &mut V {
+        self.values[id]
+            .as_mut()
+            .expect("StrMap index points to a removed entry")
+    }
+}
+
+impl<V, S: BuildHasher> StrMap<V, S> {
+    /// Reserves capacity for at least `entries` more entries with keys of `bytes` more bytes in total.
+    #[inline]
+    pub fn reserve(&mut self, entries: usize, bytes: usize) {
+        self.keys.reserve(entries, bytes);
+        self.values.reserve(entries);
+        self.index.reserve(entries);
+    }
+
+    /// Inserts a value, returning the previous value of the key, if any.
+    ///
+    /// An existing entry keeps its position in the iteration order.
+    #[inline]
+    pub fn insert(&mut self, key: &str, value: V) -> Option<V> {
+        match self.entry(key) {
+            Entry::Occupied(mut entry) => Some(entry.insert(value)),
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+                None
+            }
+        }
+    }
+
+    #[inline]
+    pub fn get<K: Borrow<str> + ?Sized>(&self, key: &K) -> Option<&V> {
+        let id = self.find(key.borrow())?;
+        Some(self.value(id))
+    }
+
+    #[inline]
+    pub fn get_mut<K: Borrow<str> + ?Sized>(&mut self, key: &K) -> Option<&mut V> {
+        let id = self.find(key.borrow())?;
+        Some(self.value_mut(id))
+    }
+
+    /// Returns the stored key along with its value.
+    #[inline]
+    pub fn get_key_value<K: Borrow<str> + ?Sized>(&self, key: &K) -> Option<(&str, &V)> {
+        let id = self.find(key.borrow())?;
+        Some((self.key(id), self.value(id)))
+    }
+
+    #[inline]
+    pub fn contains_key<K: Borrow<str> + ?Sized>(&self, key: &K) -> bool {
+        self.find(key.borrow()).is_some()
+    }
+
+    /// Removes a key, returning its value.
+    #[inline]
+    pub fn remove<K: Borrow<str> + ?Sized>(&mut self, key: &K) -> Option<V> {
+        let key = key.borrow();
+        let hash = self.hash(key);
+        let id = self.find_hashed(hash, key)?;
+        Some(self.remove_at(hash, id))
+    }
+
+    /// Returns the entry of a key, for in-place manipulation.
+    ///
+    /// ```
+    /// use smart_string::StrMap;
+    ///
+    /// let mut counts = StrMap::new();
+    /// for word in "a b a c a".split(' ') {
+    ///     *counts.entry(word).or_insert(0) += 1;
+    /// }
+    /// assert_eq!(counts["a"], 3);
+    /// assert_eq!(counts["c"], 1);
+    /// ```
+    #[inline]
+    pub fn entry<'a, 'k>(&'a mut self, key: &'k str) -> Entry<'a, 'k, V, S> {
+        let hash = self.hash(key);
+        match self.find_hashed(hash, key) {
+            Some(id) => Entry::Occupied(OccupiedEntry::new(self, hash, id)),
+            None => Entry::Vacant(VacantEntry::new(self, hash, key)),
+        }
+    }
+
+    /// Drops the keys of the removed entries from the buffer.
+    pub fn compact(&mut self) {
+        if self.values.len() == self.len() {
+            return;
+        }
+        let values = &self.values;
+        let mut id = 0;
+        self.keys.retain(|_| {
+            id += 1;
+            values[id - 1].is_some()
+        });
+        self.values.retain(Option::is_some);
+        self.index.clear();
+        for (id, key) in self.keys.iter().enumerate() {
+            self.index.insert(hash_str(&self.hash_builder, key), id);
+        }
+    }
+
+    /// Compacts the map and shrinks its buffers and hash index as much as possible.
+    pub fn shrink_to_fit(&mut self) {
+        self.compact();
+        self.keys.shrink_to_fit();
+        self.values.shrink_to_fit();
+        self.index.shrink_to_fit();
+    }
+
+    #[inline]
+    fn hash(&self, key: &str) -> u64 {
+        hash_str(&self.hash_builder, key)
+    }
+
+    #[inline]
+    fn find(&self, key: &str) -> Option<usize> {
+        self.find_hashed(self.hash(key), key)
+    }
+
+    #[inline]
+    fn find_hashed(&self, hash: u64, key: &str) -> Option<usize> {
+        self.index.find(hash, |id| self.keys.get(id) == Some(key))
+    }
+
+    #[inline]
+    fn key(&self, id: usize) -> &str {
+        self.keys.get(id).expect("StrMap index out of bounds")
+    }
+
+    /// Appends a new entry; the key must not be in the map.
+    #[inline]
+    fn push(&mut self, hash: u64, key: &str, value: V) -> usize {
+        let id = self.keys.len();
+        self.keys.push(key);
+        self.values.push(Some(value));
+        self.index.insert(hash, id);
+        id
+    }
+
+    /// Removes the live entry at `id`, whose key hashes to `hash`.
+    fn remove_at(&mut self, hash: u64, id: usize) -> V {
+        self.index.remove(hash, id);
+        let value = self.values[id]
+            .take()
+            .expect("StrMap index points to a removed entry");
+        // The removed entries at the top are dropped right away, the others wait for compaction.
+        while let Some(None) = self.values.last() {
+            self.values.pop();
+            self.keys.remove_top();
+        }
+        if (self.values.len() - self.len()) * 2 > self.values.len() {
+            self.compact();
+        }
+        value
+    }
+}
+
+// -- Common traits --------------------------------------------------------------------------------
+
+impl<V, S: Default> Default for StrMap<V, S> {
+    #[inline]
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<V: fmt::Debug, S> fmt::Debug for StrMap<V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Maps are equal if they have the same entries, regardless of their order.
+impl<V: PartialEq, S: BuildHasher> PartialEq for StrMap<V, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl<V: Eq, S: BuildHasher> Eq for StrMap<V, S> {}
+
+impl<K: Borrow<str> + ?Sized, V, S: BuildHasher> ops::Index<&K> for StrMap<V, S> {
+    type Output = V;
+
+    /// Panics if the key is not in the map.
+    #[inline]
+    fn index(&self, key: &K) -> &V {
+        self.get(key).expect("key not found in StrMap")
+    }
+}
+
+impl<'a, V, S: BuildHasher> Extend<(&'a str, V)> for StrMap<V, S> {
+    fn extend<T: IntoIterator<Item = (&'a str, V)>>(&mut self, iter: T) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, V, S: BuildHasher + Default> FromIterator<(&'a str, V)> for StrMap<V, S> {
+    fn from_iter<T: IntoIterator<Item = (&'a str, V)>>(iter: T) -> Self {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<'a, V, S> IntoIterator for &'a StrMap<V, S> {
+    type Item = (&'a str, &'a V);
+    type IntoIter = Iter<'a, V>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, V, S> IntoIterator for &'a mut StrMap<V, S> {
+    type Item = (&'a str, &'a mut V);
+    type IntoIter = IterMut<'a, V>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::SmartString;
+
+    #[test]
+    fn test_create() {
+        let map = StrMap::<u32>::new();
+        assert_eq!(map.len(), 0);
+        assert!(map.is_empty());
+        assert_eq!(map.get("a"), None);
+        assert_eq!(map.iter().next(), None);
+    }
+
+    #[test]
+    fn test_insert_get() {
+        let mut map = StrMap::new();
+        assert_eq!(map.insert("a", 1), None);
+        assert_eq!(map.insert("b", 2), None);
+        assert_eq!(map.insert("a", 3), Some(1));
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get("a"), Some(&3));
+        assert_eq!(map.get(&String::from("b")), Some(&2));
+        assert_eq!(map.get(&SmartString::<15>::from("b")), Some(&2));
+        assert_eq!(map.get_key_value("b"), Some(("b", &2)));
+        assert_eq!(map.get("c"), None);
+        assert!(map.contains_key("a"));
+        assert!(!map.contains_key(""));
+
+        *map.get_mut("b").unwrap() += 10;
+        assert_eq!(map["b"], 12);
+        assert_eq!(map.iter().collect::<Vec<_>>(), [("a", &3), ("b", &12)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "key not found in StrMap")]
+    fn test_index_missing() {
+        let map = StrMap::<u32>::new();
+        let _ = map["a"];
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut map: StrMap<_> = [("a", 1), ("b", 2), ("c", 3), ("d", 4)]
+            .into_iter()
+            .collect();
+
+        assert_eq!(map.remove("b"), Some(2));
+        assert_eq!(map.remove("b"), None);
+        assert_eq!(map.len(), 3);
+        // The key of "b" waits for compaction.
+        assert_eq!(map.keys.as_str(), "abcd");
+        assert_eq!(map.get("c"), Some(&3));
+        assert_eq!(
+            map.iter().collect::<Vec<_>>(),
+            [("a", &1), ("c", &3), ("d", &4)]
+        );
+
+        // Removing the top entry drops the removed entries below it as well.
+        assert_eq!(map.remove("d"), Some(4));
+        assert_eq!(map.remove("c"), Some(3));
+        assert_eq!(map.keys.as_str(), "a");
+
+        // A re-inserted key goes to the end.
+        map.insert("b", 5);
+        map.insert("c", 6);
+        assert_eq!(map.remove("a"), Some(1));
+        assert_eq!(map.keys.as_str(), "abc");
+        assert_eq!(map.keys().collect::<Vec<_>>(), ["b", "c"]);
+    }
+
+    #[test]
+    fn test_lazy_compaction() {
+        let mut map = StrMap::new();
+        for i in 0..100 {
+            map.insert(&i.to_string(), i);
+        }
+        for i in 0..50 {
+            assert_eq!(map.remove(&i.to_string()), Some(i));
+        }
+        assert_eq!(map.keys.len(), 100);
+
+        // More than half of the entries removed.
+        assert_eq!(map.remove("50"), Some(50));
+        assert_eq!(map.keys.len(), 49);
+        assert_eq!(map.values.len(), 49);
+        for i in 51..100 {
+            assert_eq!(map.get(&i.to_string()), Some(&i));
+        }
+        assert_eq!(
+            map.values().copied().collect::<Vec<_>>(),
+            (51..100).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_compact() {
+        let mut map: StrMap<_> = [("a", 1), ("b", 2), ("c", 3)].into_iter().collect();
+        map.remove("a");
+        map.compact();
+        assert_eq!(map.keys.as_str(), "bc");
+        assert_eq!(map.get("c"), Some(&3));
+        map.insert("a", 4);
+        map.shrink_to_fit();
+        assert_eq!(
+            map.iter().collect::<Vec<_>>(),
+            [("b", &2), ("c", &3), ("a", &4)]
+        );
+
+        map.reserve(100, 0);
+        map.shrink_to_fit();
+        assert!(map.index.capacity() < 8);
+        assert_eq!(map.get("a"), Some(&4));
+    }
+
+    #[test]
+    fn test_iter_mut() {
+        let mut map: StrMap<_> = [("a", 1), ("b", 2), ("c", 3)].into_iter().collect();
+        map.remove("b");
+        for (key, value) in &mut map {
+            *value += key.len() * 10;
+        }
+        let mut it = map.iter();
+        assert_eq!(it.len(), 2);
+        assert_eq!(it.next(), Some(("a", &11)));
+        assert_eq!(it.len(), 1);
+        assert_eq!(it.next(), Some(("c", &13)));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_eq_debug_clear() {
+        let mut a: StrMap<_> = [("x", 1), ("y", 2)].into_iter().collect();
+        let b: StrMap<_> = [("y", 2), ("x", 1)].into_iter().collect();
+        let c: StrMap<_> = [("y", 2), ("x", 3)].into_iter().collect();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(format!("{:?}", a), r#"{"x": 1, "y": 2}"#);
+
+        a.clear();
+        assert!(a.is_empty());
+        assert_eq!(a.get("x"), None);
+        assert_eq!(a, StrMap::default());
+    }
+}
@@ -0,0 +1,99 @@
+use core::fmt;
+use core::hash::BuildHasher;
+use core::marker::PhantomData;
+
+use serde::de::MapAccess;
+use serde::de::Visitor;
+use serde::ser::SerializeMap;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+
+use crate::SmartString;
+use crate::StrMap;
+
+struct MapVisitor<V, S>(PhantomData<(V, S)>);
+
+impl<'de, V: Deserialize<'de>, S: BuildHasher + Default> Visitor<'de> for MapVisitor<V, S> {
+    type Value = StrMap<V, S>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map with string keys")
+    }
+
+    fn visit_map<M>(self, mut access: M) -> Result<Self::Value, M::Error>
+    where
+        M: MapAccess<'de>,
+    {
+        let mut map = StrMap::default();
+        // Not `&str`: the key can't be borrowed from the input if it has escapes, or if the input is a reader.
+        while let Some((key, value)) = access.next_entry::<SmartString, V>()? {
+            map.insert(&key, value);
+        }
+        Ok(map)
+    }
+}
+
+impl<V: Serialize, S> Serialize for StrMap<V, S> {
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        let mut map = serializer.serialize_map(Some(self.len()))?;
+        for (key, value) in self.iter() {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de, V: Deserialize<'de>, S: BuildHasher + Default> Deserialize<'de> for StrMap<V, S> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(MapVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize() {
+        let mut map = StrMap::new();
+        map.insert("b", 1);
+        map.insert("a", 2);
+        map.insert("c", 3);
+        map.remove("a");
+
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(json, r#"{"b":1,"c":3}"#);
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let json = r#"{"b":[1],"a":[2,3],"b":[4]}"#;
+        let map: StrMap<Vec<u32>> = serde_json::from_str(json).unwrap();
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get("a"), Some(&vec![2, 3]));
+        assert_eq!(
+            map.iter().collect::<Vec<_>>(),
+            [("b", &vec![4]), ("a", &vec![2, 3])]
+        );
+    }
+
+    #[test]
+    fn test_deserialize_unborrowed_keys() {
+        let json = r#"{"a\"b":1,"a very long key that doesn't fit a SmartString":2}"#;
+        let map: StrMap<u32> = serde_json::from_str(json).unwrap();
+        assert_eq!(map.get("a\"b"), Some(&1));
+
+        let map: StrMap<u32> = serde_json::from_reader(json.as_bytes()).unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(
+            map.get("a very long key that doesn't fit a SmartString"),
+            Some(&2)
+        );
+    }
+}
@@ -0,0 +1,241 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::hash::BuildHasher;
+use core::hash::Hash;
+use core::hash::Hasher;
+
+/// Hashes a key of the index.
+#[inline]
+pub(crate) fn hash_str<S: BuildHasher>(hash_builder: &S, s: &str) -> u64 {
+    let mut hasher = hash_builder.build_hasher();
+    s.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// An open-addressing (linear probing) table of entry ids keyed by their hashes.
+///
+/// The table only stores `(hash, id)` pairs; the owner resolves ids to keys, so the equality check is supplied
+/// to every lookup. The number of slots is a power of two, kept at most 7/8 full.
+#[derive(Clone, Default)]
+pub(crate) struct RawIndex {
+    slots: Vec<Slot>,
+    len: usize,
+}
+
+#[derive(Clone, Copy)]
+struct Slot {
+    hash: u64,
+    id: usize,
+}
+
+const EMPTY: Slot = Slot {
+    hash: 0,
+    id: usize::MAX,
+};
+
+impl Slot {
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.id == usize::MAX
+    }
+}
+
+impl RawIndex {
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut index = Self::default();
+        index.reserve(capacity);
+        index
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the number of ids the table can hold without growing.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.slots.len() - self.slots.len() / 8
+    }
+
+    /// Returns the id whose hash is `hash` and for which `eq` returns `true`.
+    #[inline]
+    pub fn find(&self, hash: u64, mut eq: impl FnMut(usize) -> bool) -> Option<usize> {
+        if self.len == 0 {
+            return None;
+        }
+        let mask = self.slots.len() - 1;
+        let mut pos = hash as usize & mask;
+        loop {
+            let slot = self.slots[pos];
+            if slot.is_empty() {
+                return None;
+            }
+            if slot.hash == hash && eq(slot.id) {
+                return Some(slot.id);
+            }
+            pos = (pos + 1) & mask;
+        }
+    }
+
+    /// Adds `id`, which must not be in the table yet.
+    #[inline]
+    pub fn insert(&mut self, hash: u64, id: usize) {
+        debug_assert_ne!(id, usize::MAX);
+        self.reserve(1);
+        Self::insert_slot(&mut self.slots, Slot { hash, id });
+        self.len += 1;
+    }
+
+    /// Removes `id`, which must be in the table under `hash`.
+    ///
+    /// The entries following it in the probe sequence are shifted back, so the table needs no tombstones.
+    pub fn remove(&mut self, hash: u64, id: usize) {
+        let mask = self.slots.len() - 1;
+        let mut hole = hash as usize & mask;
+        while self.slots[hole].id != id {
+            debug_assert!(
+                !self.slots[hole].is_empty(),
+                "id {} is not in the index",
+                id
+            );
+            hole = (hole + 1) & mask;
+        }
+        let mut pos = (hole + 1) & mask;
+        loop {
+            let slot = self.slots[pos];
+            if slot.is_empty() {
+                break;
+            }
+            // A slot can fill the hole if the hole is not before its ideal position in the probe sequence.
+            let ideal = slot.hash as usize & mask;
+            if pos.wrapping_sub(ideal) & mask >= pos.wrapping_sub(hole) & mask {
+                self.slots[hole] = slot;
+                hole = pos;
+            }
+            pos = (pos + 1) & mask;
+        }
+        self.slots[hole] = EMPTY;
+        self.len -= 1;
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        let required = self.len.checked_add(additional).expect("capacity overflow");
+        if required <= self.capacity() {
+            return;
+        }
+        self.rehash(Self::buckets_for(required));
+    }
+
+    /// Shrinks the table to the smallest number of slots that holds its ids.
+    pub fn shrink_to_fit(&mut self) {
+        let buckets = match self.len {
+            0 => 0,
+            len => Self::buckets_for(len),
+        };
+        if buckets < self.slots.len() {
+            self.rehash(buckets);
+        }
+    }
+
+    /// Returns the number of slots to hold `required` ids.
+    #[inline]
+    fn buckets_for(required: usize) -> usize {
+        (required.max(4).checked_mul(8).expect("capacity overflow") / 7 + 1).next_power_of_two()
+    }
+
+    /// Moves the ids to a table of `buckets` slots.
+    fn rehash(&mut self, buckets: usize) {
+        let old = core::mem::replace(&mut self.slots, vec![EMPTY; buckets]);
+        for slot in old.into_iter().filter(|slot| !slot.is_empty()) {
+            Self::insert_slot(&mut self.slots, slot);
+        }
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        if self.len != 0 {
+            self.slots.fill(EMPTY);
+            self.len = 0;
+        }
+    }
+
+    #[inline]
+    fn insert_slot(slots: &mut [Slot], slot: Slot) {
+        let mask = slots.len() - 1;
+        let mut pos = slot.hash as usize & mask;
+        while !slots[pos].is_empty() {
+            pos = (pos + 1) & mask;
+        }
+        slots[pos] = slot;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_find() {
+        let mut index = RawIndex::default();
+        assert_eq!(index.capacity(), 0);
+        assert_eq!(index.find(1, |_| true), None);
+
+        // Colliding hashes are told apart by `eq`.
+        for id in 0..100 {
+            index.insert(id as u64 % 3, id);
+        }
+        assert_eq!(index.len(), 100);
+        assert!(index.capacity() >= 100);
+        for id in 0..100 {
+            assert_eq!(index.find(id as u64 % 3, |other| other == id), Some(id));
+        }
+        assert_eq!(index.find(1, |other| other == 0), None);
+        assert_eq!(index.find(5, |_| true), None);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut index = RawIndex::default();
+        // Hashes 6, 7, 6, 7 wrap around the end of the table (8 slots).
+        let hashes = [6, 7, 6, 7, 1];
+        for (id, hash) in hashes.iter().enumerate() {
+            index.insert(*hash, id);
+        }
+        index.remove(6, 0);
+        assert_eq!(index.len(), 4);
+        assert_eq!(index.find(6, |id| id == 0), None);
+        for (id, hash) in hashes.iter().enumerate().skip(1) {
+            assert_eq!(index.find(*hash, |other| other == id), Some(id));
+        }
+
+        index.remove(7, 1);
+        index.remove(1, 4);
+        assert_eq!(index.find(6, |id| id == 2), Some(2));
+        assert_eq!(index.find(7, |id| id == 3), Some(3));
+        index.remove(6, 2);
+        index.remove(7, 3);
+        assert_eq!(index.len(), 0);
+        assert_eq!(index.find(7, |_| true), None);
+
+        index.shrink_to_fit();
+        assert_eq!(index.capacity(), 0);
+    }
+
+    #[test]
+    fn test_reserve_clear() {
+        let mut index = RawIndex::with_capacity(10);
+        let capacity = index.capacity();
+        assert!(capacity >= 10);
+        for id in 0..10 {
+            index.insert(id as u64, id);
+        }
+        assert_eq!(index.capacity(), capacity);
+
+        index.clear();
+        assert_eq!(index.len(), 0);
+        assert_eq!(index.capacity(), capacity);
+        assert_eq!(index.find(3, |_| true), None);
+    }
+}
@@ -0,0 +1,335 @@
+//! A hash set of strings stored back to back in a single buffer.
+
+use core::fmt;
+use core::hash::BuildHasher;
+use std::collections::hash_map::RandomState;
+
+use crate::str_stack::StrStackIter;
+use crate::StrStack;
+
+pub(crate) mod index;
+#[cfg(feature = "serde")]
+mod with_serde;
+
+use index::hash_str;
+use index::RawIndex;
+
+/// A set of unique strings stored in a [`StrStack`], with an open-addressing hash index.
+///
+/// Unlike `HashSet<String>`, the set doesn't allocate per element: all strings share one byte buffer, and the
+/// index holds only hashes and ids. Each string gets a stable id — its insertion index — which [`get`](Self::get)
+/// resolves back to the string. Iteration follows the insertion order.
+///
+/// ```
+/// use smart_string::StrSet;
+///
+/// let mut tags = StrSet::new();
+/// let rust = tags.insert("rust");
+/// let web = tags.insert("web");
+/// assert_eq!(tags.insert("rust"), rust);
+///
+/// assert_eq!(tags.len(), 2);
+/// assert!(tags.contains("web"));
+/// assert_eq!(tags.get_id("web"), Some(web));
+/// assert_eq!(tags.get(rust), Some("rust"));
+/// assert_eq!(tags.iter().collect::<Vec<_>>(), ["rust", "web"]);
+/// ```
+#[derive(Clone, Default)]
+pub struct StrSet<S = RandomState> {
+    strings: StrStack,
+    index: RawIndex,
+    hash_builder: S,
+}
+
+impl StrSet {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty set with room for at least `entries` strings of `bytes` bytes in total.
+    #[inline]
+    pub fn with_capacity(entries: usize, bytes: usize) -> Self {
+        Self::with_capacity_and_hasher(entries, bytes, RandomState::new())
+    }
+}
+
+impl<S> StrSet<S> {
+    /// Creates an empty set that hashes its strings with `hash_builder`.
+    #[inline]
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self {
+            strings: StrStack::new(),
+            index: RawIndex::default(),
+            hash_builder,
+        }
+    }
+
+    /// Creates an empty set with room for at least `entries` strings of `bytes` bytes in total, hashing them with
+    /// `hash_builder`.
+    #[inline]
+    pub fn with_capacity_and_hasher(entries: usize, bytes: usize, hash_builder: S) -> Self {
+        Self {
+            strings: StrStack::with_capacity(entries, bytes),
+            index: RawIndex::with_capacity(entries),
+            hash_builder,
+        }
+    }
+
+    #[inline]
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// Returns the total length of the strings in bytes.
+    #[inline]
+    pub fn bytes_len(&self) -> usize {
+        self.strings.bytes_len()
+    }
+
+    /// Returns the string with the given id.
+    #[inline]
+    pub fn get(&self, id: usize) -> Option<&str> {
+        self.strings.get(id)
+    }
+
+    /// Returns the underlying stack; the index of each entry is the id of its string.
+    #[inline]
+    pub fn as_str_stack(&self) -> &StrStack {
+        &self.strings
+    }
+
+    /// Returns an iterator over the strings in insertion (id) order.
+    #[inline]
+    pub fn iter(&self) -> StrStackIter<'_> {
+        self.strings.iter()
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.strings.clear();
+        self.index.clear();
+    }
+
+    /// Shrinks the string buffers and the hash index as much as possible.
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.strings.shrink_to_fit();
+        self.index.shrink_to_fit();
+    }
+}
+
+impl<S: BuildHasher> StrSet<S> {
+    /// Reserves capacity for at least `entries` more strings of `bytes` more bytes in total.
+    #[inline]
+    pub fn reserve(&mut self, entries: usize, bytes: usize) {
+        self.strings.reserve(entries, bytes);
+        self.index.reserve(entries);
+    }
+
+    /// Adds a string to the set if it's not present yet, and returns its id either way.
+    #[inline]
+    pub fn insert(&mut self, s: &str) -> usize {
+        let hash = self.hash(s);
+        if let Some(id) = self.find(hash, s) {
+            return id;
+        }
+        let id = self.strings.len();
+        self.strings.push(s);
+        self.index.insert(hash, id);
+        id
+    }
+
+    #[inline]
+    pub fn contains(&self, s: &str) -> bool {
+        self.get_id(s).is_some()
+    }
+
+    /// Returns the id of the string, if it's in the set.
+    #[inline]
+    pub fn get_id(&self, s: &str) -> Option<usize> {
+        self.find(self.hash(s), s)
+    }
+
+    #[inline]
+    fn hash(&self, s: &str) -> u64 {
+        hash_str(&self.hash_builder, s)
+    }
+
+    #[inline]
+    fn find(&self, hash: u64, s: &str) -> Option<usize> {
+        debug_assert_eq!(self.index.len(), self.strings.len());
+        self.index.find(hash, |id| self.strings.get(id) == Some(s))
+    }
+}
+
+// -- Common traits --------------------------------------------------------------------------------
+
+impl<S> fmt::Debug for StrSet<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// Sets are equal if they contain the same strings, regardless of the insertion order (and so of the ids).
+impl<S: BuildHasher> PartialEq for StrSet<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|s| other.contains(s))
+    }
+}
+
+impl<S: BuildHasher> Eq for StrSet<S> {}
+
+impl<'a, S> IntoIterator for &'a StrSet<S> {
+    type Item = &'a str;
+    type IntoIter = StrStackIter<'a>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, S: BuildHasher> Extend<&'a str> for StrSet<S> {
+    fn extend<T: IntoIterator<Item = &'a str>>(&mut self, iter: T) {
+        for s in iter {
+            self.insert(s);
+        }
+    }
+}
+
+impl<'a, S: BuildHasher + Default> FromIterator<&'a str> for StrSet<S> {
+    fn from_iter<T: IntoIterator<Item = &'a str>>(iter: T) -> Self {
+        let mut set = Self::default();
+        set.extend(iter);
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::hash::Hasher;
+
+    use super::*;
+
+    #[test]
+    fn test_create() {
+        let set = StrSet::new();
+        assert_eq!(set.len(), 0);
+        assert!(set.is_empty());
+        assert_eq!(set.get(0), None);
+        assert_eq!(set.get_id(""), None);
+        assert!(!set.contains("a"));
+        assert_eq!(set.iter().next(), None);
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut set = StrSet::new();
+        assert_eq!(set.insert("b"), 0);
+        assert_eq!(set.insert("a"), 1);
+        assert_eq!(set.insert(""), 2);
+        assert_eq!(set.insert("a"), 1);
+        assert_eq!(set.insert(""), 2);
+
+        assert_eq!(set.len(), 3);
+        assert_eq!(set.bytes_len(), 2);
+        assert_eq!(set.get(0), Some("b"));
+        assert_eq!(set.get(1), Some("a"));
+        assert_eq!(set.get(2), Some(""));
+        assert_eq!(set.get(3), None);
+        assert_eq!(set.get_id("a"), Some(1));
+        assert_eq!(set.get_id("c"), None);
+        assert!(set.contains(""));
+        assert_eq!(set.iter().collect::<Vec<_>>(), ["b", "a", ""]);
+        assert_eq!(set.as_str_stack().as_str(), "ba");
+    }
+
+    #[test]
+    fn test_many() {
+        let mut set = StrSet::new();
+        for i in 0..1000 {
+            assert_eq!(set.insert(&i.to_string()), i);
+        }
+        for i in 0..1000 {
+            assert_eq!(set.insert(&i.to_string()), i);
+            assert_eq!(set.get_id(&i.to_string()), Some(i));
+        }
+        assert_eq!(set.len(), 1000);
+        assert!(!set.contains("1000"));
+    }
+
+    #[derive(Clone, Default)]
+    struct ConstHasher;
+
+    impl BuildHasher for ConstHasher {
+        type Hasher = ConstHasher;
+
+        fn build_hasher(&self) -> ConstHasher {
+            ConstHasher
+        }
+    }
+
+    impl Hasher for ConstHasher {
+        fn finish(&self) -> u64 {
+            42
+        }
+
+        fn write(&mut self, _bytes: &[u8]) {}
+    }
+
+    #[test]
+    fn test_collisions() {
+        let mut set = StrSet::with_hasher(ConstHasher);
+        for s in ["a", "b", "c", "d", "e", "f", "g", "h", "i"] {
+            set.insert(s);
+        }
+        assert_eq!(set.insert("e"), 4);
+        assert_eq!(set.get_id("i"), Some(8));
+        assert_eq!(set.get_id("j"), None);
+        assert_eq!(set.len(), 9);
+    }
+
+    #[test]
+    fn test_clear_reserve() {
+        let mut set = StrSet::with_capacity(4, 16);
+        set.extend(["foo", "bar"]);
+        set.clear();
+        assert!(set.is_empty());
+        assert!(!set.contains("foo"));
+        assert_eq!(set.insert("bar"), 0);
+
+        set.reserve(100, 1000);
+        assert!(set.as_str_stack().entries_capacity() >= 101);
+        assert!(set.as_str_stack().data_capacity() >= 1003);
+        assert_eq!(set.get_id("bar"), Some(0));
+
+        set.shrink_to_fit();
+        assert_eq!(set.as_str_stack().entries_capacity(), 1);
+        assert!(set.index.capacity() < 8);
+        assert_eq!(set.get_id("bar"), Some(0));
+        assert_eq!(set.insert("foo"), 1);
+    }
+
+    #[test]
+    fn test_eq_debug() {
+        let a: StrSet = ["x", "y", "x"].into_iter().collect();
+        let b: StrSet = ["y", "x"].into_iter().collect();
+        let c: StrSet = ["y", "z"].into_iter().collect();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(a.clone(), a);
+        assert_eq!(format!("{:?}", a), r#"{"x", "y"}"#);
+        assert_eq!((&b).into_iter().collect::<Vec<_>>(), ["y", "x"]);
+    }
+}
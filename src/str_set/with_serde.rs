@@ -0,0 +1,93 @@
+use core::fmt;
+use core::hash::BuildHasher;
+use core::marker::PhantomData;
+
+use serde::de::SeqAccess;
+use serde::de::Visitor;
+use serde::ser::SerializeSeq;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+
+use crate::SmartString;
+use crate::StrSet;
+
+struct SeqVisitor<S>(PhantomData<S>);
+
+impl<'de, S: BuildHasher + Default> Visitor<'de> for SeqVisitor<S> {
+    type Value = StrSet<S>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string sequence")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut set = StrSet::default();
+        // Not `&str`: the string can't be borrowed from the input if it has escapes, or if the input is a reader.
+        while let Some(s) = seq.next_element::<SmartString>()? {
+            set.insert(&s);
+        }
+        Ok(set)
+    }
+}
+
+/// Serializes the strings in insertion order, so the ids survive a round trip.
+impl<S> Serialize for StrSet<S> {
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for s in self.iter() {
+            seq.serialize_element(s)?;
+        }
+        seq.end()
+    }
+}
+
+/// Duplicates in the input are merged; each string keeps the id of its first occurrence.
+impl<'de, S: BuildHasher + Default> Deserialize<'de> for StrSet<S> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(SeqVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize() {
+        let set: StrSet = ["123", "456", "789"].into_iter().collect();
+        let json = serde_json::to_string(&set).unwrap();
+        assert_eq!(json, r#"["123","456","789"]"#);
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let json = r#"["123","456","123","789"]"#;
+        let set: StrSet = serde_json::from_str(json).unwrap();
+
+        assert_eq!(set.len(), 3);
+        assert_eq!(set.get_id("123"), Some(0));
+        assert_eq!(set.get_id("789"), Some(2));
+        assert_eq!(set.iter().collect::<Vec<_>>(), ["123", "456", "789"]);
+    }
+
+    #[test]
+    fn test_deserialize_unborrowed() {
+        let json = r#"["a\"b","a string too long to fit a SmartString","a\"b"]"#;
+        let set: StrSet = serde_json::from_str(json).unwrap();
+        assert_eq!(set.get_id("a\"b"), Some(0));
+
+        let set: StrSet = serde_json::from_reader(json.as_bytes()).unwrap();
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            ["a\"b", "a string too long to fit a SmartString"]
+        );
+    }
+}
@@ -0,0 +1,221 @@
+use core::fmt;
+use core::ptr;
+use core::slice;
+use core::str::from_utf8_unchecked;
+
+use crate::allocator::Allocator;
+use crate::allocator::Global;
+use crate::str_stack::Offset;
+use crate::str_stack::TryPushError;
+use crate::StrStack;
+
+/// A guard that appends a new entry to a [`StrStack`] piece by piece, directly into its buffer.
+///
+/// This struct is created by [`StrStack::begin`]. It implements [`fmt::Write`], so `write!` and
+/// [`DisplayExt::write_to_fmt`](crate::DisplayExt::write_to_fmt) can target it without a temporary `String`.
+///
+/// The entry is committed by [`finish`](Self::finish); dropping the builder without finishing rolls it back.
+///
+/// ```
+/// use core::fmt::Write;
+///
+/// use smart_string::StrStack;
+///
+/// let mut stack = StrStack::new();
+///
+/// let mut entry = stack.begin();
+/// write!(entry, "{}-{}", 1, 2).unwrap();
+/// entry.push_str("-3");
+/// entry.finish();
+///
+/// let mut entry = stack.begin();
+/// entry.push_str("discarded");
+/// drop(entry);
+///
+/// assert_eq!(stack.len(), 1);
+/// assert_eq!(stack.get_top(), Some("1-2-3"));
+/// ```
+pub struct EntryBuilder<'a, I: Offset = usize, A: Allocator = Global> {
+    stack: &'a mut StrStack<I, A>,
+    /// Length of the entry being built, which is written past the end of the data and only becomes part of it on
+    /// `finish`, so a leaked builder leaves the stack untouched.
+    len: usize,
+}
+
+impl<'a, I: Offset, A: Allocator> EntryBuilder<'a, I, A> {
+    #[inline]
+    pub(super) fn new(stack: &'a mut StrStack<I, A>) -> Self {
+        Self { stack, len: 0 }
+    }
+
+    /// Returns the contents of the entry written so far.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        let data = &self.stack.data;
+        // SAFETY: the `len` bytes past the end of the data were initialized by `push_str(&str)`, so they are
+        // valid UTF-8.
+        unsafe {
+            from_utf8_unchecked(slice::from_raw_parts(
+                data.as_ptr().add(data.len()),
+                self.len,
+            ))
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline]
+    pub fn push_str(&mut self, s: &str) {
+        let data = &mut self.stack.data;
+        data.reserve(self.len + s.len());
+        // SAFETY: the reservation above makes room for `s` past the `len` bytes already written.
+        unsafe {
+            let dst = data.as_mut_ptr().add(data.len() + self.len);
+            ptr::copy_nonoverlapping(s.as_ptr(), dst, s.len());
+        }
+        self.len += s.len();
+    }
+
+    #[inline]
+    pub fn push(&mut self, ch: char) {
+        self.push_str(ch.encode_utf8(&mut [0; 4]));
+    }
+
+    /// Commits the entry to the stack, panicking (and rolling back) if the total data length would overflow the
+    /// offset type `I`.
+    #[inline]
+    pub fn finish(self) {
+        self.try_finish().expect("StrStack offset overflow");
+    }
+
+    /// Commits the entry to the stack, or rolls it back and returns an error if the total data length would
+    /// overflow the offset type `I`.
+    #[inline]
+    pub fn try_finish(self) -> Result<(), TryPushError> {
+        let len = self.stack.data.len() + self.len;
+        let end = I::from_usize(len).ok_or(TryPushError::OffsetOverflow)?;
+        // SAFETY: the bytes up to `len` were initialized by `push_str`, within the reserved capacity.
+        unsafe { self.stack.data.set_len(len) };
+        self.stack.ends.push(end);
+        Ok(())
+    }
+}
+
+impl<I: Offset, A: Allocator> fmt::Write for EntryBuilder<'_, I, A> {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+}
+
+impl<I: Offset, A: Allocator> fmt::Debug for EntryBuilder<'_, I, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("EntryBuilder").field(&self.as_str()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::fmt::Write;
+
+    use super::*;
+    use crate::DisplayExt;
+
+    #[test]
+    fn test_finish() {
+        let mut stack = StrStack::new();
+        stack.push("a");
+
+        let mut entry = stack.begin();
+        assert!(entry.is_empty());
+        entry.push_str("bc");
+        entry.push('ä');
+        assert_eq!(entry.as_str(), "bcä");
+        assert_eq!(entry.len(), 4);
+        entry.finish();
+
+        assert_eq!(stack.len(), 2);
+        assert_eq!(stack.get(1), Some("bcä"));
+        assert_eq!(stack.as_str(), "abcä");
+    }
+
+    #[test]
+    fn test_finish_empty() {
+        let mut stack = StrStack::new();
+        stack.begin().finish();
+        assert_eq!(stack.len(), 1);
+        assert_eq!(stack.get_top(), Some(""));
+    }
+
+    #[test]
+    #[allow(clippy::drop_non_drop)]
+    fn test_rollback_on_drop() {
+        let mut stack = StrStack::new();
+        stack.push("a");
+
+        let mut entry = stack.begin();
+        entry.push_str("bc");
+        drop(entry);
+
+        assert_eq!(stack.len(), 1);
+        assert_eq!(stack.as_str(), "a");
+    }
+
+    #[test]
+    #[allow(clippy::forget_non_drop)]
+    fn test_push_after_leak() {
+        let mut stack = StrStack::new();
+        stack.push("a");
+
+        let mut entry = stack.begin();
+        entry.push_str("ORPHAN");
+        core::mem::forget(entry);
+        assert_eq!(stack.bytes_len(), 1);
+
+        stack.push("b");
+        assert_eq!(stack.iter().collect::<Vec<_>>(), ["a", "b"]);
+        assert_eq!(stack.as_str(), "ab");
+        assert_eq!(stack.bytes_len(), 2);
+
+        let mut entry = stack.begin();
+        entry.push_str("ORPHAN");
+        core::mem::forget(entry);
+        stack.begin().finish();
+        assert_eq!(stack.get_top(), Some(""));
+    }
+
+    #[test]
+    fn test_write() {
+        let mut stack = StrStack::new();
+
+        let mut entry = stack.begin();
+        write!(entry, "{}+{}", 1, 2).unwrap();
+        format_args!("={}", 3).write_to_fmt(&mut entry).unwrap();
+        assert_eq!(format!("{:?}", entry), r#"EntryBuilder("1+2=3")"#);
+        entry.finish();
+
+        assert_eq!(stack.get_top(), Some("1+2=3"));
+    }
+
+    #[test]
+    fn test_try_finish_offset_overflow() {
+        let mut stack = StrStack::<u16>::default();
+        stack.push("a");
+
+        let mut entry = stack.begin();
+        entry.push_str(&"x".repeat(u16::MAX as usize));
+        assert_eq!(entry.try_finish(), Err(TryPushError::OffsetOverflow));
+
+        assert_eq!(stack.len(), 1);
+        assert_eq!(stack.as_str(), "a");
+    }
+}
@@ -0,0 +1,324 @@
+use core::mem;
+use core::ops;
+#[cfg(debug_assertions)]
+use core::sync::atomic::AtomicUsize;
+#[cfg(debug_assertions)]
+use core::sync::atomic::Ordering;
+
+use crate::allocator::Allocator;
+use crate::allocator::Global;
+use crate::str_stack::Offset;
+use crate::StrStack;
+
+/// A saved length of a [`StrStack`], created by [`StrStack::checkpoint`] and restored by [`StrStack::rollback`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    len: usize,
+    /// The stack the checkpoint was taken on.
+    #[cfg(debug_assertions)]
+    id: usize,
+    /// The generation of that stack when the checkpoint was taken.
+    #[cfg(debug_assertions)]
+    generation: usize,
+}
+
+impl Checkpoint {
+    #[inline]
+    pub(super) fn new<I: Offset, A: Allocator>(stack: &StrStack<I, A>) -> Self {
+        Self {
+            len: stack.len(),
+            #[cfg(debug_assertions)]
+            id: stack.history.id,
+            #[cfg(debug_assertions)]
+            generation: stack.history.generation,
+        }
+    }
+
+    /// Returns the number of entries at the checkpoint.
+    #[inline]
+    pub fn entries(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the checkpoint was taken on `stack`, and none of its entries were removed or modified since.
+    #[cfg(debug_assertions)]
+    pub(super) fn matches<I: Offset, A: Allocator>(&self, stack: &StrStack<I, A>) -> bool {
+        self.id == stack.history.id
+            && self.len <= stack.len()
+            && stack.history.is_unchanged(self.len, self.generation)
+    }
+}
+
+/// Debug-build bookkeeping of a [`StrStack`], to detect foreign and stale checkpoints without allocating.
+#[cfg(debug_assertions)]
+pub(super) struct History {
+    id: usize,
+    /// Incremented whenever entries are removed or modified.
+    generation: usize,
+    /// The lowest index removed or modified so far, and the last generation it was.
+    ///
+    /// A checkpoint taken before that generation is stale iff it covers the index. One taken after can't be told
+    /// apart from a valid one, so it is let through.
+    lowest: (usize, usize),
+}
+
+#[cfg(debug_assertions)]
+impl History {
+    #[inline]
+    pub(super) fn new() -> Self {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+        Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            generation: 0,
+            lowest: (usize::MAX, 0),
+        }
+    }
+
+    /// Records that the entries from `index` on were removed or modified.
+    #[inline]
+    pub(super) fn touch(&mut self, index: usize) {
+        self.generation += 1;
+        if index <= self.lowest.0 {
+            self.lowest = (index, self.generation);
+        }
+    }
+
+    /// Returns whether the first `len` entries are unchanged since `generation`, as far as can be told.
+    #[inline]
+    fn is_unchanged(&self, len: usize, generation: usize) -> bool {
+        let (index, touched) = self.lowest;
+        generation >= touched || index >= len
+    }
+}
+
+/// A clone is another stack: the checkpoints of the original don't apply to it.
+#[cfg(debug_assertions)]
+impl Clone for History {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(debug_assertions)]
+impl Default for History {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A guard that rolls a [`StrStack`] back to where it was when the guard was created, unless committed.
+///
+/// This struct is created by [`StrStack::scope`]. It dereferences to the stack, so entries can be pushed through
+/// it; dropping the guard without calling [`commit`](Self::commit) removes them again.
+///
+/// ```
+/// use smart_string::StrStack;
+///
+/// let mut stack = StrStack::new();
+/// stack.push("a");
+///
+/// let mut scope = stack.scope();
+/// scope.push("b");
+/// scope.commit();
+///
+/// let mut scope = stack.scope();
+/// scope.push("discarded");
+/// drop(scope);
+///
+/// assert_eq!(stack.iter().collect::<Vec<_>>(), ["a", "b"]);
+/// ```
+pub struct Scope<'a, I: Offset = usize, A: Allocator = Global> {
+    stack: &'a mut StrStack<I, A>,
+    checkpoint: Checkpoint,
+}
+
+impl<'a, I: Offset, A: Allocator> Scope<'a, I, A> {
+    #[inline]
+    pub(super) fn new(stack: &'a mut StrStack<I, A>) -> Self {
+        let checkpoint = stack.checkpoint();
+        Self { stack, checkpoint }
+    }
+
+    /// Returns the checkpoint the scope rolls back to.
+    #[inline]
+    pub fn checkpoint(&self) -> Checkpoint {
+        self.checkpoint
+    }
+
+    /// Keeps the entries pushed within the scope.
+    #[inline]
+    pub fn commit(self) {
+        mem::forget(self);
+    }
+}
+
+impl<I: Offset, A: Allocator> Drop for Scope<'_, I, A> {
+    #[inline]
+    fn drop(&mut self) {
+        // Not `rollback`: a stack truncated below the checkpoint within the scope shouldn't panic here.
+        self.stack.truncate(self.checkpoint.len);
+    }
+}
+
+impl<I: Offset, A: Allocator> ops::Deref for Scope<'_, I, A> {
+    type Target = StrStack<I, A>;
+
+    #[inline]
+    fn deref(&self) -> &StrStack<I, A> {
+        self.stack
+    }
+}
+
+impl<I: Offset, A: Allocator> ops::DerefMut for Scope<'_, I, A> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut StrStack<I, A> {
+        self.stack
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint_rollback() {
+        let mut stack = StrStack::new();
+        stack.push("a");
+        let first = stack.checkpoint();
+        assert_eq!(first.entries(), 1);
+
+        stack.push("bc");
+        let second = stack.checkpoint();
+        stack.push("d");
+
+        stack.rollback(second);
+        assert_eq!(stack.as_str(), "abc");
+        // Rolling back to the same checkpoint again is fine.
+        stack.rollback(second);
+        stack.rollback(first);
+        assert_eq!(stack.as_str(), "a");
+        assert_eq!(stack.len(), 1);
+
+        stack.push("e");
+        assert_eq!(stack.iter().collect::<Vec<_>>(), ["a", "e"]);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "StrStack checkpoint doesn't match the stack")]
+    fn test_rollback_stale() {
+        let mut stack = StrStack::new();
+        let first = stack.checkpoint();
+        stack.push("a");
+        let second = stack.checkpoint();
+        stack.rollback(first);
+        stack.rollback(second);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "StrStack checkpoint doesn't match the stack")]
+    fn test_rollback_foreign() {
+        let mut other = StrStack::new();
+        other.push("abc");
+        let checkpoint = other.checkpoint();
+
+        let mut stack = StrStack::new();
+        stack.push("a");
+        stack.push("b");
+        stack.rollback(checkpoint);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "StrStack checkpoint doesn't match the stack")]
+    fn test_rollback_stale_refilled() {
+        let mut stack = StrStack::new();
+        stack.push("a");
+        let first = stack.checkpoint();
+        stack.push("b");
+        let second = stack.checkpoint();
+        stack.rollback(first);
+        stack.push("b");
+        stack.push("c");
+        stack.rollback(second);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "StrStack checkpoint doesn't match the stack")]
+    fn test_rollback_modified_top() {
+        let mut stack = StrStack::new();
+        stack.push("a");
+        let checkpoint = stack.checkpoint();
+        stack.push_str_to_top("b");
+        stack.rollback(checkpoint);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "StrStack checkpoint doesn't match the stack")]
+    fn test_rollback_foreign_same_shape() {
+        let mut other = StrStack::new();
+        other.push("a");
+        let checkpoint = other.checkpoint();
+
+        let mut stack = other.clone();
+        stack.push("b");
+        stack.rollback(checkpoint);
+    }
+
+    #[test]
+    fn test_rollback_after_changes_above() {
+        let mut stack = StrStack::new();
+        stack.push("a");
+        let checkpoint = stack.checkpoint();
+        for s in ["d", "c", "b"] {
+            stack.push(s);
+        }
+        stack.sort();
+        stack.remove::<String>(2);
+        stack.retain(|s| s != "b");
+        stack.truncate(1);
+        stack.push("e");
+        stack.rollback(checkpoint);
+        assert_eq!(stack.as_str(), "a");
+    }
+
+    #[test]
+    fn test_scope() {
+        let mut stack = StrStack::new();
+        stack.push("a");
+        {
+            let mut scope = stack.scope();
+            scope.push("b");
+            assert_eq!(scope.checkpoint().entries(), 1);
+            assert_eq!(scope.len(), 2);
+
+            let mut inner = scope.scope();
+            inner.push("c");
+            inner.commit();
+            assert_eq!(scope.len(), 3);
+        }
+        assert_eq!(stack.as_str(), "a");
+
+        let mut scope = stack.scope();
+        scope.push("d");
+        scope.commit();
+        assert_eq!(stack.as_str(), "ad");
+    }
+
+    #[test]
+    fn test_scope_truncated_below() {
+        let mut stack = StrStack::new();
+        stack.push("a");
+        let mut scope = stack.scope();
+        scope.clear();
+        scope.push("b");
+        scope.push("c");
+        drop(scope);
+        assert_eq!(stack.as_str(), "b");
+    }
+}
@@ -0,0 +1,328 @@
+//! A compact serde representation of [`StrStack`]: the concatenated entries plus the end of each entry.
+//!
+//! The default representation is a sequence of strings, which is readable but costs a length prefix per entry and
+//! a push per entry when deserializing. This one is meant for binary formats such as bincode or postcard:
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Labels {
+//!     #[serde(with = "smart_string::str_stack::compact")]
+//!     names: StrStack<u32>,
+//! }
+//! ```
+//!
+//! The functions can also be called directly:
+//!
+//! ```
+//! use smart_string::str_stack::compact;
+//! use smart_string::StrStack;
+//!
+//! let mut names = StrStack::<u32>::default();
+//! names.push("foo");
+//! names.push("bar");
+//!
+//! let mut json = Vec::new();
+//! compact::serialize(&names, &mut serde_json::Serializer::new(&mut json)).unwrap();
+//! assert_eq!(json, br#"{"data":"foobar","ends":[3,6]}"#);
+//!
+//! let names: StrStack<u32> =
+//!     compact::deserialize(&mut serde_json::Deserializer::from_slice(&json)).unwrap();
+//! assert_eq!(names.get(1), Some("bar"));
+//! ```
+//!
+//! Deserialization validates the parts like [`StrStackRef::new`] does (UTF-8 data, non-decreasing ends on char
+//! boundaries, the last one matching the data length), so malformed input is rejected with an error.
+
+use core::fmt;
+use core::marker::PhantomData;
+
+use serde::de::DeserializeSeed;
+use serde::de::Error;
+use serde::de::MapAccess;
+use serde::de::SeqAccess;
+use serde::de::Visitor;
+use serde::ser::SerializeSeq;
+use serde::ser::SerializeStruct;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+
+use crate::allocator::Allocator;
+use crate::allocator::VecIn;
+use crate::str_stack::Offset;
+use crate::str_stack::StrStackRef;
+use crate::StrStack;
+
+const FIELDS: &[&str] = &["data", "ends"];
+
+/// Serializes the stack as a `{ data, ends }` struct.
+pub fn serialize<I, A, S>(stack: &StrStack<I, A>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    I: Offset,
+    A: Allocator,
+    S: Serializer,
+{
+    let stack = stack.as_stack_ref();
+    let (_, ends) = stack.as_raw_parts();
+    let mut state = serializer.serialize_struct("StrStack", FIELDS.len())?;
+    state.serialize_field("data", stack.as_str())?;
+    state.serialize_field("ends", &Ends(ends))?;
+    state.end()
+}
+
+/// Deserializes a stack written by [`serialize`], validating it.
+pub fn deserialize<'de, I, A, D>(deserializer: D) -> Result<StrStack<I, A>, D::Error>
+where
+    I: Offset,
+    A: Allocator + Clone + Default,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_struct("StrStack", FIELDS, CompactVisitor(PhantomData))
+}
+
+/// The ends, serialized as `u64`s whatever the offset type is.
+struct Ends<'a, I>(&'a [I]);
+
+impl<I: Offset> Serialize for Ends<'_, I> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+        for end in self.0 {
+            seq.serialize_element(&(end.to_usize() as u64))?;
+        }
+        seq.end()
+    }
+}
+
+enum Field {
+    Data,
+    Ends,
+}
+
+struct FieldVisitor;
+
+impl<'de> Visitor<'de> for FieldVisitor {
+    type Value = Field;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("`data` or `ends`")
+    }
+
+    fn visit_str<E: Error>(self, v: &str) -> Result<Field, E> {
+        match v {
+            "data" => Ok(Field::Data),
+            "ends" => Ok(Field::Ends),
+            _ => Err(E::unknown_field(v, FIELDS)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Field {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_identifier(FieldVisitor)
+    }
+}
+
+struct CompactVisitor<I, A>(PhantomData<(I, A)>);
+
+impl<'de, I: Offset, A: Allocator + Clone + Default> Visitor<'de> for CompactVisitor<I, A> {
+    type Value = StrStack<I, A>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a StrStack as data and ends")
+    }
+
+    fn visit_seq<S>(self, mut seq: S) -> Result<Self::Value, S::Error>
+    where
+        S: SeqAccess<'de>,
+    {
+        let alloc = A::default();
+        let data = seq
+            .next_element_seed(DataSeed(alloc.clone()))?
+            .ok_or_else(|| S::Error::invalid_length(0, &self))?;
+        let ends = seq
+            .next_element_seed(EndsSeed(alloc, PhantomData))?
+            .ok_or_else(|| S::Error::invalid_length(1, &self))?;
+        from_parts(data, ends)
+    }
+
+    fn visit_map<M>(self, mut map: M) -> Result<Self::Value, M::Error>
+    where
+        M: MapAccess<'de>,
+    {
+        let alloc = A::default();
+        let mut data = None;
+        let mut ends = None;
+        while let Some(field) = map.next_key()? {
+            match field {
+                Field::Data if data.is_some() => return Err(M::Error::duplicate_field("data")),
+                Field::Data => data = Some(map.next_value_seed(DataSeed(alloc.clone()))?),
+                Field::Ends if ends.is_some() => return Err(M::Error::duplicate_field("ends")),
+                Field::Ends => {
+                    ends = Some(map.next_value_seed(EndsSeed(alloc.clone(), PhantomData))?)
+                }
+            }
+        }
+        let data = data.ok_or_else(|| M::Error::missing_field("data"))?;
+        let ends = ends.ok_or_else(|| M::Error::missing_field("ends"))?;
+        from_parts(data, ends)
+    }
+}
+
+/// Reads the data straight into a buffer of the stack; it is validated by `from_parts`.
+struct DataSeed<A>(A);
+
+impl<'de, A: Allocator> DeserializeSeed<'de> for DataSeed<A> {
+    type Value = VecIn<u8, A>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<'de, A: Allocator> Visitor<'de> for DataSeed<A> {
+    type Value = VecIn<u8, A>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string")
+    }
+
+    fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+        self.visit_bytes(v.as_bytes())
+    }
+
+    fn visit_bytes<E: Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        let mut data = VecIn::with_capacity_in(v.len(), self.0);
+        data.extend_from_slice(v);
+        Ok(data)
+    }
+}
+
+/// Reads the ends straight into a buffer of the stack, converting them to `I`.
+struct EndsSeed<I, A>(A, PhantomData<I>);
+
+impl<'de, I: Offset, A: Allocator> DeserializeSeed<'de> for EndsSeed<I, A> {
+    type Value = VecIn<I, A>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, I: Offset, A: Allocator> Visitor<'de> for EndsSeed<I, A> {
+    type Value = VecIn<I, A>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a sequence of offsets")
+    }
+
+    fn visit_seq<S>(self, mut seq: S) -> Result<Self::Value, S::Error>
+    where
+        S: SeqAccess<'de>,
+    {
+        // Don't trust the size hint of the input with a large preallocation.
+        let mut ends = VecIn::with_capacity_in(seq.size_hint().unwrap_or(0).min(4096), self.0);
+        while let Some(end) = seq.next_element::<u64>()? {
+            let end = usize::try_from(end)
+                .ok()
+                .and_then(I::from_usize)
+                .ok_or_else(|| S::Error::custom("StrStack offset overflow"))?;
+            ends.push(end);
+        }
+        Ok(ends)
+    }
+}
+
+/// Builds a stack from the parts, once they pass the checks of `StrStackRef::new`.
+fn from_parts<I, A, E>(data: VecIn<u8, A>, ends: VecIn<I, A>) -> Result<StrStack<I, A>, E>
+where
+    I: Offset,
+    A: Allocator + Clone,
+    E: Error,
+{
+    StrStackRef::new(&data, &ends).map_err(E::custom)?;
+    Ok(StrStack::from_bufs(data, ends))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::allocator::Global;
+
+    fn to_json<I: Offset>(stack: &StrStack<I>) -> String {
+        let mut json = Vec::new();
+        serialize(stack, &mut serde_json::Serializer::new(&mut json)).unwrap();
+        String::from_utf8(json).unwrap()
+    }
+
+    fn parse<I: Offset>(json: &str) -> Result<StrStack<I>, String> {
+        deserialize(&mut serde_json::Deserializer::from_str(json)).map_err(|e| e.to_string())
+    }
+
+    fn parse_err<I: Offset>(json: &str) -> String {
+        parse::<I>(json).map(drop).unwrap_err()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut stack = StrStack::<u16>::default();
+        for s in ["foo", "", "bär"] {
+            stack.push(s);
+        }
+        let json = to_json(&stack);
+        assert_eq!(json, r#"{"data":"foobär","ends":[3,3,7]}"#);
+
+        let stack = parse::<u16>(&json).unwrap();
+        assert_eq!(stack.iter().collect::<Vec<_>>(), ["foo", "", "bär"]);
+
+        let stack = parse::<usize>(&to_json(&StrStack::new())).unwrap();
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn test_seq_form() {
+        let stack = parse::<u32>(r#"["ab",[1,2]]"#).unwrap();
+        assert_eq!(stack.iter().collect::<Vec<_>>(), ["a", "b"]);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let err = parse_err::<u32>(r#"{"data":"abc","ends":[2,1,3]}"#);
+        assert!(err.starts_with("StrStack end 1 is before the previous one"));
+
+        let err = parse_err::<u32>(r#"{"data":"ä","ends":[1,2]}"#);
+        assert!(err.starts_with("StrStack end 0 is not on a char boundary"));
+
+        let err = parse_err::<u32>(r#"{"data":"abc","ends":[1]}"#);
+        assert!(err.starts_with("StrStack data length 3 doesn't match the last end 1"));
+
+        let err = parse_err::<u32>(r#"{"data":"abc","ends":[1,5]}"#);
+        assert!(err.starts_with("StrStack end 1 is not on a char boundary"));
+
+        let err = parse_err::<u16>(r#"{"data":"a","ends":[65536]}"#);
+        assert!(err.starts_with("StrStack offset overflow"));
+
+        let err = parse_err::<u16>(r#"{"data":"a"}"#);
+        assert!(err.starts_with("missing field `ends`"));
+
+        let err = parse_err::<u16>(r#"{"data":"a","lens":[1]}"#);
+        assert!(err.starts_with("unknown field `lens`"));
+
+        let err = parse_err::<u16>(r#"["a"]"#);
+        assert!(err.starts_with("invalid length 1"));
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let mut data = VecIn::new_in(Global);
+        data.extend_from_slice(b"a\xff");
+        let mut ends = VecIn::new_in(Global);
+        ends.push(2u32);
+        let err = from_parts::<_, _, serde::de::value::Error>(data, ends)
+            .map(drop)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("invalid StrStack data: invalid utf-8"));
+    }
+}
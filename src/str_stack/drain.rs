@@ -0,0 +1,181 @@
+use core::fmt;
+use core::iter::FusedIterator;
+use core::str::from_utf8_unchecked;
+
+use crate::str_stack::Offset;
+
+/// A draining iterator for `StrStack`.
+///
+/// This struct is created by the [`drain`] method on [`StrStack`]. See its documentation for more.
+///
+/// The entries are removed from the stack as soon as it is created; they are only kept readable in the spare
+/// capacity of the stack's buffers while the iterator (or any yielded `&str`) is alive.
+///
+/// [`drain`]: crate::StrStack::drain
+/// [`StrStack`]: crate::StrStack
+pub struct Drain<'a, I: Offset = usize> {
+    /// The bytes of the drained entries.
+    bytes: &'a [u8],
+    /// The ends of the drained entries, relative to `bytes`.
+    ends: &'a [I],
+    /// Index of the next entry from the front.
+    front: usize,
+    /// Index past the next entry from the back.
+    back: usize,
+}
+
+impl<'a, I: Offset> Drain<'a, I> {
+    /// # Safety
+    ///
+    /// `bytes` must be valid UTF-8, and `ends` must be a non-decreasing sequence of char boundaries of `bytes`
+    /// ending with `bytes.len()` (if non-empty).
+    #[inline]
+    pub(super) unsafe fn new(bytes: &'a [u8], ends: &'a [I]) -> Self {
+        Self {
+            bytes,
+            ends,
+            front: 0,
+            back: ends.len(),
+        }
+    }
+
+    #[inline]
+    fn start(&self, index: usize) -> usize {
+        match index {
+            0 => 0,
+            _ => self.ends[index - 1].to_usize(),
+        }
+    }
+
+    #[inline]
+    fn slice(&self, begin: usize, end: usize) -> &'a str {
+        // SAFETY: the constructor guarantees `bytes` is valid UTF-8 and `ends` are char boundaries of it.
+        unsafe { from_utf8_unchecked(&self.bytes[begin..end]) }
+    }
+
+    /// Returns the remaining entries of this iterator, concatenated.
+    #[must_use]
+    pub fn as_str(&self) -> &'a str {
+        match self.front < self.back {
+            true => self.slice(self.start(self.front), self.start(self.back)),
+            false => "",
+        }
+    }
+}
+
+impl<I: Offset> fmt::Debug for Drain<'_, I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut list = f.debug_list();
+        for index in self.front..self.back {
+            list.entry(&self.slice(self.start(index), self.ends[index].to_usize()));
+        }
+        list.finish()
+    }
+}
+
+impl<'a, I: Offset> Iterator for Drain<'a, I> {
+    type Item = &'a str;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a str> {
+        if self.front == self.back {
+            return None;
+        }
+        let index = self.front;
+        self.front += 1;
+        Some(self.slice(self.start(index), self.ends[index].to_usize()))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+
+    #[inline]
+    fn last(mut self) -> Option<&'a str> {
+        self.next_back()
+    }
+}
+
+impl<'a, I: Offset> DoubleEndedIterator for Drain<'a, I> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a str> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        let index = self.back;
+        Some(self.slice(self.start(index), self.ends[index].to_usize()))
+    }
+}
+
+impl<I: Offset> ExactSizeIterator for Drain<'_, I> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.back - self.front
+    }
+}
+
+impl<I: Offset> FusedIterator for Drain<'_, I> {}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use crate::StrStack;
+
+    fn stack(entries: &[&str]) -> StrStack {
+        let mut stack = StrStack::new();
+        for s in entries {
+            stack.push(s);
+        }
+        stack
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut stack = stack(&["a", "€", "bc", "", "d"]);
+        let drained: Vec<_> = stack.drain(1..4).collect();
+        assert_eq!(drained, ["€", "bc", ""]);
+        assert_eq!(stack.iter().collect::<Vec<_>>(), ["a", "d"]);
+        assert_eq!(stack.as_str(), "ad");
+        assert_eq!(stack.get_bounds(1), Some((1, 2)));
+    }
+
+    #[test]
+    fn test_drain_not_exhausted() {
+        let mut stack = stack(&["a", "b", "c", "d"]);
+        {
+            let mut drain = stack.drain(..3);
+            assert_eq!(drain.len(), 3);
+            assert_eq!(drain.as_str(), "abc");
+            assert_eq!(drain.next(), Some("a"));
+            assert_eq!(drain.next_back(), Some("c"));
+            assert_eq!(drain.as_str(), "b");
+            assert_eq!(format!("{:?}", drain), r#"["b"]"#);
+        }
+        assert_eq!(stack.iter().collect::<Vec<_>>(), ["d"]);
+    }
+
+    #[test]
+    fn test_drain_all_and_empty() {
+        let mut stack = stack(&["a", "b"]);
+        assert_eq!(stack.drain(1..1).count(), 0);
+        assert_eq!(stack.len(), 2);
+
+        assert_eq!(stack.drain(..).rev().collect::<Vec<_>>(), ["b", "a"]);
+        assert!(stack.is_empty());
+        assert_eq!(stack.as_str(), "");
+
+        stack.push("x");
+        assert_eq!(stack.get_top(), Some("x"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_drain_out_of_bounds() {
+        let mut stack = stack(&["a"]);
+        stack.drain(..2);
+    }
+}
@@ -0,0 +1,49 @@
+use core::fmt;
+use core::str::Utf8Error;
+
+/// An error returned when pushing to a [`StrStack`](crate::StrStack) fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryPushError {
+    /// The total data length would exceed what the offset type can represent.
+    OffsetOverflow,
+}
+
+impl fmt::Display for TryPushError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TryPushError::OffsetOverflow => f.write_str("StrStack offset overflow"),
+        }
+    }
+}
+
+/// An error returned when raw parts don't form a valid [`StrStackRef`](crate::str_stack::StrStackRef).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RawPartsError {
+    /// The data is not valid UTF-8.
+    InvalidUtf8(Utf8Error),
+    /// The end at `index` is before the previous one.
+    UnorderedEnds { index: usize },
+    /// The end at `index` is not on a char boundary (or is out of bounds).
+    NotCharBoundary { index: usize },
+    /// The last end (0 for no entries) doesn't match the data length.
+    LengthMismatch { data_len: usize, end: usize },
+}
+
+impl fmt::Display for RawPartsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RawPartsError::InvalidUtf8(e) => write!(f, "invalid StrStack data: {}", e),
+            RawPartsError::UnorderedEnds { index } => {
+                write!(f, "StrStack end {} is before the previous one", index)
+            }
+            RawPartsError::NotCharBoundary { index } => {
+                write!(f, "StrStack end {} is not on a char boundary", index)
+            }
+            RawPartsError::LengthMismatch { data_len, end } => write!(
+                f,
+                "StrStack data length {} doesn't match the last end {}",
+                data_len, end
+            ),
+        }
+    }
+}
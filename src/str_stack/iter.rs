@@ -1,52 +1,100 @@
+use core::iter::FusedIterator;
+use core::ops;
+
+use crate::allocator::Allocator;
+use crate::str_stack::Offset;
 use crate::StrStack;
 
-#[derive(Clone, Copy, Debug)]
-struct Cursor {
-    index: usize,
-    begin: usize,
-    end: usize,
+/// An iterator over the entries of a [`StrStack`] or a [`StrStackRef`](crate::str_stack::StrStackRef).
+///
+/// It is double-ended, and skips entries in O(1) (`nth`, `nth_back`).
+#[derive(Clone)]
+pub struct StrStackIter<'a, I: Offset = usize> {
+    data: &'a str,
+    ends: &'a [I],
+    /// Index of the next entry from the front.
+    front: usize,
+    /// Index past the next entry from the back.
+    back: usize,
 }
 
-impl Cursor {
+impl<'a, I: Offset> StrStackIter<'a, I> {
     #[inline]
-    pub fn new(index: usize, begin: usize, end: usize) -> Self {
-        Self { index, begin, end }
+    pub fn new<A: Allocator>(stack: &'a StrStack<I, A>) -> Self {
+        Self::from_parts(stack.as_str(), &stack.ends)
     }
-}
 
-pub struct StrStackIter<'a> {
-    stack: &'a StrStack,
-    next: Option<Cursor>,
-}
+    /// `ends` must be valid entry boundaries within `data`: non-decreasing char boundaries, the last one (if any)
+    /// being `data.len()`.
+    #[inline]
+    pub(super) fn from_parts(data: &'a str, ends: &'a [I]) -> Self {
+        Self::from_parts_range(data, ends, 0..ends.len())
+    }
+
+    /// Iterates over the entries in `range`, which must be within `ends`; see [`from_parts`](Self::from_parts).
+    #[inline]
+    pub(super) fn from_parts_range(data: &'a str, ends: &'a [I], range: ops::Range<usize>) -> Self {
+        debug_assert!(range.start <= range.end && range.end <= ends.len());
+        Self {
+            data,
+            ends,
+            front: range.start,
+            back: range.end,
+        }
+    }
+
+    /// Returns the bounds of the entry at `index` in the whole data.
+    #[inline]
+    fn bounds(&self, index: usize) -> (usize, usize) {
+        let begin = match index {
+            0 => 0,
+            _ => self.ends[index - 1].to_usize(),
+        };
+        (begin, self.ends[index].to_usize())
+    }
 
-impl<'a> StrStackIter<'a> {
     #[inline]
-    pub fn new(stack: &'a StrStack) -> Self {
-        let next = stack
-            .ends
-            .first()
-            .copied()
-            .map(|end| Cursor::new(0, 0, end));
-        Self { stack, next }
+    fn entry(&self, index: usize) -> &'a str {
+        let (begin, end) = self.bounds(index);
+        debug_assert!(
+            begin <= end && self.data.is_char_boundary(begin) && self.data.is_char_boundary(end)
+        );
+        // SAFETY: `ends` holds non-decreasing char boundaries within `data` (see `from_parts`).
+        unsafe { self.data.get_unchecked(begin..end) }
+    }
+
+    /// Returns the entries not iterated over yet, concatenated.
+    #[inline]
+    pub fn as_str(&self) -> &'a str {
+        match self.front < self.back {
+            true => {
+                let begin = self.bounds(self.front).0;
+                let end = self.ends[self.back - 1].to_usize();
+                // SAFETY: both are entry boundaries within `data`, see `entry`.
+                unsafe { self.data.get_unchecked(begin..end) }
+            }
+            false => "",
+        }
+    }
+
+    /// Turns the iterator into one that also yields the index and the bounds of each entry.
+    #[inline]
+    pub fn with_bounds(self) -> IterWithBounds<'a, I> {
+        IterWithBounds { inner: self }
     }
 }
 
-impl<'a> Iterator for StrStackIter<'a> {
+impl<'a, I: Offset> Iterator for StrStackIter<'a, I> {
     type Item = &'a str;
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        let Cursor { index, begin, end } = self.next?;
-        let next_index = index + 1;
-        self.next = self
-            .stack
-            .ends
-            .get(next_index)
-            .copied()
-            .map(|next_end| Cursor::new(next_index, end, next_end));
-        // SAFETY: `StrStackIter` is constructed from a valid `StrStack` and advances using `ends` boundaries.
-        // `StrStack` only stores UTF-8 segments pushed via `push(&str)`, so `[begin..end]` is in-bounds and valid UTF-8.
-        Some(unsafe { self.stack.get_unchecked(begin, end) })
+        if self.front == self.back {
+            return None;
+        }
+        let index = self.front;
+        self.front += 1;
+        Some(self.entry(index))
     }
 
     #[inline]
@@ -54,20 +102,53 @@ impl<'a> Iterator for StrStackIter<'a> {
         let len = self.len();
         (len, Some(len))
     }
+
+    #[inline]
+    fn count(self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.front += n.min(self.len());
+        self.next()
+    }
+
+    #[inline]
+    fn last(mut self) -> Option<Self::Item> {
+        self.next_back()
+    }
+}
+
+impl<'a, I: Offset> DoubleEndedIterator for StrStackIter<'a, I> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(self.entry(self.back))
+    }
+
+    #[inline]
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.back -= n.min(self.len());
+        self.next_back()
+    }
 }
 
-impl<'a> ExactSizeIterator for StrStackIter<'a> {
+impl<'a, I: Offset> ExactSizeIterator for StrStackIter<'a, I> {
     #[inline]
     fn len(&self) -> usize {
-        self.next
-            .map(|c| self.stack.ends.len() - c.index)
-            .unwrap_or(0)
+        self.back - self.front
     }
 }
 
-impl<'a> IntoIterator for &'a StrStack {
-    type Item = <StrStackIter<'a> as Iterator>::Item;
-    type IntoIter = StrStackIter<'a>;
+impl<I: Offset> FusedIterator for StrStackIter<'_, I> {}
+
+impl<'a, I: Offset, A: Allocator> IntoIterator for &'a StrStack<I, A> {
+    type Item = <StrStackIter<'a, I> as Iterator>::Item;
+    type IntoIter = StrStackIter<'a, I>;
 
     #[inline]
     fn into_iter(self) -> Self::IntoIter {
@@ -75,6 +156,56 @@ impl<'a> IntoIterator for &'a StrStack {
     }
 }
 
+/// An iterator over the entries of a [`StrStack`] along with their indices and bounds, created by
+/// [`StrStack::iter_with_bounds`] or [`StrStackIter::with_bounds`].
+///
+/// The bounds are byte offsets in the concatenated entries ([`StrStack::as_str`]).
+#[derive(Clone)]
+pub struct IterWithBounds<'a, I: Offset = usize> {
+    inner: StrStackIter<'a, I>,
+}
+
+impl<'a, I: Offset> Iterator for IterWithBounds<'a, I> {
+    type Item = (usize, (usize, usize), &'a str);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.inner.front;
+        let s = self.inner.next()?;
+        Some((index, self.inner.bounds(index), s))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.inner.front += n.min(self.inner.len());
+        self.next()
+    }
+}
+
+impl<'a, I: Offset> DoubleEndedIterator for IterWithBounds<'a, I> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let s = self.inner.next_back()?;
+        let index = self.inner.back;
+        Some((index, self.inner.bounds(index), s))
+    }
+
+    #[inline]
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.inner.back -= n.min(self.inner.len());
+        self.next_back()
+    }
+}
+
+impl<I: Offset> ExactSizeIterator for IterWithBounds<'_, I> {}
+
+impl<I: Offset> FusedIterator for IterWithBounds<'_, I> {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -131,4 +262,75 @@ mod tests {
         assert_eq!(it.len(), 0);
         assert_eq!(it.size_hint(), (0, Some(0)));
     }
+
+    fn stack(entries: &[&str]) -> StrStack {
+        let mut stack = StrStack::new();
+        for s in entries {
+            stack.push(s);
+        }
+        stack
+    }
+
+    #[test]
+    fn test_double_ended() {
+        let stack = stack(&["a", "bb", "", "ccc"]);
+        assert_eq!(
+            stack.iter().rev().collect::<Vec<_>>(),
+            ["ccc", "", "bb", "a"]
+        );
+
+        let mut it = stack.iter();
+        assert_eq!(it.next(), Some("a"));
+        assert_eq!(it.next_back(), Some("ccc"));
+        assert_eq!(it.as_str(), "bb");
+        assert_eq!(it.len(), 2);
+        assert_eq!(it.next_back(), Some(""));
+        assert_eq!(it.next(), Some("bb"));
+        assert_eq!(it.as_str(), "");
+        assert_eq!(it.next(), None);
+        assert_eq!(it.next_back(), None);
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_nth() {
+        let stack = stack(&["a", "b", "c", "d", "e"]);
+        let mut it = stack.iter();
+        assert_eq!(it.nth(1), Some("b"));
+        assert_eq!(it.nth_back(1), Some("d"));
+        assert_eq!(it.len(), 1);
+        assert_eq!(it.clone().last(), Some("c"));
+        assert_eq!(it.nth(1), None);
+        assert_eq!(it.len(), 0);
+        assert_eq!(it.next_back(), None);
+
+        let mut it = stack.iter();
+        assert_eq!(it.nth_back(10), None);
+        assert_eq!(it.next(), None);
+        assert_eq!(stack.iter().nth(4), Some("e"));
+        assert_eq!(stack.iter().count(), 5);
+    }
+
+    #[test]
+    fn test_with_bounds() {
+        let stack = stack(&["ab", "", "€"]);
+        assert_eq!(
+            stack.iter_with_bounds().collect::<Vec<_>>(),
+            [(0, (0, 2), "ab"), (1, (2, 2), ""), (2, (2, 5), "€")]
+        );
+        for (index, (begin, end), s) in stack.iter_with_bounds() {
+            assert_eq!(&stack.as_str()[begin..end], s);
+            assert_eq!(stack.get(index), Some(s));
+        }
+
+        let mut it = stack.iter_with_bounds();
+        assert_eq!(it.next_back(), Some((2, (2, 5), "€")));
+        assert_eq!(it.nth(1), Some((1, (2, 2), "")));
+        assert_eq!(it.len(), 0);
+        assert_eq!(it.next(), None);
+
+        let mut it = stack.range(1..).with_bounds();
+        assert_eq!(it.nth_back(1), Some((1, (2, 2), "")));
+        assert_eq!(it.next(), None);
+    }
 }
@@ -1,15 +1,73 @@
-use std::str::from_utf8_unchecked;
+use alloc::string::String;
+use core::cmp::Ordering;
+use core::fmt;
+use core::fmt::Write;
+use core::ops;
+use core::slice;
+use core::str::from_utf8_unchecked;
+use core::str::from_utf8_unchecked_mut;
 
+use crate::allocator::Allocator;
+use crate::allocator::Global;
+use crate::allocator::TryReserveError;
+use crate::allocator::VecIn;
+
+mod builder;
+mod checkpoint;
+#[cfg(feature = "serde")]
+pub mod compact;
+mod drain;
+mod error;
 mod iter;
+mod offset;
+mod search;
+mod split;
+mod stack_ref;
+mod tagged;
+mod top;
 #[cfg(feature = "serde")]
 mod with_serde;
 
+pub use builder::EntryBuilder;
+pub use checkpoint::Checkpoint;
+#[cfg(debug_assertions)]
+use checkpoint::History;
+pub use checkpoint::Scope;
+pub use drain::Drain;
+pub use error::RawPartsError;
+pub use error::TryPushError;
+pub use iter::IterWithBounds;
 pub use iter::StrStackIter;
+pub use offset::Offset;
+pub use search::FindEntries;
+pub use split::SplitPattern;
+pub use stack_ref::StrStackRef;
+pub use tagged::TaggedIter;
+pub use tagged::TaggedIterMut;
+pub use tagged::TaggedStrStack;
+pub use top::TopWriter;
 
-#[derive(Clone, Default, PartialEq, Eq)]
-pub struct StrStack {
-    data: Vec<u8>,
-    ends: Vec<usize>,
+/// A stack of strings stored back to back in a single buffer.
+///
+/// The end of each entry is stored as an [`Offset`] `I`; a narrower one (`u16`, `u32`) saves index memory but limits
+/// the total data length. Pushing past the limit fails (`try_push`) or panics (`push`).
+///
+/// ```
+/// use smart_string::StrStack;
+///
+/// let mut stack = StrStack::<u32>::default();
+/// stack.push("foo");
+/// assert_eq!(stack.get_top(), Some("foo"));
+/// ```
+///
+/// Both the string data and the boundaries are allocated through `A`, the global allocator by default;
+/// see the [`allocator`](crate::allocator) module.
+#[derive(Clone)]
+pub struct StrStack<I: Offset = usize, A: Allocator = Global> {
+    data: VecIn<u8, A>,
+    ends: VecIn<I, A>,
+    #[cfg(debug_assertions)]
+    history: History,
 }
 
 impl StrStack {
@@ -18,6 +76,86 @@ impl StrStack {
         Self::default()
     }
 
+    /// Creates an empty stack with room for at least `entries` entries of `bytes` bytes in total.
+    #[inline]
+    pub fn with_capacity(entries: usize, bytes: usize) -> Self {
+        Self::with_capacity_in(entries, bytes, Global)
+    }
+
+    /// Creates a stack of the substrings of `input` separated by `pattern`, like [`str::split`] yields them.
+    ///
+    /// The data buffer is allocated once, for the whole input.
+    ///
+    /// ```
+    /// use smart_string::StrStack;
+    ///
+    /// let stack = StrStack::from_split("a,b,,c", ',');
+    /// assert_eq!(stack.iter().collect::<Vec<_>>(), ["a", "b", "", "c"]);
+    ///
+    /// let stack = StrStack::from_split("a, b;c", &[',', ';'][..]);
+    /// assert_eq!(stack.iter().collect::<Vec<_>>(), ["a", " b", "c"]);
+    /// ```
+    pub fn from_split<P: SplitPattern>(input: &str, pattern: P) -> Self {
+        let mut stack = Self::with_capacity(0, input.len());
+        pattern.split_into(input, &mut stack);
+        stack
+    }
+
+    /// Creates a stack of the lines of `input`, like [`str::lines`] yields them (without line terminators).
+    ///
+    /// The data buffer is allocated once, for the whole input.
+    pub fn from_lines(input: &str) -> Self {
+        let mut stack = Self::with_capacity(0, input.len());
+        stack.extend(input.lines());
+        stack
+    }
+
+    /// Creates a stack of the whitespace-separated words of `input`, like [`str::split_whitespace`] yields them.
+    ///
+    /// The data buffer is allocated once, for the whole input.
+    pub fn from_split_whitespace(input: &str) -> Self {
+        let mut stack = Self::with_capacity(0, input.len());
+        stack.extend(input.split_whitespace());
+        stack
+    }
+}
+
+impl<I: Offset, A: Allocator + Clone> StrStack<I, A> {
+    /// Creates an empty stack that allocates through `alloc`.
+    #[inline]
+    pub fn new_in(alloc: A) -> Self {
+        Self::from_bufs(VecIn::new_in(alloc.clone()), VecIn::new_in(alloc))
+    }
+
+    /// Creates an empty stack with room for at least `entries` entries of `bytes` bytes in total, allocating
+    /// through `alloc`.
+    #[inline]
+    pub fn with_capacity_in(entries: usize, bytes: usize, alloc: A) -> Self {
+        Self::from_bufs(
+            VecIn::with_capacity_in(bytes, alloc.clone()),
+            VecIn::with_capacity_in(entries, alloc),
+        )
+    }
+
+    /// Creates a stack from buffers that uphold its invariants.
+    #[inline]
+    fn from_bufs(data: VecIn<u8, A>, ends: VecIn<I, A>) -> Self {
+        Self {
+            #[cfg(debug_assertions)]
+            history: History::new(),
+            data,
+            ends,
+        }
+    }
+}
+
+impl<I: Offset, A: Allocator> StrStack<I, A> {
+    /// Returns a reference to the underlying allocator.
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        self.data.allocator()
+    }
+
     #[inline]
     pub fn len(&self) -> usize {
         self.ends.len()
@@ -28,9 +166,68 @@ impl StrStack {
         self.ends.is_empty()
     }
 
+    /// Returns the total length of the entries, in bytes.
+    #[inline]
+    pub fn bytes_len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns the number of bytes the data buffer can hold without reallocating.
+    #[inline]
+    pub fn data_capacity(&self) -> usize {
+        self.data.capacity()
+    }
+
+    /// Returns the number of entries the boundary buffer can hold without reallocating.
+    #[inline]
+    pub fn entries_capacity(&self) -> usize {
+        self.ends.capacity()
+    }
+
+    /// Reserves room for at least `entries` more entries of `bytes` more bytes in total.
+    ///
+    /// Like `Vec::reserve`, it may reserve more to avoid frequent reallocations.
+    #[inline]
+    pub fn reserve(&mut self, entries: usize, bytes: usize) {
+        self.data.reserve(bytes);
+        self.ends.reserve(entries);
+    }
+
+    /// Reserves room for exactly `entries` more entries of `bytes` more bytes in total.
+    #[inline]
+    pub fn reserve_exact(&mut self, entries: usize, bytes: usize) {
+        self.data.reserve_exact(bytes);
+        self.ends.reserve_exact(entries);
+    }
+
+    /// Fallible [`reserve`](Self::reserve).
+    #[inline]
+    pub fn try_reserve(&mut self, entries: usize, bytes: usize) -> Result<(), TryReserveError> {
+        self.data.try_reserve(bytes)?;
+        self.ends.try_reserve(entries)
+    }
+
+    /// Fallible [`reserve_exact`](Self::reserve_exact).
+    #[inline]
+    pub fn try_reserve_exact(
+        &mut self,
+        entries: usize,
+        bytes: usize,
+    ) -> Result<(), TryReserveError> {
+        self.data.try_reserve_exact(bytes)?;
+        self.ends.try_reserve_exact(entries)
+    }
+
+    /// Shrinks both buffers to fit the contents.
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.data.shrink_to_fit();
+        self.ends.shrink_to_fit();
+    }
+
     #[inline]
     pub fn as_str(&self) -> &str {
-        // SAFETY: `self.data` is only appended to via `push(&str)` and truncated via `remove_top()`,
+        // SAFETY: `self.data` is only appended to with `&str`s and truncated at entry boundaries,
         // so it is always valid UTF-8.
         unsafe { from_utf8_unchecked(&self.data) }
     }
@@ -64,9 +261,9 @@ impl StrStack {
             return None;
         }
         let (start, end) = if index > 0 {
-            (self.ends[index - 1], self.ends[index])
+            (self.ends[index - 1].to_usize(), self.ends[index].to_usize())
         } else {
-            (0, self.ends[0])
+            (0, self.ends[0].to_usize())
         };
         debug_assert!(start <= end);
         debug_assert!(end <= self.data.len());
@@ -84,11 +281,103 @@ impl StrStack {
     #[inline]
     pub fn remove_top(&mut self) -> Option<()> {
         self.ends.pop()?;
-        let end = self.ends.last().copied().unwrap_or(0);
-        self.data.truncate(end);
+        self.touch(self.len());
+        self.truncate_data();
+        Some(())
+    }
+
+    /// Returns the top entry as a mutable string slice.
+    #[inline]
+    pub fn top_mut(&mut self) -> Option<&mut str> {
+        let top = self.len().checked_sub(1)?;
+        self.touch(top);
+        let (begin, end) = (self.entry_start(top), self.top_end());
+        // SAFETY: `begin..end` are the bounds of the top entry; the slice can only be mutated in ways that keep it
+        // valid UTF-8.
+        Some(unsafe { from_utf8_unchecked_mut(&mut self.data[begin..end]) })
+    }
+
+    /// Appends `s` to the top entry in place, or returns `None` if the stack is empty.
+    ///
+    /// Panics if the total data length would overflow the offset type `I`.
+    ///
+    /// ```
+    /// use smart_string::StrStack;
+    ///
+    /// let mut stack = StrStack::new();
+    /// assert_eq!(stack.push_str_to_top("a"), None);
+    ///
+    /// stack.push("foo");
+    /// stack.push_str_to_top("bar");
+    /// stack.push_char_to_top('!');
+    /// assert_eq!(stack.get_top(), Some("foobar!"));
+    ///
+    /// stack.truncate_top(3);
+    /// stack.top_mut().unwrap().make_ascii_uppercase();
+    /// assert_eq!(stack.get_top(), Some("FOO"));
+    /// ```
+    #[inline]
+    pub fn push_str_to_top(&mut self, s: &str) -> Option<()> {
+        if self.is_empty() {
+            return None;
+        }
+        self.try_extend_top(s).expect("StrStack offset overflow");
+        Some(())
+    }
+
+    /// Appends `ch` to the top entry in place, or returns `None` if the stack is empty.
+    ///
+    /// Panics if the total data length would overflow the offset type `I`.
+    #[inline]
+    pub fn push_char_to_top(&mut self, ch: char) -> Option<()> {
+        self.push_str_to_top(ch.encode_utf8(&mut [0; 4]))
+    }
+
+    /// Shortens the top entry to `len` bytes, or returns `None` if the stack is empty.
+    ///
+    /// Has no effect if `len` is greater than the entry's length. Panics if `len` is not on a char boundary
+    /// (matches `String::truncate`).
+    #[inline]
+    pub fn truncate_top(&mut self, len: usize) -> Option<()> {
+        let top = self.get_top()?;
+        if len < top.len() {
+            assert!(
+                top.is_char_boundary(len),
+                "new top length is not on a char boundary"
+            );
+            let top = self.len() - 1;
+            let end = self.entry_start(top) + len;
+            self.touch(top);
+            self.data.truncate(end);
+            *self.ends.last_mut()? = to_offset(end);
+        }
         Some(())
     }
 
+    /// Returns a writer that appends to the top entry in place, or `None` if the stack is empty.
+    #[inline]
+    pub fn top_writer(&mut self) -> Option<TopWriter<'_, I, A>> {
+        TopWriter::new(self)
+    }
+
+    /// Appends `s` to the top entry, which must exist; on error, the stack is left unchanged.
+    #[inline]
+    fn try_extend_top(&mut self, s: &str) -> Result<(), TryPushError> {
+        debug_assert!(!self.is_empty());
+        let end = self
+            .data
+            .len()
+            .checked_add(s.len())
+            .and_then(I::from_usize)
+            .ok_or(TryPushError::OffsetOverflow)?;
+        self.touch(self.len() - 1);
+        self.data.extend_from_slice(s.as_bytes());
+        if let Some(last) = self.ends.last_mut() {
+            *last = end;
+        }
+        Ok(())
+    }
+
     #[inline]
     pub fn pop_owned<T>(&mut self) -> Option<T>
     where
@@ -99,22 +388,806 @@ impl StrStack {
         Some(s)
     }
 
+    /// Pushes a new entry, panicking if the total data length would overflow the offset type `I`.
     #[inline]
     pub fn push(&mut self, s: &str) {
+        self.try_push(s).expect("StrStack offset overflow");
+    }
+
+    /// Pushes a new entry, or returns an error if the total data length would overflow the offset type `I`.
+    ///
+    /// On error, the stack is left unchanged.
+    #[inline]
+    pub fn try_push(&mut self, s: &str) -> Result<(), TryPushError> {
+        let end = self
+            .data
+            .len()
+            .checked_add(s.len())
+            .and_then(I::from_usize)
+            .ok_or(TryPushError::OffsetOverflow)?;
         self.data.extend_from_slice(s.as_bytes());
-        self.ends.push(self.data.len());
+        self.ends.push(end);
+        Ok(())
+    }
+
+    /// Returns the end of the top entry, i.e. the start of the next one.
+    #[inline]
+    fn top_end(&self) -> usize {
+        self.ends.last().map_or(0, |end| end.to_usize())
+    }
+
+    /// Drops the data past the top entry, once entries were removed.
+    #[inline]
+    fn truncate_data(&mut self) {
+        let end = self.top_end();
+        self.data.truncate(end);
+    }
+
+    /// Records, in debug builds, that the entries from `index` on are removed or modified, so the checkpoints
+    /// covering them are stale.
+    #[inline]
+    fn touch(&mut self, index: usize) {
+        #[cfg(debug_assertions)]
+        self.history.touch(index);
+        #[cfg(not(debug_assertions))]
+        let _ = index;
+    }
+
+    /// Returns the start of the entry at `index` (`index <= len`).
+    #[inline]
+    fn entry_start(&self, index: usize) -> usize {
+        match index {
+            0 => 0,
+            _ => self.ends[index - 1].to_usize(),
+        }
+    }
+
+    /// Removes the entry at `index` and returns it converted into `T`, or `None` if `index` is out of bounds.
+    ///
+    /// The following entries are shifted down, so this is O(n) in the size of the tail.
+    #[inline]
+    pub fn remove<T>(&mut self, index: usize) -> Option<T>
+    where
+        T: for<'a> From<&'a str>,
+    {
+        if index >= self.len() {
+            return None;
+        }
+        self.drain(index..index + 1).next().map(T::from)
+    }
+
+    /// Removes the entry at `index` and returns it converted into `T`, replacing it with the top entry; or returns
+    /// `None` if `index` is out of bounds.
+    ///
+    /// Like `Vec::swap_remove`, this doesn't preserve the order of the entries. Only the top entry is copied if it
+    /// has the same length as the removed one; otherwise the entries in between are shifted too, as in
+    /// [`remove`](Self::remove).
+    ///
+    /// ```
+    /// use smart_string::StrStack;
+    ///
+    /// let mut stack = StrStack::new();
+    /// for s in ["ab", "c", "de"] {
+    ///     stack.push(s);
+    /// }
+    /// assert_eq!(stack.swap_remove::<String>(0).as_deref(), Some("ab"));
+    /// assert_eq!(stack.iter().collect::<Vec<_>>(), ["de", "c"]);
+    /// ```
+    pub fn swap_remove<T>(&mut self, index: usize) -> Option<T>
+    where
+        T: for<'a> From<&'a str>,
+    {
+        let top = self.len().checked_sub(1)?;
+        if index >= top {
+            return match index == top {
+                true => self.pop_owned(),
+                false => None,
+            };
+        }
+        let (begin, end) = self.get_bounds(index)?;
+        // SAFETY: the bounds come from `get_bounds`.
+        let removed = T::from(unsafe { self.get_unchecked(begin, end) });
+        self.touch(index);
+
+        let (top_begin, top_end) = (self.entry_start(top), self.top_end());
+        let (removed_len, top_len) = (end - begin, top_end - top_begin);
+        if removed_len == top_len {
+            self.data.copy_within(top_begin..top_end, begin);
+        } else {
+            // [removed][middle][top] -> [top][removed][middle] -> [top][middle][removed]
+            let tail = &mut self.data[begin..top_end];
+            tail.rotate_right(top_len);
+            tail[top_len..].rotate_left(removed_len);
+            self.ends[index] = to_offset(begin + top_len);
+            for end in &mut self.ends[index + 1..top] {
+                *end = to_offset(end.to_usize() - removed_len + top_len);
+            }
+        }
+        self.ends.truncate(top);
+        self.truncate_data();
+        Some(removed)
+    }
+
+    /// Inserts an entry at `index`, shifting the following entries up.
+    ///
+    /// Panics if `index > len`, or if the total data length would overflow the offset type `I`.
+    #[inline]
+    pub fn insert(&mut self, index: usize, s: &str) {
+        self.try_insert(index, s).expect("StrStack offset overflow");
+    }
+
+    /// Inserts an entry at `index`, or returns an error if the total data length would overflow the offset type `I`.
+    ///
+    /// On error, the stack is left unchanged. Panics if `index > len`.
+    pub fn try_insert(&mut self, index: usize, s: &str) -> Result<(), TryPushError> {
+        let len = self.len();
+        assert!(
+            index <= len,
+            "insertion index (is {index}) should be <= len (is {len})"
+        );
+        self.data
+            .len()
+            .checked_add(s.len())
+            .and_then(I::from_usize)
+            .ok_or(TryPushError::OffsetOverflow)?;
+
+        if index < len {
+            self.touch(index);
+        }
+        let begin = self.entry_start(index);
+        self.data.splice(begin..begin, s.as_bytes().iter().copied());
+        self.ends.insert(index, to_offset(begin));
+        for end in &mut self.ends[index..] {
+            *end = to_offset(end.to_usize() + s.len());
+        }
+        Ok(())
+    }
+
+    /// Retains only the entries for which `f` returns `true`, compacting the buffers in a single pass.
+    ///
+    /// The order of the retained entries is preserved. If `f` panics, the entries it has not been called on yet
+    /// are kept.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&str) -> bool,
+    {
+        let len = self.len();
+        let mut guard = RetainGuard {
+            stack: self,
+            read: 0,
+            read_start: 0,
+            kept: 0,
+            kept_bytes: 0,
+        };
+        while guard.read < len {
+            let begin = guard.read_start;
+            let end = guard.stack.ends[guard.read].to_usize();
+            // SAFETY: `begin..end` are the bounds of the entry at `read`, which is not moved yet.
+            let keep = f(unsafe { guard.stack.get_unchecked(begin, end) });
+            if keep {
+                let kept_bytes = guard.kept_bytes;
+                if begin != kept_bytes {
+                    guard.stack.data.copy_within(begin..end, kept_bytes);
+                }
+                guard.kept_bytes += end - begin;
+                guard.stack.ends[guard.kept] = to_offset(guard.kept_bytes);
+                guard.kept += 1;
+            } else if guard.kept == guard.read {
+                // The first removed entry; the ones before it stay in place.
+                guard.stack.touch(guard.read);
+            }
+            guard.read += 1;
+            guard.read_start = end;
+        }
+    }
+
+    /// Removes the entries in `range`, returning them as an iterator of `&str`.
+    ///
+    /// The entries are removed even if the iterator is not consumed until the end. Panics if the range is out of
+    /// bounds (matches `Vec` semantics).
+    ///
+    /// ```
+    /// use smart_string::StrStack;
+    ///
+    /// let mut stack = StrStack::new();
+    /// for s in ["a", "b", "c", "d"] {
+    ///     stack.push(s);
+    /// }
+    /// assert_eq!(stack.drain(1..3).collect::<Vec<_>>(), ["b", "c"]);
+    /// assert_eq!(stack.iter().collect::<Vec<_>>(), ["a", "d"]);
+    /// ```
+    pub fn drain<R>(&mut self, range: R) -> Drain<'_, I>
+    where
+        R: ops::RangeBounds<usize>,
+    {
+        let ops::Range { start, end } = to_range(range, self.len());
+        // Panics on invalid ranges exactly like `Vec::drain`.
+        let _ = &self.ends[start..end];
+
+        let count = end - start;
+        if count != 0 {
+            self.touch(start);
+        }
+        let begin = self.entry_start(start);
+        let removed = self.entry_start(end) - begin;
+
+        // Move the drained entries past the kept ones: they stay readable in the spare capacity while the stack is
+        // borrowed by the iterator, and the stack is consistent even if the iterator is leaked.
+        self.data[begin..].rotate_left(removed);
+        self.ends[start..].rotate_left(count);
+        let len = self.ends.len() - count;
+        for end in &mut self.ends[start..len] {
+            *end = to_offset(end.to_usize() - removed);
+        }
+        for end in &mut self.ends[len..] {
+            *end = to_offset(end.to_usize() - begin);
+        }
+        let data_len = self.data.len() - removed;
+        self.ends.truncate(len);
+        self.data.truncate(data_len);
+
+        // SAFETY: `truncate` leaves the memory intact, so these are the initialized, rotated-out parts of the
+        // buffers; the stack is mutably borrowed for as long as the slices are alive, so nothing overwrites them.
+        let (bytes, ends) = unsafe {
+            (
+                slice::from_raw_parts(self.data.as_mut_ptr().add(data_len), removed),
+                slice::from_raw_parts(self.ends.as_mut_ptr().add(len), count),
+            )
+        };
+        // SAFETY: the drained bytes are whole entries, hence valid UTF-8, and their ends were rebased onto them.
+        unsafe { Drain::new(bytes, ends) }
+    }
+
+    /// Sorts the entries, rebuilding the buffers in sorted order.
+    ///
+    /// The sort is stable. If you don't need that, [`sort_unstable`](Self::sort_unstable) may be faster.
+    ///
+    /// The scratch space comes from the stack's allocator: the entry bounds are sorted in a temporary buffer, and
+    /// the entries are rebuilt in the spare capacity of the data buffer, which is kept for later use.
+    ///
+    /// ```
+    /// use smart_string::StrStack;
+    ///
+    /// let mut stack = StrStack::new();
+    /// for s in ["pear", "apple", "fig", "apple"] {
+    ///     stack.push(s);
+    /// }
+    /// stack.sort();
+    /// stack.dedup();
+    /// assert_eq!(stack.iter().collect::<Vec<_>>(), ["apple", "fig", "pear"]);
+    /// assert_eq!(stack.binary_search("fig"), Ok(1));
+    /// assert_eq!(stack.binary_search("kiwi"), Err(2));
+    /// ```
+    #[inline]
+    pub fn sort(&mut self)
+    where
+        A: Clone,
+    {
+        self.sort_by(str::cmp);
+    }
+
+    /// Sorts the entries with a comparator function; see [`sort`](Self::sort).
+    ///
+    /// If `compare` panics, the stack is left unchanged.
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        A: Clone,
+        F: FnMut(&str, &str) -> Ordering,
+    {
+        let mut bounds = self.bounds();
+        let data = self.as_str();
+        // Entries start at distinct offsets unless they are both empty, so breaking ties by offset makes the
+        // (allocation-free) unstable sort stable.
+        bounds
+            .sort_unstable_by(|a, b| compare(&data[a.0..a.1], &data[b.0..b.1]).then(a.0.cmp(&b.0)));
+        self.reorder(&bounds);
+    }
+
+    /// Sorts the entries with a key extraction function; see [`sort`](Self::sort).
+    #[inline]
+    pub fn sort_by_key<K, F>(&mut self, mut f: F)
+    where
+        A: Clone,
+        K: Ord,
+        F: FnMut(&str) -> K,
+    {
+        self.sort_by(|a, b| f(a).cmp(&f(b)));
+    }
+
+    /// Sorts the entries, without preserving the order of equal ones.
+    #[inline]
+    pub fn sort_unstable(&mut self)
+    where
+        A: Clone,
+    {
+        self.sort_unstable_by(str::cmp);
+    }
+
+    /// Sorts the entries with a comparator function, without preserving the order of equal ones.
+    ///
+    /// If `compare` panics, the stack is left unchanged.
+    pub fn sort_unstable_by<F>(&mut self, mut compare: F)
+    where
+        A: Clone,
+        F: FnMut(&str, &str) -> Ordering,
+    {
+        let mut bounds = self.bounds();
+        let data = self.as_str();
+        bounds.sort_unstable_by(|a, b| compare(&data[a.0..a.1], &data[b.0..b.1]));
+        self.reorder(&bounds);
+    }
+
+    /// Sorts the entries with a key extraction function, without preserving the order of equal ones.
+    #[inline]
+    pub fn sort_unstable_by_key<K, F>(&mut self, mut f: F)
+    where
+        A: Clone,
+        K: Ord,
+        F: FnMut(&str) -> K,
+    {
+        self.sort_unstable_by(|a, b| f(a).cmp(&f(b)));
+    }
+
+    /// Returns the bounds of all entries.
+    fn bounds(&self) -> VecIn<(usize, usize), A>
+    where
+        A: Clone,
+    {
+        let mut bounds = VecIn::with_capacity_in(self.len(), self.allocator().clone());
+        for (_, entry, _) in self.iter_with_bounds() {
+            bounds.push(entry);
+        }
+        bounds
+    }
+
+    /// Rewrites the buffers with the entries in the order of `bounds`, a permutation of [`bounds`](Self::bounds).
+    fn reorder(&mut self, bounds: &[(usize, usize)]) {
+        debug_assert_eq!(bounds.len(), self.len());
+        // The entries before the first one that moves stay in place.
+        let moved =
+            match (0..bounds.len()).find(|&index| bounds[index].0 != self.entry_start(index)) {
+                Some(index) => index,
+                None => return,
+            };
+        self.touch(moved);
+        // Build the moved entries past the data, then copy them back over their old place.
+        let data_len = self.data.len();
+        let mut end = self.entry_start(moved);
+        let start = end;
+        for (index, &(begin, old_end)) in bounds.iter().enumerate().skip(moved) {
+            self.data.extend_from_within(begin..old_end);
+            end += old_end - begin;
+            self.ends[index] = to_offset(end);
+        }
+        self.data.copy_within(data_len.., start);
+        self.data.truncate(data_len);
+    }
+
+    /// Returns whether the entries are sorted.
+    #[inline]
+    pub fn is_sorted(&self) -> bool {
+        self.iter().zip(self.iter().skip(1)).all(|(a, b)| a <= b)
+    }
+
+    /// Removes consecutive repeated entries, as `Vec::dedup` does.
+    pub fn dedup(&mut self) {
+        let len = self.len();
+        if len < 2 {
+            return;
+        }
+        // Same compaction as `retain`; there is no user code to guard against here.
+        let mut kept = 1;
+        let mut kept_start = 0;
+        let mut kept_bytes = self.ends[0].to_usize();
+        let mut read_start = kept_bytes;
+        for read in 1..len {
+            let end = self.ends[read].to_usize();
+            if self.data[read_start..end] != self.data[kept_start..kept_bytes] {
+                self.data.copy_within(read_start..end, kept_bytes);
+                kept_start = kept_bytes;
+                kept_bytes += end - read_start;
+                self.ends[kept] = to_offset(kept_bytes);
+                kept += 1;
+            } else if kept == read {
+                self.touch(read);
+            }
+            read_start = end;
+        }
+        self.ends.truncate(kept);
+        self.data.truncate(kept_bytes);
+    }
+
+    /// Binary searches a sorted stack for `s`, as `slice::binary_search` does.
+    #[inline]
+    pub fn binary_search(&self, s: &str) -> Result<usize, usize> {
+        self.binary_search_by(|entry| entry.cmp(s))
+    }
+
+    /// Binary searches a sorted stack with a comparator function, as `slice::binary_search_by` does.
+    pub fn binary_search_by<F>(&self, mut f: F) -> Result<usize, usize>
+    where
+        F: FnMut(&str) -> Ordering,
+    {
+        let stack = self.as_stack_ref();
+        let mut low = 0;
+        let mut high = self.len();
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match f(stack.get(mid).unwrap_or_default()) {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid,
+                Ordering::Equal => return Ok(mid),
+            }
+        }
+        Err(low)
+    }
+
+    /// Returns the index of the first entry for which `pred` is false, in a stack partitioned by `pred`
+    /// (as `slice::partition_point` does).
+    #[inline]
+    pub fn partition_point<P>(&self, mut pred: P) -> usize
+    where
+        P: FnMut(&str) -> bool,
+    {
+        self.binary_search_by(|entry| match pred(entry) {
+            true => Ordering::Less,
+            false => Ordering::Greater,
+        })
+        .unwrap_or_else(|index| index)
+    }
+
+    /// Returns the index range of the entries starting with `prefix`, in a sorted stack.
+    ///
+    /// ```
+    /// use smart_string::StrStack;
+    ///
+    /// let mut dict = StrStack::new();
+    /// for s in ["car", "cart", "carton", "cat", "dog"] {
+    ///     dict.push(s);
+    /// }
+    /// assert_eq!(dict.prefix_range("cart"), 1..3);
+    /// assert_eq!(dict.range(dict.prefix_range("ca")).count(), 4);
+    /// assert_eq!(dict.prefix_range("cow"), 4..4);
+    /// ```
+    pub fn prefix_range(&self, prefix: &str) -> ops::Range<usize> {
+        let start = self.partition_point(|entry| entry < prefix);
+        // In sorted order, the entries starting with `prefix` follow the ones below it, and the entries above
+        // `prefix` that don't start with it are above all of those.
+        let end = self.partition_point(|entry| entry < prefix || entry.starts_with(prefix));
+        start..end
+    }
+
+    /// Returns the index of the entry containing the byte at `offset` of [`as_str`](Self::as_str), or `None` if
+    /// `offset` is out of bounds.
+    ///
+    /// Empty entries contain no bytes, so they are never returned. This is a binary search over the entry ends.
+    ///
+    /// ```
+    /// use smart_string::StrStack;
+    ///
+    /// let mut stack = StrStack::new();
+    /// for s in ["foo", "", "bar"] {
+    ///     stack.push(s);
+    /// }
+    /// let offset = stack.as_str().find('b').unwrap();
+    /// assert_eq!(stack.entry_at_byte(offset), Some(2));
+    /// assert_eq!(stack.entry_at_byte(2), Some(0));
+    /// assert_eq!(stack.entry_at_byte(6), None);
+    /// ```
+    #[inline]
+    pub fn entry_at_byte(&self, offset: usize) -> Option<usize> {
+        self.as_stack_ref().entry_at_byte(offset)
+    }
+
+    /// Returns the indices of the first and the last entries overlapping a byte range of [`as_str`](Self::as_str),
+    /// or `None` if the range is out of bounds.
+    ///
+    /// An empty range is located at the entry containing its start.
+    ///
+    /// ```
+    /// use smart_string::StrStack;
+    ///
+    /// let mut stack = StrStack::new();
+    /// for s in ["foo", "bar", "baz"] {
+    ///     stack.push(s);
+    /// }
+    /// assert_eq!(stack.locate(2..7), Some((0, 2)));
+    /// assert_eq!(stack.locate(3..6), Some((1, 1)));
+    /// assert_eq!(stack.locate(8..10), None);
+    /// ```
+    #[inline]
+    pub fn locate(&self, range: ops::Range<usize>) -> Option<(usize, usize)> {
+        self.as_stack_ref().locate(range)
+    }
+
+    /// Returns an iterator over the entries containing `pattern`, with their indices.
+    ///
+    /// The search runs over the concatenated entries, skipping matches that span entries.
+    ///
+    /// ```
+    /// use smart_string::StrStack;
+    ///
+    /// let mut stack = StrStack::new();
+    /// for s in ["error: disk", "ok", "warn", "error: net"] {
+    ///     stack.push(s);
+    /// }
+    /// let errors: Vec<_> = stack
+    ///     .find_entries("error")
+    ///     .map(|(index, _)| index)
+    ///     .collect();
+    /// assert_eq!(errors, [0, 3]);
+    /// ```
+    #[inline]
+    pub fn find_entries<'p>(&self, pattern: &'p str) -> FindEntries<'_, 'p, I> {
+        FindEntries::new(self.as_stack_ref(), pattern)
+    }
+
+    /// Pushes the formatted `args` as a new entry, writing them straight into the buffer.
+    ///
+    /// If a formatting trait implementation returns an error, nothing is pushed.
+    /// Panics if the total data length would overflow the offset type `I`.
+    ///
+    /// ```
+    /// use smart_string::StrStack;
+    ///
+    /// let mut stack = StrStack::new();
+    /// stack.push_fmt(format_args!("{}:{}", "key", 42)).unwrap();
+    /// assert_eq!(stack.get_top(), Some("key:42"));
+    /// ```
+    #[inline]
+    pub fn push_fmt(&mut self, args: fmt::Arguments<'_>) -> fmt::Result {
+        let mut entry = self.begin();
+        entry.write_fmt(args)?;
+        entry.finish();
+        Ok(())
     }
 
+    /// Starts a new entry to be written piece by piece; see [`EntryBuilder`].
     #[inline]
-    fn clear(&mut self) {
+    pub fn begin(&mut self) -> EntryBuilder<'_, I, A> {
+        EntryBuilder::new(self)
+    }
+
+    /// Removes all entries, keeping the allocated capacity.
+    #[inline]
+    pub fn clear(&mut self) {
+        if !self.is_empty() {
+            self.touch(0);
+        }
         self.data.clear();
         self.ends.clear();
     }
 
+    /// Keeps the first `len` entries and removes the rest, keeping the allocated capacity.
+    ///
+    /// Has no effect if `len` is greater than or equal to the current number of entries.
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.ends.len() {
+            self.touch(len);
+            self.ends.truncate(len);
+            self.truncate_data();
+        }
+    }
+
     #[inline]
-    pub fn iter(&self) -> StrStackIter<'_> {
+    pub fn iter(&self) -> StrStackIter<'_, I> {
         StrStackIter::new(self)
     }
+
+    /// Returns an iterator over the entries in `range`. Panics if the range is out of bounds (matches slice
+    /// semantics).
+    ///
+    /// ```
+    /// use smart_string::StrStack;
+    ///
+    /// let mut stack = StrStack::new();
+    /// for s in ["a", "b", "c", "d"] {
+    ///     stack.push(s);
+    /// }
+    /// assert_eq!(stack.range(1..3).collect::<Vec<_>>(), ["b", "c"]);
+    /// assert_eq!(stack.range(2..).rev().collect::<Vec<_>>(), ["d", "c"]);
+    /// assert_eq!(stack.range(1..3).as_str(), "bc");
+    /// ```
+    #[inline]
+    pub fn range<R>(&self, range: R) -> StrStackIter<'_, I>
+    where
+        R: ops::RangeBounds<usize>,
+    {
+        self.as_stack_ref().range(range)
+    }
+
+    /// Returns an iterator over the entries along with their indices and their bounds in [`as_str`](Self::as_str).
+    ///
+    /// ```
+    /// use smart_string::StrStack;
+    ///
+    /// let mut stack = StrStack::new();
+    /// stack.push("foo");
+    /// stack.push("bar");
+    ///
+    /// let (index, (begin, end), s) = stack.iter_with_bounds().last().unwrap();
+    /// assert_eq!((index, begin, end, s), (1, 3, 6, "bar"));
+    /// assert_eq!(&stack.as_str()[begin..end], "bar");
+    /// ```
+    #[inline]
+    pub fn iter_with_bounds(&self) -> IterWithBounds<'_, I> {
+        self.iter().with_bounds()
+    }
+
+    /// Returns a checkpoint of the current entries, to return to with [`rollback`](Self::rollback).
+    ///
+    /// ```
+    /// use smart_string::StrStack;
+    ///
+    /// let mut stack = StrStack::new();
+    /// stack.push("let");
+    /// let checkpoint = stack.checkpoint();
+    /// stack.push("x");
+    /// stack.push("+");
+    /// stack.rollback(checkpoint);
+    /// assert_eq!(stack.iter().collect::<Vec<_>>(), ["let"]);
+    /// ```
+    #[inline]
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint::new(self)
+    }
+
+    /// Removes the entries pushed since `checkpoint` was taken, in O(1).
+    ///
+    /// In debug builds, panics if the checkpoint doesn't match the stack: if it was taken on another stack (a clone
+    /// included), or any of the entries it covers has been removed or modified since, e.g. by rolling back to an
+    /// earlier checkpoint, even if the stack has grown back since. The check doesn't allocate, so it is best-effort:
+    /// only the lowest entry ever removed or modified is tracked.
+    #[inline]
+    pub fn rollback(&mut self, checkpoint: Checkpoint) {
+        #[cfg(debug_assertions)]
+        assert!(
+            checkpoint.matches(self),
+            "StrStack checkpoint doesn't match the stack"
+        );
+        self.truncate(checkpoint.entries());
+    }
+
+    /// Returns a guard that rolls the stack back to its current entries when dropped, unless committed.
+    #[inline]
+    pub fn scope(&mut self) -> Scope<'_, I, A> {
+        Scope::new(self)
+    }
+
+    /// Returns the raw parts of the stack: the entries back to back, and the end of each entry.
+    ///
+    /// They can be persisted and loaded back as a [`StrStackRef`] without copying.
+    #[inline]
+    pub fn as_raw_parts(&self) -> (&[u8], &[I]) {
+        (&self.data, &self.ends)
+    }
+
+    /// Returns a borrowed view of the stack.
+    #[inline]
+    pub fn as_stack_ref(&self) -> StrStackRef<'_, I> {
+        let (data, ends) = self.as_raw_parts();
+        // SAFETY: the invariants of `StrStack` are those of `StrStackRef`.
+        unsafe { StrStackRef::new_unchecked(data, ends) }
+    }
+}
+
+/// Resolves the bounds of an entry range; the result may be out of bounds or inverted.
+#[inline]
+fn to_range<R: ops::RangeBounds<usize>>(range: R, len: usize) -> ops::Range<usize> {
+    let start = match range.start_bound() {
+        ops::Bound::Included(&n) => n,
+        ops::Bound::Excluded(&n) => n.checked_add(1).expect("range start overflow"),
+        ops::Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        ops::Bound::Included(&n) => n.checked_add(1).expect("range end overflow"),
+        ops::Bound::Excluded(&n) => n,
+        ops::Bound::Unbounded => len,
+    };
+    start..end
+}
+
+/// Converts a data length known to fit into `I`.
+#[inline]
+fn to_offset<I: Offset>(n: usize) -> I {
+    I::from_usize(n).expect("StrStack offset overflow")
+}
+
+/// Finishes the compaction of `StrStack::retain`, even if the predicate panics.
+struct RetainGuard<'a, I: Offset, A: Allocator> {
+    stack: &'a mut StrStack<I, A>,
+    /// Number of entries the predicate has been called on.
+    read: usize,
+    /// Start of the entry at `read`.
+    read_start: usize,
+    /// Number of entries kept (compacted to the front).
+    kept: usize,
+    /// Length of the kept entries, in bytes.
+    kept_bytes: usize,
+}
+
+impl<I: Offset, A: Allocator> Drop for RetainGuard<'_, I, A> {
+    fn drop(&mut self) {
+        // Shift the unprocessed tail (empty, unless the predicate panicked) down to the kept entries.
+        let stack = &mut *self.stack;
+        let len = stack.ends.len();
+        let data_len = stack.data.len();
+        let shift = self.read_start - self.kept_bytes;
+        if shift != 0 {
+            stack
+                .data
+                .copy_within(self.read_start..data_len, self.kept_bytes);
+        }
+        for index in self.read..len {
+            stack.ends[self.kept + index - self.read] =
+                to_offset(stack.ends[index].to_usize() - shift);
+        }
+        stack.ends.truncate(self.kept + len - self.read);
+        stack.data.truncate(data_len - shift);
+    }
+}
+
+impl<I: Offset, A: Allocator> PartialEq for StrStack<I, A> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        *self.data == *other.data && *self.ends == *other.ends
+    }
+}
+
+impl<I: Offset, A: Allocator> Eq for StrStack<I, A> {}
+
+impl<I: Offset, A: Allocator + Clone + Default> Default for StrStack<I, A> {
+    #[inline]
+    fn default() -> Self {
+        Self::new_in(A::default())
+    }
+}
+
+impl<'a, I: Offset, A: Allocator> Extend<&'a str> for StrStack<I, A> {
+    fn extend<It: IntoIterator<Item = &'a str>>(&mut self, iter: It) {
+        let iter = iter.into_iter();
+        self.ends.reserve(iter.size_hint().0);
+        for s in iter {
+            self.push(s);
+        }
+    }
+}
+
+impl<I: Offset, A: Allocator> Extend<String> for StrStack<I, A> {
+    fn extend<It: IntoIterator<Item = String>>(&mut self, iter: It) {
+        let iter = iter.into_iter();
+        self.ends.reserve(iter.size_hint().0);
+        for s in iter {
+            self.push(&s);
+        }
+    }
+}
+
+impl<'a, I: Offset, A: Allocator + Clone + Default> FromIterator<&'a str> for StrStack<I, A> {
+    fn from_iter<It: IntoIterator<Item = &'a str>>(iter: It) -> Self {
+        let mut stack = Self::new_in(A::default());
+        stack.extend(iter);
+        stack
+    }
+}
+
+impl<I: Offset, A: Allocator + Clone + Default> FromIterator<String> for StrStack<I, A> {
+    fn from_iter<It: IntoIterator<Item = String>>(iter: It) -> Self {
+        let mut stack = Self::new_in(A::default());
+        stack.extend(iter);
+        stack
+    }
+}
+
+impl<I: Offset, A: Allocator + Clone + Default> From<&[&str]> for StrStack<I, A> {
+    /// Creates a stack of the given entries, allocating both buffers once.
+    fn from(entries: &[&str]) -> Self {
+        let bytes = entries.iter().map(|s| s.len()).sum();
+        let mut stack = Self::with_capacity_in(entries.len(), bytes, A::default());
+        stack.extend(entries.iter().copied());
+        stack
+    }
 }
 
 #[cfg(test)]
@@ -282,4 +1355,511 @@ mod tests {
         assert_eq!(stack.len(), 1);
         assert_eq!(stack.get_top(), Some("€"));
     }
+
+    #[test]
+    fn test_push_fmt() {
+        let mut stack = StrStack::new();
+        stack.push("a");
+        stack.push_fmt(format_args!("{}-{}", "€", 2)).unwrap();
+        assert_eq!(stack.len(), 2);
+        assert_eq!(stack.get_top(), Some("€-2"));
+        assert_eq!(stack.as_str(), "a€-2");
+    }
+
+    #[test]
+    fn test_push_fmt_error_rolls_back() {
+        struct Failing;
+
+        impl fmt::Display for Failing {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("partial")?;
+                Err(fmt::Error)
+            }
+        }
+
+        let mut stack = StrStack::new();
+        stack.push("a");
+        assert_eq!(
+            stack.push_fmt(format_args!("x{}", Failing)),
+            Err(fmt::Error)
+        );
+        assert_eq!(stack.len(), 1);
+        assert_eq!(stack.as_str(), "a");
+    }
+
+    #[test]
+    fn test_narrow_offsets() {
+        let mut stack = StrStack::<u16>::default();
+        stack.push("€");
+        stack.push("ab");
+        assert_eq!(stack.get_bounds(1), Some((3, 5)));
+        assert_eq!(stack.iter().collect::<Vec<_>>(), ["€", "ab"]);
+        stack.remove_top().unwrap();
+        assert_eq!(stack.as_str(), "€");
+
+        let mut stack = StrStack::<u32>::default();
+        stack.push("abc");
+        assert_eq!(stack.get_top(), Some("abc"));
+    }
+
+    #[test]
+    fn test_try_push_offset_overflow() {
+        let big = "x".repeat(u16::MAX as usize - 1);
+
+        let mut stack = StrStack::<u16>::default();
+        stack.push(&big);
+        assert_eq!(stack.try_push("a"), Ok(()));
+        assert_eq!(stack.try_push("b"), Err(TryPushError::OffsetOverflow));
+        assert_eq!(stack.len(), 2);
+        assert_eq!(stack.as_str().len(), u16::MAX as usize);
+
+        // Empty entries still fit.
+        assert_eq!(stack.try_push(""), Ok(()));
+        assert_eq!(stack.len(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "StrStack offset overflow")]
+    fn test_push_offset_overflow() {
+        let mut stack = StrStack::<u16>::default();
+        stack.push(&"x".repeat(u16::MAX as usize + 1));
+    }
+
+    #[test]
+    fn test_capacity() {
+        let mut stack = StrStack::with_capacity(4, 16);
+        assert!(stack.entries_capacity() >= 4);
+        assert!(stack.data_capacity() >= 16);
+
+        stack.push("abc");
+        stack.push("€");
+        assert_eq!(stack.bytes_len(), 6);
+
+        stack.reserve_exact(10, 100);
+        assert!(stack.entries_capacity() >= 12);
+        assert!(stack.data_capacity() >= 106);
+        stack.reserve(1, 1);
+        stack.try_reserve(1, 1).unwrap();
+        stack.try_reserve_exact(1, 1).unwrap();
+        assert!(stack.try_reserve(0, usize::MAX).is_err());
+
+        stack.shrink_to_fit();
+        assert_eq!(stack.entries_capacity(), 2);
+        assert_eq!(stack.data_capacity(), 6);
+        assert_eq!(stack.iter().collect::<Vec<_>>(), ["abc", "€"]);
+    }
+
+    #[test]
+    fn test_clear_and_truncate() {
+        let mut stack = StrStack::new();
+        stack.push("a");
+        stack.push("bc");
+        stack.push("def");
+
+        stack.truncate(5);
+        assert_eq!(stack.len(), 3);
+
+        stack.truncate(2);
+        assert_eq!(stack.len(), 2);
+        assert_eq!(stack.as_str(), "abc");
+        assert_eq!(stack.get_top(), Some("bc"));
+
+        stack.truncate(0);
+        assert!(stack.is_empty());
+        assert_eq!(stack.bytes_len(), 0);
+
+        stack.push("x");
+        let capacity = stack.data_capacity();
+        stack.clear();
+        assert!(stack.is_empty());
+        assert_eq!(stack.as_str(), "");
+        assert_eq!(stack.data_capacity(), capacity);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut stack = StrStack::new();
+        stack.push("a");
+        stack.push("€b");
+        stack.push("c");
+
+        assert_eq!(stack.remove::<String>(3), None);
+        assert_eq!(stack.remove::<String>(1).as_deref(), Some("€b"));
+        assert_eq!(stack.iter().collect::<Vec<_>>(), ["a", "c"]);
+        assert_eq!(stack.get_bounds(1), Some((1, 2)));
+        assert_eq!(stack.remove::<SmartString>(0).as_deref(), Some("a"));
+        assert_eq!(stack.remove::<Rc<str>>(0).as_deref(), Some("c"));
+        assert!(stack.is_empty());
+
+        assert_eq!(stack.swap_remove::<String>(0), None);
+        assert_eq!(stack.bytes_len(), 0);
+    }
+
+    #[test]
+    fn test_swap_remove() {
+        let mut stack = stack_of(&["a", "€", "bc", "", "de"]);
+        assert_eq!(stack.swap_remove::<String>(5), None);
+
+        // Same length as the top entry.
+        assert_eq!(stack.swap_remove::<String>(2).as_deref(), Some("bc"));
+        assert_eq!(stack.iter().collect::<Vec<_>>(), ["a", "€", "de", ""]);
+
+        // Longer, then shorter than the top entry.
+        assert_eq!(stack.swap_remove::<String>(1).as_deref(), Some("€"));
+        assert_eq!(stack.iter().collect::<Vec<_>>(), ["a", "", "de"]);
+        assert_eq!(stack.swap_remove::<String>(0).as_deref(), Some("a"));
+        assert_eq!(stack.iter().collect::<Vec<_>>(), ["de", ""]);
+        assert_eq!(stack.as_str(), "de");
+        assert_eq!(stack.get_bounds(1), Some((2, 2)));
+
+        // The top entry itself.
+        assert_eq!(stack.swap_remove::<String>(1).as_deref(), Some(""));
+        assert_eq!(stack.swap_remove::<String>(0).as_deref(), Some("de"));
+        assert!(stack.is_empty());
+        assert_eq!(stack.bytes_len(), 0);
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut stack = StrStack::new();
+        stack.insert(0, "b");
+        stack.insert(0, "€");
+        stack.insert(2, "d");
+        stack.insert(2, "");
+        stack.insert(1, "a");
+        assert_eq!(stack.iter().collect::<Vec<_>>(), ["€", "a", "b", "", "d"]);
+        assert_eq!(stack.as_str(), "€abd");
+        assert_eq!(stack.get_bounds(3), Some((5, 5)));
+    }
+
+    #[test]
+    #[should_panic(expected = "insertion index (is 2) should be <= len (is 1)")]
+    fn test_insert_out_of_bounds() {
+        let mut stack = StrStack::new();
+        stack.push("a");
+        stack.insert(2, "b");
+    }
+
+    #[test]
+    fn test_try_insert_offset_overflow() {
+        let mut stack = StrStack::<u16>::default();
+        stack.push(&"x".repeat(u16::MAX as usize - 1));
+        stack.push("y");
+        assert_eq!(stack.try_insert(0, "z"), Err(TryPushError::OffsetOverflow));
+        assert_eq!(stack.len(), 2);
+        assert_eq!(stack.try_insert(1, ""), Ok(()));
+        assert_eq!(stack.get(1), Some(""));
+        assert_eq!(stack.get(2), Some("y"));
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut stack = StrStack::new();
+        for s in ["a", "€€", "", "bb", "c", "dd"] {
+            stack.push(s);
+        }
+        stack.retain(|s| s.chars().count() != 1);
+        assert_eq!(stack.iter().collect::<Vec<_>>(), ["€€", "", "bb", "dd"]);
+        assert_eq!(stack.as_str(), "€€bbdd");
+
+        stack.retain(|_| true);
+        assert_eq!(stack.len(), 4);
+
+        stack.retain(|_| false);
+        assert!(stack.is_empty());
+        assert_eq!(stack.bytes_len(), 0);
+    }
+
+    #[test]
+    fn test_retain_panic_keeps_unprocessed() {
+        let mut stack = StrStack::new();
+        for s in ["a", "b", "c", "d"] {
+            stack.push(s);
+        }
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            stack.retain(|s| match s {
+                "b" => false,
+                "c" => panic!("boom"),
+                _ => true,
+            });
+        }));
+        assert!(result.is_err());
+        assert_eq!(stack.iter().collect::<Vec<_>>(), ["a", "c", "d"]);
+        assert_eq!(stack.as_str(), "acd");
+    }
+
+    #[test]
+    fn test_range() {
+        let mut stack = StrStack::new();
+        for s in ["a", "b", "c", "d"] {
+            stack.push(s);
+        }
+        assert_eq!(stack.range(..).collect::<Vec<_>>(), ["a", "b", "c", "d"]);
+        assert_eq!(stack.range(1..=2).rev().collect::<Vec<_>>(), ["c", "b"]);
+        assert_eq!(stack.range(4..).next(), None);
+        assert_eq!(stack.range(2..2).len(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_range_out_of_bounds() {
+        let mut stack = StrStack::new();
+        stack.push("a");
+        let _ = stack.range(..2);
+    }
+
+    #[test]
+    fn test_top_mut() {
+        let mut stack = StrStack::new();
+        assert_eq!(stack.top_mut(), None);
+        assert_eq!(stack.push_char_to_top('a'), None);
+        assert_eq!(stack.truncate_top(0), None);
+        assert!(stack.is_empty());
+
+        stack.push("ab");
+        stack.push("");
+        assert_eq!(stack.top_mut().map(|s| &*s), Some(""));
+        stack.push_str_to_top("cd");
+        stack.push_char_to_top('€');
+        assert_eq!(stack.get_top(), Some("cd€"));
+        stack.top_mut().unwrap().make_ascii_uppercase();
+        assert_eq!(stack.iter().collect::<Vec<_>>(), ["ab", "CD€"]);
+
+        stack.truncate_top(10);
+        assert_eq!(stack.get_top(), Some("CD€"));
+        stack.truncate_top(1);
+        assert_eq!(stack.get_top(), Some("C"));
+        assert_eq!(stack.as_str(), "abC");
+        stack.truncate_top(0);
+        assert_eq!(stack.iter().collect::<Vec<_>>(), ["ab", ""]);
+        stack.push("d");
+        assert_eq!(stack.as_str(), "abd");
+    }
+
+    #[test]
+    #[should_panic(expected = "new top length is not on a char boundary")]
+    fn test_truncate_top_char_boundary() {
+        let mut stack = StrStack::new();
+        stack.push("€");
+        stack.truncate_top(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "StrStack offset overflow")]
+    fn test_push_str_to_top_offset_overflow() {
+        let mut stack = StrStack::<u16>::default();
+        stack.push("a");
+        stack.push_str_to_top(&"x".repeat(u16::MAX as usize));
+    }
+
+    fn stack_of(entries: &[&str]) -> StrStack {
+        let mut stack = StrStack::new();
+        for s in entries {
+            stack.push(s);
+        }
+        stack
+    }
+
+    #[test]
+    fn test_sort() {
+        let mut stack = stack_of(&["b", "", "ä", "ab", "a"]);
+        assert!(!stack.is_sorted());
+        stack.sort();
+        assert!(stack.is_sorted());
+        assert_eq!(stack.iter().collect::<Vec<_>>(), ["", "a", "ab", "b", "ä"]);
+        assert_eq!(stack.as_str(), "aabbä");
+
+        stack.sort_unstable_by(|a, b| b.cmp(a));
+        assert_eq!(stack.iter().collect::<Vec<_>>(), ["ä", "b", "ab", "a", ""]);
+        stack.sort_unstable();
+        assert_eq!(stack.iter().collect::<Vec<_>>(), ["", "a", "ab", "b", "ä"]);
+
+        // Stable: "b" stays before "a" among the entries of length 1.
+        let mut stack = stack_of(&["cc", "b", "aaa", "a"]);
+        stack.sort_by_key(str::len);
+        assert_eq!(stack.iter().collect::<Vec<_>>(), ["b", "a", "cc", "aaa"]);
+        stack.sort_unstable_by_key(|s| core::cmp::Reverse(s.len()));
+        assert_eq!(stack.get(0), Some("aaa"));
+
+        let mut empty = StrStack::new();
+        empty.sort();
+        assert!(empty.is_sorted());
+    }
+
+    #[test]
+    fn test_sort_by_panic() {
+        let mut stack = stack_of(&["b", "a", "c"]);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            stack.sort_by(|_, _| panic!("compare"));
+        }));
+        assert!(result.is_err());
+        assert_eq!(stack.iter().collect::<Vec<_>>(), ["b", "a", "c"]);
+    }
+
+    #[test]
+    fn test_dedup() {
+        let mut stack = stack_of(&["a", "a", "", "", "b", "a", "a"]);
+        stack.dedup();
+        assert_eq!(stack.iter().collect::<Vec<_>>(), ["a", "", "b", "a"]);
+        assert_eq!(stack.as_str(), "aba");
+
+        let mut stack = stack_of(&["x"]);
+        stack.dedup();
+        assert_eq!(stack.len(), 1);
+    }
+
+    #[test]
+    fn test_binary_search() {
+        let stack = stack_of(&["a", "c", "c", "e"]);
+        assert_eq!(stack.binary_search("a"), Ok(0));
+        assert!(matches!(stack.binary_search("c"), Ok(1 | 2)));
+        assert_eq!(stack.binary_search("e"), Ok(3));
+        assert_eq!(stack.binary_search(""), Err(0));
+        assert_eq!(stack.binary_search("d"), Err(3));
+        assert_eq!(stack.binary_search("f"), Err(4));
+        assert_eq!(stack.partition_point(|s| s < "c"), 1);
+        assert_eq!(StrStack::new().binary_search("a"), Err(0));
+    }
+
+    #[test]
+    fn test_prefix_range() {
+        let stack = stack_of(&["", "a", "ab", "abc", "abd", "b", "ba"]);
+        assert_eq!(stack.prefix_range(""), 0..7);
+        assert_eq!(stack.prefix_range("a"), 1..5);
+        assert_eq!(stack.prefix_range("ab"), 2..5);
+        assert_eq!(stack.prefix_range("abc"), 3..4);
+        assert_eq!(stack.prefix_range("abca"), 4..4);
+        assert_eq!(stack.prefix_range("b"), 5..7);
+        assert_eq!(stack.prefix_range("c"), 7..7);
+    }
+
+    #[test]
+    fn test_entry_at_byte() {
+        let stack = stack_of(&["", "ab", "", "", "c", "dé"]);
+        let expected = [Some(1), Some(1), Some(4), Some(5), Some(5), Some(5), None];
+        for (offset, index) in expected.iter().enumerate() {
+            assert_eq!(stack.entry_at_byte(offset), *index, "offset {}", offset);
+        }
+        assert_eq!(StrStack::new().entry_at_byte(0), None);
+    }
+
+    #[test]
+    fn test_locate() {
+        let stack = stack_of(&["ab", "", "cd", "e"]);
+        assert_eq!(stack.locate(0..5), Some((0, 3)));
+        assert_eq!(stack.locate(1..3), Some((0, 2)));
+        assert_eq!(stack.locate(2..4), Some((2, 2)));
+        assert_eq!(stack.locate(2..2), Some((2, 2)));
+        assert_eq!(stack.locate(4..6), None);
+        assert_eq!(stack.locate(5..5), None);
+    }
+
+    #[test]
+    #[allow(clippy::forget_non_drop)]
+    fn test_views_after_leaked_builder() {
+        let mut stack = stack_of(&["a", "b", "c"]);
+        let mut entry = stack.begin();
+        entry.push_str("leaked");
+        core::mem::forget(entry);
+
+        let (data, ends) = stack.as_raw_parts();
+        assert_eq!(data, b"abc");
+        assert!(StrStackRef::new(data, ends).is_ok());
+        assert_eq!(stack.binary_search("b"), Ok(1));
+        assert_eq!(stack.range(1..).collect::<Vec<_>>(), ["b", "c"]);
+        assert_eq!(stack.entry_at_byte(2), Some(2));
+        assert_eq!(stack.find_entries("c").count(), 1);
+    }
+
+    #[test]
+    #[allow(clippy::forget_non_drop)]
+    fn test_top_edits_after_leaked_builder() {
+        let leak = |stack: &mut StrStack| {
+            let mut entry = stack.begin();
+            entry.push_str("é");
+            core::mem::forget(entry);
+        };
+
+        let mut stack = stack_of(&["ab"]);
+        leak(&mut stack);
+        stack.truncate_top(1);
+        assert_eq!(stack.get_top(), Some("a"));
+        assert_eq!(stack.as_raw_parts().0, b"a");
+
+        let mut stack = stack_of(&["ab"]);
+        leak(&mut stack);
+        assert_eq!(stack.top_mut().map(|top| &*top), Some("ab"));
+
+        let mut stack = stack_of(&["abc", "de", "f"]);
+        leak(&mut stack);
+        assert_eq!(stack.swap_remove::<String>(0).as_deref(), Some("abc"));
+        assert_eq!(entries(&stack), ["f", "de"]);
+        assert_eq!(stack.as_str(), "fde");
+    }
+
+    fn entries<I: Offset>(stack: &StrStack<I>) -> Vec<&str> {
+        stack.iter().collect()
+    }
+
+    #[test]
+    fn test_from_split() {
+        let stack = StrStack::from_split("a,b,,c,", ',');
+        assert_eq!(entries(&stack), ["a", "b", "", "c", ""]);
+        assert_eq!(stack.data_capacity(), 7);
+
+        let stack = StrStack::from_split("a::bé::", "::");
+        assert_eq!(entries(&stack), ["a", "bé", ""]);
+        assert_eq!(
+            entries(&StrStack::from_split("a-b_c", ['-', '_'])),
+            ["a", "b", "c"]
+        );
+        assert_eq!(
+            entries(&StrStack::from_split("a-b", &['-'][..])),
+            ["a", "b"]
+        );
+        assert_eq!(entries(&StrStack::from_split("", ',')), [""]);
+    }
+
+    #[test]
+    fn test_from_lines() {
+        let stack = StrStack::from_lines("foo\r\nbar\n\nbaz\n");
+        assert_eq!(entries(&stack), ["foo", "bar", "", "baz"]);
+        assert!(StrStack::from_lines("").is_empty());
+    }
+
+    #[test]
+    fn test_from_split_whitespace() {
+        let stack = StrStack::from_split_whitespace("  foo\tbar \n baz ");
+        assert_eq!(entries(&stack), ["foo", "bar", "baz"]);
+        assert!(StrStack::from_split_whitespace(" \t ").is_empty());
+    }
+
+    #[test]
+    fn test_from_iter_and_extend() {
+        let mut stack: StrStack<u32> = "a b".split(' ').collect();
+        stack.extend(["", "c"]);
+        stack.extend(vec![String::from("dé")]);
+        assert_eq!(entries(&stack), ["a", "b", "", "c", "dé"]);
+
+        let stack: StrStack = vec![String::from("x"), String::from("yz")]
+            .into_iter()
+            .collect();
+        assert_eq!(entries(&stack), ["x", "yz"]);
+    }
+
+    #[test]
+    fn test_from_slice() {
+        let stack = StrStack::<u16>::from(&["ab", "", "c"][..]);
+        assert_eq!(entries(&stack), ["ab", "", "c"]);
+        assert_eq!(stack.entries_capacity(), 3);
+        assert_eq!(stack.data_capacity(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "StrStack offset overflow")]
+    fn test_extend_overflow() {
+        let long = "a".repeat(usize::from(u16::MAX));
+        let mut stack = StrStack::<u16>::default();
+        stack.extend([long.as_str(), "b"]);
+    }
 }
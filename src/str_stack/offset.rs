@@ -0,0 +1,64 @@
+use core::fmt;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// The integer type of the entry boundaries of a [`StrStack`](crate::StrStack): `u16`, `u32` or `usize`.
+///
+/// A narrower type saves index memory (2 or 4 bytes per entry instead of 8 on 64-bit targets), at the cost of
+/// limiting the total data length to `u16::MAX` or `u32::MAX` bytes.
+pub trait Offset: Copy + Ord + fmt::Debug + sealed::Sealed {
+    /// The largest data length this type can represent.
+    const MAX: usize;
+
+    /// Converts a data length, returning `None` if it exceeds [`MAX`](Self::MAX).
+    fn from_usize(n: usize) -> Option<Self>;
+
+    /// Converts back to a data length.
+    fn to_usize(self) -> usize;
+}
+
+macro_rules! impl_offset {
+    ($($t:ty),*) => {
+        $(
+            impl sealed::Sealed for $t {}
+
+            impl Offset for $t {
+                const MAX: usize = if (<$t>::MAX as u128) < (usize::MAX as u128) {
+                    <$t>::MAX as usize
+                } else {
+                    usize::MAX
+                };
+
+                #[inline(always)]
+                fn from_usize(n: usize) -> Option<Self> {
+                    <$t>::try_from(n).ok()
+                }
+
+                #[inline(always)]
+                fn to_usize(self) -> usize {
+                    // Lossless: only lengths of the data (a `usize`) are ever stored.
+                    self as usize
+                }
+            }
+        )*
+    };
+}
+
+impl_offset!(u16, u32, usize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_usize() {
+        assert_eq!(u16::from_usize(65535), Some(65535));
+        assert_eq!(u16::from_usize(65536), None);
+        assert_eq!(<u16 as Offset>::MAX, 65535);
+        assert_eq!(u32::from_usize(7).map(Offset::to_usize), Some(7));
+        assert_eq!(<usize as Offset>::MAX, usize::MAX);
+        assert_eq!(usize::from_usize(usize::MAX), Some(usize::MAX));
+    }
+}
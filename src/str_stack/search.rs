@@ -0,0 +1,104 @@
+use core::iter::FusedIterator;
+
+use crate::str_stack::Offset;
+use crate::str_stack::StrStackRef;
+
+/// An iterator over the entries of a [`StrStack`](crate::StrStack) that contain a pattern, created by
+/// [`StrStack::find_entries`](crate::StrStack::find_entries).
+///
+/// It yields the index and the contents of each matching entry once, searching the concatenated entries.
+pub struct FindEntries<'a, 'p, I: Offset = usize> {
+    stack: StrStackRef<'a, I>,
+    pattern: &'p str,
+    /// The byte offset to search from; always the start of an entry.
+    pos: usize,
+    /// The index of the entry starting at `pos`.
+    index: usize,
+}
+
+impl<'a, 'p, I: Offset> FindEntries<'a, 'p, I> {
+    #[inline]
+    pub(super) fn new(stack: StrStackRef<'a, I>, pattern: &'p str) -> Self {
+        Self {
+            stack,
+            pattern,
+            pos: 0,
+            index: 0,
+        }
+    }
+}
+
+impl<'a, I: Offset> Iterator for FindEntries<'a, '_, I> {
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let data = self.stack.as_str();
+        while self.index < self.stack.len() {
+            // The empty pattern matches every entry, including the empty ones.
+            let (index, found) = match self.pattern.is_empty() {
+                true => (self.index, self.pos),
+                false => {
+                    let found = self.pos + data[self.pos..].find(self.pattern)?;
+                    (self.stack.entry_at_byte(found)?, found)
+                }
+            };
+            let (begin, end) = self.stack.get_bounds(index)?;
+            // Continue from the next entry: each entry is yielded once. If this is the first match in the entry but
+            // it spans into the next one, no later match fits in the entry either.
+            self.pos = end;
+            self.index = index + 1;
+            if found + self.pattern.len() <= end {
+                return Some((index, &data[begin..end]));
+            }
+        }
+        None
+    }
+}
+
+impl<I: Offset> FusedIterator for FindEntries<'_, '_, I> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::StrStack;
+
+    fn find(entries: &[&str], pattern: &str) -> Vec<(usize, String)> {
+        let mut stack = StrStack::new();
+        for s in entries {
+            stack.push(s);
+        }
+        let found = stack
+            .find_entries(pattern)
+            .map(|(i, s)| (i, s.to_owned()))
+            .collect();
+        found
+    }
+
+    #[test]
+    fn test_find_entries() {
+        let entries = ["foo", "bar", "foobar", "", "barfoo"];
+        assert_eq!(
+            find(&entries, "foo"),
+            [
+                (0, "foo".to_owned()),
+                (2, "foobar".to_owned()),
+                (4, "barfoo".to_owned())
+            ]
+        );
+        assert_eq!(find(&entries, "ob"), [(2, "foobar".to_owned())]);
+        assert_eq!(find(&entries, "baz"), []);
+        assert_eq!(find(&entries, "").len(), 5);
+        assert_eq!(find(&[], "a"), []);
+    }
+
+    #[test]
+    fn test_spanning_matches() {
+        // "aa" first matches across the boundary of the first two entries.
+        assert_eq!(find(&["a", "aa"], "aa"), [(1, "aa".to_owned())]);
+        assert_eq!(find(&["xa", "b", "ab"], "ab"), [(2, "ab".to_owned())]);
+        assert_eq!(find(&["ab", "c"], "bc"), []);
+        assert_eq!(
+            find(&["é", "é"], "é"),
+            [(0, "é".to_owned()), (1, "é".to_owned())]
+        );
+    }
+}
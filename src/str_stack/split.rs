@@ -0,0 +1,38 @@
+use crate::allocator::Allocator;
+use crate::str_stack::Offset;
+use crate::StrStack;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A separator for [`StrStack::from_split`]: a `char`, a `&str`, or a slice or array of `char`s, matching like
+/// the same pattern does in [`str::split`].
+///
+/// `str::split` takes any `Pattern`, but that trait is unstable, so this one covers the common cases.
+pub trait SplitPattern: sealed::Sealed {
+    #[doc(hidden)]
+    fn split_into<I: Offset, A: Allocator>(self, input: &str, stack: &mut StrStack<I, A>);
+}
+
+macro_rules! impl_split_pattern {
+    ($(impl$([$($gen:tt)*])? for $t:ty;)*) => {
+        $(
+            impl$(<$($gen)*>)? sealed::Sealed for $t {}
+
+            impl$(<$($gen)*>)? SplitPattern for $t {
+                #[inline]
+                fn split_into<I: Offset, A: Allocator>(self, input: &str, stack: &mut StrStack<I, A>) {
+                    stack.extend(input.split(self));
+                }
+            }
+        )*
+    };
+}
+
+impl_split_pattern! {
+    impl for char;
+    impl['p] for &'p str;
+    impl['p] for &'p [char];
+    impl[const N: usize] for [char; N];
+}
@@ -0,0 +1,292 @@
+use core::fmt;
+use core::ops;
+use core::str::from_utf8;
+use core::str::from_utf8_unchecked;
+
+use crate::allocator::from_vec;
+use crate::str_stack::to_range;
+use crate::str_stack::Offset;
+use crate::str_stack::RawPartsError;
+use crate::str_stack::StrStackIter;
+use crate::StrStack;
+
+/// A borrowed, read-only [`StrStack`]: entry data and entry ends in external slices, e.g. in a memory-mapped file.
+///
+/// The raw parts have the same layout as in a `StrStack` (see [`StrStack::as_raw_parts`]): `data` holds the
+/// entries back to back, and `ends[i]` is the end of entry `i` in `data`.
+///
+/// ```
+/// use smart_string::str_stack::StrStackRef;
+/// use smart_string::StrStack;
+///
+/// let mut stack = StrStack::<u32>::default();
+/// stack.push("foo");
+/// stack.push("bar");
+/// let (data, ends) = stack.as_raw_parts();
+/// assert_eq!((data, ends), (&b"foobar"[..], &[3, 6][..]));
+///
+/// let view = StrStackRef::new(data, ends).unwrap();
+/// assert_eq!(view.get(1), Some("bar"));
+/// assert_eq!(view.iter().collect::<Vec<_>>(), ["foo", "bar"]);
+/// assert!(StrStackRef::new(data, &[3u32, 2]).is_err());
+/// ```
+#[derive(Clone, Copy)]
+pub struct StrStackRef<'a, I: Offset = usize> {
+    data: &'a str,
+    ends: &'a [I],
+}
+
+impl<'a, I: Offset> StrStackRef<'a, I> {
+    /// Creates a view over raw parts, validating them once: `data` must be UTF-8, and `ends` must be
+    /// non-decreasing char boundaries, the last one being `data.len()`.
+    pub fn new(data: &'a [u8], ends: &'a [I]) -> Result<Self, RawPartsError> {
+        let data = from_utf8(data).map_err(RawPartsError::InvalidUtf8)?;
+        let mut prev = 0;
+        for (index, end) in ends.iter().enumerate() {
+            let end = end.to_usize();
+            if end < prev {
+                return Err(RawPartsError::UnorderedEnds { index });
+            }
+            if !data.is_char_boundary(end) {
+                return Err(RawPartsError::NotCharBoundary { index });
+            }
+            prev = end;
+        }
+        if prev != data.len() {
+            return Err(RawPartsError::LengthMismatch {
+                data_len: data.len(),
+                end: prev,
+            });
+        }
+        Ok(Self { data, ends })
+    }
+
+    /// Creates a view over raw parts without validation.
+    ///
+    /// # Safety
+    ///
+    /// The parts must pass the checks of [`new`](Self::new): `data` is valid UTF-8, and `ends` are non-decreasing
+    /// char boundaries within `data`, the last one being `data.len()`.
+    #[inline]
+    pub unsafe fn new_unchecked(data: &'a [u8], ends: &'a [I]) -> Self {
+        // Only the O(1) checks: `StrStack` builds a view on every search, and `new` would make those O(n).
+        debug_assert_eq!(ends.last().map_or(0, |end| end.to_usize()), data.len());
+        Self {
+            data: from_utf8_unchecked(data),
+            ends,
+        }
+    }
+
+    /// Returns the raw parts, as accepted by [`new`](Self::new).
+    #[inline]
+    pub fn as_raw_parts(&self) -> (&'a [u8], &'a [I]) {
+        (self.data.as_bytes(), self.ends)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    /// Returns the total length of the entries in bytes.
+    #[inline]
+    pub fn bytes_len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns all entries concatenated.
+    #[inline]
+    pub fn as_str(&self) -> &'a str {
+        self.data
+    }
+
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&'a str> {
+        let (begin, end) = self.get_bounds(index)?;
+        // SAFETY: the ends are non-decreasing char boundaries within `data` (checked on construction).
+        Some(unsafe { self.data.get_unchecked(begin..end) })
+    }
+
+    #[inline]
+    pub fn get_bounds(&self, index: usize) -> Option<(usize, usize)> {
+        let end = self.ends.get(index)?.to_usize();
+        let begin = match index {
+            0 => 0,
+            _ => self.ends[index - 1].to_usize(),
+        };
+        Some((begin, end))
+    }
+
+    /// Returns the index of the entry containing the byte at `offset` of [`as_str`](Self::as_str); see
+    /// [`StrStack::entry_at_byte`].
+    #[inline]
+    pub fn entry_at_byte(&self, offset: usize) -> Option<usize> {
+        let index = self.ends.partition_point(|end| end.to_usize() <= offset);
+        match index < self.len() {
+            true => Some(index),
+            false => None,
+        }
+    }
+
+    /// Returns the indices of the first and the last entries overlapping a byte range of
+    /// [`as_str`](Self::as_str); see [`StrStack::locate`].
+    #[inline]
+    pub fn locate(&self, range: ops::Range<usize>) -> Option<(usize, usize)> {
+        let first = self.entry_at_byte(range.start)?;
+        match range.end > range.start {
+            true => Some((first, self.entry_at_byte(range.end - 1)?)),
+            false => Some((first, first)),
+        }
+    }
+
+    #[inline]
+    pub fn get_top(&self) -> Option<&'a str> {
+        self.get(self.len().checked_sub(1)?)
+    }
+
+    #[inline]
+    pub fn iter(&self) -> StrStackIter<'a, I> {
+        StrStackIter::from_parts(self.data, self.ends)
+    }
+
+    /// Returns an iterator over the entries in `range`. Panics if the range is out of bounds (matches slice
+    /// semantics).
+    #[inline]
+    pub fn range<R>(&self, range: R) -> StrStackIter<'a, I>
+    where
+        R: ops::RangeBounds<usize>,
+    {
+        let range = to_range(range, self.len());
+        let _ = &self.ends[range.clone()];
+        StrStackIter::from_parts_range(self.data, self.ends, range)
+    }
+
+    /// Copies the entries into an owned stack.
+    pub fn to_str_stack(&self) -> StrStack<I> {
+        StrStack::from_bufs(
+            from_vec(self.data.as_bytes().to_vec()),
+            from_vec(self.ends.to_vec()),
+        )
+    }
+}
+
+impl<I: Offset> fmt::Debug for StrStackRef<'_, I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<I: Offset> PartialEq for StrStackRef<'_, I> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data && self.ends == other.ends
+    }
+}
+
+impl<I: Offset> Eq for StrStackRef<'_, I> {}
+
+impl<'a, I: Offset> IntoIterator for StrStackRef<'a, I> {
+    type Item = &'a str;
+    type IntoIter = StrStackIter<'a, I>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, I: Offset> IntoIterator for &StrStackRef<'a, I> {
+    type Item = &'a str;
+    type IntoIter = StrStackIter<'a, I>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        let view = StrStackRef::<usize>::new(b"abcd", &[1, 1, 4]).unwrap();
+        assert_eq!(view.len(), 3);
+        assert!(!view.is_empty());
+        assert_eq!(view.bytes_len(), 4);
+        assert_eq!(view.as_str(), "abcd");
+        assert_eq!(view.get(0), Some("a"));
+        assert_eq!(view.get(1), Some(""));
+        assert_eq!(view.get(2), Some("bcd"));
+        assert_eq!(view.get(3), None);
+        assert_eq!(view.get_bounds(2), Some((1, 4)));
+        assert_eq!(view.get_top(), Some("bcd"));
+        assert_eq!(view.iter().len(), 3);
+        assert_eq!(view.range(1..=2).collect::<Vec<_>>(), ["", "bcd"]);
+        assert_eq!(view.range(..1).as_str(), "a");
+        assert_eq!(format!("{:?}", view), r#"["a", "", "bcd"]"#);
+
+        let empty = StrStackRef::<u16>::new(b"", &[]).unwrap();
+        assert!(empty.is_empty());
+        assert_eq!(empty.get_top(), None);
+        assert_eq!(empty.into_iter().next(), None);
+        assert_eq!(empty.range(..).next(), None);
+    }
+
+    #[test]
+    fn test_new_invalid() {
+        assert!(matches!(
+            StrStackRef::<u32>::new(b"a\xff", &[2]),
+            Err(RawPartsError::InvalidUtf8(_))
+        ));
+        assert_eq!(
+            StrStackRef::<u32>::new(b"abc", &[2, 1, 3]),
+            Err(RawPartsError::UnorderedEnds { index: 1 })
+        );
+        assert_eq!(
+            StrStackRef::<u32>::new("aä".as_bytes(), &[2, 3]),
+            Err(RawPartsError::NotCharBoundary { index: 0 })
+        );
+        assert_eq!(
+            StrStackRef::<u32>::new(b"abc", &[1, 4]),
+            Err(RawPartsError::NotCharBoundary { index: 1 })
+        );
+        let err = StrStackRef::<u32>::new(b"abc", &[1, 2]).unwrap_err();
+        assert_eq!(
+            err,
+            RawPartsError::LengthMismatch {
+                data_len: 3,
+                end: 2
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "StrStack data length 3 doesn't match the last end 2"
+        );
+        assert!(StrStackRef::<u32>::new(b"a", &[]).is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut stack = StrStack::<u16>::default();
+        for s in ["foo", "", "bär"] {
+            stack.push(s);
+        }
+        let (data, ends) = stack.as_raw_parts();
+        let view = StrStackRef::new(data, ends).unwrap();
+        assert_eq!(view, stack.as_stack_ref());
+        assert_eq!(view.as_raw_parts(), (data, ends));
+        assert_eq!(view.iter().collect::<Vec<_>>(), ["foo", "", "bär"]);
+        assert!(view.to_str_stack() == stack);
+
+        // SAFETY: the parts come from a `StrStack`.
+        let unchecked = unsafe { StrStackRef::new_unchecked(data, ends) };
+        assert_eq!(unchecked.get(2), Some("bär"));
+    }
+}
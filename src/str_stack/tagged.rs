@@ -0,0 +1,379 @@
+use core::fmt;
+use core::iter::FusedIterator;
+use core::iter::Zip;
+use core::slice;
+
+use crate::allocator::Allocator;
+use crate::allocator::Global;
+use crate::allocator::VecIn;
+use crate::str_stack::Offset;
+use crate::str_stack::StrStackIter;
+use crate::str_stack::TryPushError;
+use crate::StrStack;
+
+/// A [`StrStack`] that stores a value (a tag, such as a token kind or a source offset) alongside each entry.
+///
+/// ```
+/// use smart_string::TaggedStrStack;
+///
+/// let mut tokens = TaggedStrStack::new();
+/// tokens.push("let", 'k');
+/// tokens.push("x", 'i');
+///
+/// assert_eq!(tokens.get(0), Some(("let", &'k')));
+/// *tokens.get_mut(1).unwrap().1 = 'v';
+/// assert_eq!(
+///     tokens.iter().collect::<Vec<_>>(),
+///     [("let", &'k'), ("x", &'v')]
+/// );
+/// assert_eq!(tokens.pop_owned::<String>(), Some(("x".to_string(), 'v')));
+/// ```
+///
+/// Like [`StrStack`], it allocates through `A`, the global allocator by default: both the entries and the tags.
+#[derive(Clone)]
+pub struct TaggedStrStack<T, I: Offset = usize, A: Allocator = Global> {
+    strings: StrStack<I, A>,
+    /// The tag of each entry of `strings`.
+    tags: VecIn<T, A>,
+}
+
+impl<T> TaggedStrStack<T> {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T, I: Offset> TaggedStrStack<T, I> {
+    /// Creates an empty stack with room for at least `entries` entries of `bytes` bytes in total.
+    #[inline]
+    pub fn with_capacity(entries: usize, bytes: usize) -> Self {
+        Self::with_capacity_in(entries, bytes, Global)
+    }
+}
+
+impl<T, I: Offset, A: Allocator + Clone> TaggedStrStack<T, I, A> {
+    /// Creates an empty stack that allocates through `alloc`.
+    #[inline]
+    pub fn new_in(alloc: A) -> Self {
+        Self {
+            strings: StrStack::new_in(alloc.clone()),
+            tags: VecIn::new_in(alloc),
+        }
+    }
+
+    /// Creates an empty stack with room for at least `entries` entries of `bytes` bytes in total, allocating
+    /// through `alloc`.
+    #[inline]
+    pub fn with_capacity_in(entries: usize, bytes: usize, alloc: A) -> Self {
+        Self {
+            strings: StrStack::with_capacity_in(entries, bytes, alloc.clone()),
+            tags: VecIn::with_capacity_in(entries, alloc),
+        }
+    }
+}
+
+impl<T, I: Offset, A: Allocator> TaggedStrStack<T, I, A> {
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.tags.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+
+    /// Returns the total length of the entries in bytes.
+    #[inline]
+    pub fn bytes_len(&self) -> usize {
+        self.strings.bytes_len()
+    }
+
+    /// Returns the entries without their tags.
+    #[inline]
+    pub fn as_str_stack(&self) -> &StrStack<I, A> {
+        &self.strings
+    }
+
+    #[inline]
+    pub fn tags(&self) -> &[T] {
+        &self.tags
+    }
+
+    #[inline]
+    pub fn tags_mut(&mut self) -> &mut [T] {
+        &mut self.tags
+    }
+
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<(&str, &T)> {
+        Some((self.strings.get(index)?, &self.tags[index]))
+    }
+
+    /// Returns an entry with a mutable reference to its tag.
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> Option<(&str, &mut T)> {
+        Some((self.strings.get(index)?, &mut self.tags[index]))
+    }
+
+    #[inline]
+    pub fn get_top(&self) -> Option<(&str, &T)> {
+        self.get(self.len().checked_sub(1)?)
+    }
+
+    /// Pushes a new entry, panicking if the total data length would overflow the offset type `I`.
+    #[inline]
+    pub fn push(&mut self, s: &str, tag: T) {
+        self.try_push(s, tag).expect("StrStack offset overflow");
+    }
+
+    /// Pushes a new entry, or returns an error if the total data length would overflow the offset type `I`.
+    ///
+    /// On error, the stack is left unchanged.
+    #[inline]
+    pub fn try_push(&mut self, s: &str, tag: T) -> Result<(), TryPushError> {
+        self.strings.try_push(s)?;
+        self.tags.push(tag);
+        Ok(())
+    }
+
+    /// Removes the top entry, returning its tag.
+    #[inline]
+    pub fn remove_top(&mut self) -> Option<T> {
+        self.strings.remove_top()?;
+        self.tags.pop()
+    }
+
+    /// Removes the top entry, returning it converted into `S` along with its tag.
+    #[inline]
+    pub fn pop_owned<S>(&mut self) -> Option<(S, T)>
+    where
+        S: for<'a> From<&'a str>,
+    {
+        let s = self.strings.get_top()?.into();
+        let tag = self.remove_top()?;
+        Some((s, tag))
+    }
+
+    /// Keeps the first `len` entries, dropping the others (and their tags); no-op if `len >= self.len()`.
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        self.strings.truncate(len);
+        self.tags.truncate(len);
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.strings.clear();
+        self.tags.clear();
+    }
+
+    #[inline]
+    pub fn iter(&self) -> TaggedIter<'_, T, I> {
+        TaggedIter {
+            inner: self.strings.iter().zip(self.tags.iter()),
+        }
+    }
+
+    /// Returns an iterator over the entries with mutable references to their tags.
+    #[inline]
+    pub fn iter_mut(&mut self) -> TaggedIterMut<'_, T, I> {
+        TaggedIterMut {
+            inner: self.strings.iter().zip(self.tags.iter_mut()),
+        }
+    }
+}
+
+// -- Common traits --------------------------------------------------------------------------------
+
+impl<T, I: Offset, A: Allocator + Clone + Default> Default for TaggedStrStack<T, I, A> {
+    #[inline]
+    fn default() -> Self {
+        Self::new_in(A::default())
+    }
+}
+
+impl<T: fmt::Debug, I: Offset, A: Allocator> fmt::Debug for TaggedStrStack<T, I, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq, I: Offset, A: Allocator> PartialEq for TaggedStrStack<T, I, A> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.strings == other.strings && self.tags[..] == other.tags[..]
+    }
+}
+
+impl<T: Eq, I: Offset, A: Allocator> Eq for TaggedStrStack<T, I, A> {}
+
+impl<'a, T, I: Offset, A: Allocator> Extend<(&'a str, T)> for TaggedStrStack<T, I, A> {
+    fn extend<It: IntoIterator<Item = (&'a str, T)>>(&mut self, iter: It) {
+        for (s, tag) in iter {
+            self.push(s, tag);
+        }
+    }
+}
+
+impl<'a, T, I: Offset, A: Allocator + Clone + Default> FromIterator<(&'a str, T)>
+    for TaggedStrStack<T, I, A>
+{
+    fn from_iter<It: IntoIterator<Item = (&'a str, T)>>(iter: It) -> Self {
+        let mut stack = Self::default();
+        stack.extend(iter);
+        stack
+    }
+}
+
+impl<'a, T, I: Offset, A: Allocator> IntoIterator for &'a TaggedStrStack<T, I, A> {
+    type Item = (&'a str, &'a T);
+    type IntoIter = TaggedIter<'a, T, I>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, I: Offset, A: Allocator> IntoIterator for &'a mut TaggedStrStack<T, I, A> {
+    type Item = (&'a str, &'a mut T);
+    type IntoIter = TaggedIterMut<'a, T, I>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+// -- Iterators ------------------------------------------------------------------------------------
+
+/// An iterator over the entries of a [`TaggedStrStack`] and their tags, created by [`TaggedStrStack::iter`].
+pub struct TaggedIter<'a, T, I: Offset = usize> {
+    inner: Zip<StrStackIter<'a, I>, slice::Iter<'a, T>>,
+}
+
+impl<'a, T, I: Offset> Iterator for TaggedIter<'a, T, I> {
+    type Item = (&'a str, &'a T);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T, I: Offset> DoubleEndedIterator for TaggedIter<'_, T, I> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<T, I: Offset> ExactSizeIterator for TaggedIter<'_, T, I> {}
+
+impl<T, I: Offset> FusedIterator for TaggedIter<'_, T, I> {}
+
+/// An iterator over the entries of a [`TaggedStrStack`] with mutable references to their tags, created by
+/// [`TaggedStrStack::iter_mut`].
+pub struct TaggedIterMut<'a, T, I: Offset = usize> {
+    inner: Zip<StrStackIter<'a, I>, slice::IterMut<'a, T>>,
+}
+
+impl<'a, T, I: Offset> Iterator for TaggedIterMut<'a, T, I> {
+    type Item = (&'a str, &'a mut T);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T, I: Offset> DoubleEndedIterator for TaggedIterMut<'_, T, I> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<T, I: Offset> ExactSizeIterator for TaggedIterMut<'_, T, I> {}
+
+impl<T, I: Offset> FusedIterator for TaggedIterMut<'_, T, I> {}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use super::*;
+
+    #[test]
+    fn test_push_get() {
+        let mut stack = TaggedStrStack::new();
+        assert!(stack.is_empty());
+        assert_eq!(stack.get_top(), None);
+
+        stack.push("a", 1);
+        stack.push("bc", 2);
+        assert_eq!(stack.len(), 2);
+        assert_eq!(stack.bytes_len(), 3);
+        assert_eq!(stack.get(0), Some(("a", &1)));
+        assert_eq!(stack.get(2), None);
+        assert_eq!(stack.get_top(), Some(("bc", &2)));
+        assert_eq!(stack.tags(), [1, 2]);
+        assert_eq!(stack.as_str_stack().as_str(), "abc");
+
+        *stack.get_mut(0).unwrap().1 += 10;
+        stack.tags_mut()[1] = 20;
+        assert_eq!(stack.iter().collect::<Vec<_>>(), [("a", &11), ("bc", &20)]);
+        assert_eq!(format!("{:?}", stack), r#"[("a", 11), ("bc", 20)]"#);
+    }
+
+    #[test]
+    fn test_pop_truncate() {
+        let mut stack: TaggedStrStack<_> =
+            [("a", 'x'), ("b", 'y'), ("c", 'z')].into_iter().collect();
+        assert_eq!(stack.pop_owned::<String>(), Some((String::from("c"), 'z')));
+        assert_eq!(stack.remove_top(), Some('y'));
+        stack.push("d", 'w');
+        stack.truncate(1);
+        assert_eq!(stack.iter().collect::<Vec<_>>(), [("a", &'x')]);
+        stack.clear();
+        assert_eq!(stack.remove_top(), None);
+        assert_eq!(stack.pop_owned::<String>(), None);
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut stack = TaggedStrStack::<u32, u16>::with_capacity(3, 3);
+        stack.extend([("a", 1), ("b", 2), ("c", 3)]);
+        for (s, tag) in &mut stack {
+            *tag += s.len() as u32;
+        }
+        let mut it = stack.iter();
+        assert_eq!(it.len(), 3);
+        assert_eq!(it.next_back(), Some(("c", &4)));
+        assert_eq!(it.next(), Some(("a", &2)));
+        assert_eq!((&stack).into_iter().next_back(), Some(("c", &4)));
+        assert_eq!(stack.clone(), stack);
+    }
+
+    #[test]
+    fn test_try_push_offset_overflow() {
+        let mut stack = TaggedStrStack::<(), u16>::default();
+        stack.push("a", ());
+        let long = "x".repeat(u16::MAX as usize);
+        assert_eq!(stack.try_push(&long, ()), Err(TryPushError::OffsetOverflow));
+        assert_eq!(stack.len(), 1);
+        assert_eq!(stack.tags().len(), 1);
+    }
+}
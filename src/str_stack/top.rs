@@ -0,0 +1,114 @@
+use core::fmt;
+
+use crate::allocator::Allocator;
+use crate::allocator::Global;
+use crate::str_stack::Offset;
+use crate::str_stack::TryPushError;
+use crate::StrStack;
+
+/// A writer that appends to the top entry of a [`StrStack`] in place.
+///
+/// This struct is created by [`StrStack::top_writer`]. Unlike [`EntryBuilder`](crate::str_stack::EntryBuilder),
+/// it extends an existing entry, and every write is committed at once.
+///
+/// ```
+/// use core::fmt::Write;
+///
+/// use smart_string::StrStack;
+///
+/// let mut stack = StrStack::new();
+/// stack.push("id-");
+///
+/// let mut top = stack.top_writer().unwrap();
+/// write!(top, "{:04}", 42).unwrap();
+/// top.push('!');
+///
+/// assert_eq!(stack.get_top(), Some("id-0042!"));
+/// ```
+pub struct TopWriter<'a, I: Offset = usize, A: Allocator = Global> {
+    /// A non-empty stack.
+    stack: &'a mut StrStack<I, A>,
+}
+
+impl<'a, I: Offset, A: Allocator> TopWriter<'a, I, A> {
+    #[inline]
+    pub(super) fn new(stack: &'a mut StrStack<I, A>) -> Option<Self> {
+        match stack.is_empty() {
+            true => None,
+            false => Some(Self { stack }),
+        }
+    }
+
+    /// Returns the top entry.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        self.stack.get_top().unwrap_or_default()
+    }
+
+    /// Appends to the top entry, panicking if the total data length would overflow the offset type `I`.
+    #[inline]
+    pub fn push_str(&mut self, s: &str) {
+        self.try_push_str(s).expect("StrStack offset overflow");
+    }
+
+    /// Appends to the top entry, or returns an error if the total data length would overflow the offset type `I`.
+    #[inline]
+    pub fn try_push_str(&mut self, s: &str) -> Result<(), TryPushError> {
+        self.stack.try_extend_top(s)
+    }
+
+    #[inline]
+    pub fn push(&mut self, ch: char) {
+        self.push_str(ch.encode_utf8(&mut [0; 4]));
+    }
+}
+
+impl<I: Offset, A: Allocator> fmt::Write for TopWriter<'_, I, A> {
+    /// Fails, without appending anything, if the total data length would overflow the offset type `I`.
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.try_push_str(s).map_err(|_| fmt::Error)
+    }
+}
+
+impl<I: Offset, A: Allocator> fmt::Debug for TopWriter<'_, I, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TopWriter").field(&self.as_str()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::fmt::Write;
+
+    use super::*;
+
+    #[test]
+    fn test_writer() {
+        let mut stack = StrStack::new();
+        assert!(stack.top_writer().is_none());
+
+        stack.push("a");
+        stack.push("b");
+        let mut top = stack.top_writer().unwrap();
+        top.push_str("c");
+        write!(top, "{}", 1).unwrap();
+        top.push('ä');
+        assert_eq!(format!("{:?}", top), r#"TopWriter("bc1ä")"#);
+
+        assert_eq!(stack.iter().collect::<Vec<_>>(), ["a", "bc1ä"]);
+        stack.push("d");
+        assert_eq!(stack.get(1), Some("bc1ä"));
+    }
+
+    #[test]
+    fn test_writer_offset_overflow() {
+        let mut stack = StrStack::<u16>::default();
+        stack.push("a");
+        let mut top = stack.top_writer().unwrap();
+        let long = "x".repeat(u16::MAX as usize);
+        assert_eq!(top.try_push_str(&long), Err(TryPushError::OffsetOverflow));
+        assert_eq!(top.write_str(&long), Err(fmt::Error));
+        assert_eq!(stack.as_str(), "a");
+    }
+}
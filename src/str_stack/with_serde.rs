@@ -1,5 +1,7 @@
 use core::fmt;
+use core::marker::PhantomData;
 
+use serde::de::Error;
 use serde::de::SeqAccess;
 use serde::de::Visitor;
 use serde::ser::SerializeSeq;
@@ -8,50 +10,80 @@ use serde::Deserializer;
 use serde::Serialize;
 use serde::Serializer;
 
+use crate::allocator::Allocator;
+use crate::str_stack::Offset;
+use crate::SmartString;
 use crate::StrStack;
+use crate::TaggedStrStack;
 
-struct SeqVisitor;
-struct SeqInPlaceVisitor<'a>(&'a mut StrStack);
+struct SeqVisitor<I, A>(PhantomData<(I, A)>);
+struct SeqInPlaceVisitor<'a, I: Offset, A: Allocator>(&'a mut StrStack<I, A>);
+struct TaggedSeqVisitor<T, I, A>(PhantomData<(T, I, A)>);
 
-impl<'de> Visitor<'de> for SeqVisitor {
-    type Value = StrStack;
+impl<'de, I: Offset, A: Allocator + Clone + Default> Visitor<'de> for SeqVisitor<I, A> {
+    type Value = StrStack<I, A>;
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         formatter.write_str("a string sequence")
     }
 
-    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    fn visit_seq<S>(self, mut seq: S) -> Result<Self::Value, S::Error>
     where
-        A: SeqAccess<'de>,
+        S: SeqAccess<'de>,
     {
-        let mut stack = StrStack::new();
+        let mut stack = StrStack::new_in(A::default());
         while let Some(s) = seq.next_element()? {
-            stack.push(s);
+            stack.try_push(s).map_err(S::Error::custom)?;
         }
         Ok(stack)
     }
 }
 
-impl<'a, 'de> Visitor<'de> for SeqInPlaceVisitor<'a> {
+impl<'a, 'de, I: Offset, A: Allocator> Visitor<'de> for SeqInPlaceVisitor<'a, I, A> {
     type Value = ();
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         formatter.write_str("a string sequence")
     }
 
-    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    fn visit_seq<S>(self, mut seq: S) -> Result<Self::Value, S::Error>
     where
-        A: SeqAccess<'de>,
+        S: SeqAccess<'de>,
     {
         self.0.clear();
         while let Some(s) = seq.next_element()? {
-            self.0.push(s);
+            self.0.try_push(s).map_err(S::Error::custom)?;
         }
         Ok(())
     }
 }
 
-impl Serialize for StrStack {
+impl<'de, T, I, A> Visitor<'de> for TaggedSeqVisitor<T, I, A>
+where
+    T: Deserialize<'de>,
+    I: Offset,
+    A: Allocator + Clone + Default,
+{
+    type Value = TaggedStrStack<T, I, A>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a sequence of (string, tag) pairs")
+    }
+
+    fn visit_seq<S>(self, mut seq: S) -> Result<Self::Value, S::Error>
+    where
+        S: SeqAccess<'de>,
+    {
+        let mut stack = TaggedStrStack::default();
+        // Not `&str`: the string can't be borrowed from the input if it has escapes, or if the input is a reader.
+        while let Some((s, tag)) = seq.next_element::<(SmartString, T)>()? {
+            stack.try_push(&s, tag).map_err(S::Error::custom)?;
+        }
+        Ok(stack)
+    }
+}
+
+impl<I: Offset, A: Allocator> Serialize for StrStack<I, A> {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         let mut seq = serializer.serialize_seq(Some(self.len()))?;
         for s in self.iter() {
@@ -61,12 +93,12 @@ impl Serialize for StrStack {
     }
 }
 
-impl<'de> Deserialize<'de> for StrStack {
+impl<'de, I: Offset, A: Allocator + Clone + Default> Deserialize<'de> for StrStack<I, A> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        deserializer.deserialize_seq(SeqVisitor)
+        deserializer.deserialize_seq(SeqVisitor(PhantomData))
     }
 
     fn deserialize_in_place<D>(deserializer: D, place: &mut Self) -> Result<(), D::Error>
@@ -77,6 +109,30 @@ impl<'de> Deserialize<'de> for StrStack {
     }
 }
 
+impl<T: Serialize, I: Offset, A: Allocator> Serialize for TaggedStrStack<T, I, A> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for entry in self.iter() {
+            seq.serialize_element(&entry)?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de, T, I, A> Deserialize<'de> for TaggedStrStack<T, I, A>
+where
+    T: Deserialize<'de>,
+    I: Offset,
+    A: Allocator + Clone + Default,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(TaggedSeqVisitor(PhantomData))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -105,4 +161,46 @@ mod tests {
         assert_eq!(it.next(), Some("789"));
         assert_eq!(it.next(), None);
     }
+
+    #[test]
+    fn test_deserialize_offset_overflow() {
+        let json = format!(r#"["{}","b"]"#, "a".repeat(u16::MAX as usize));
+        let err = serde_json::from_str::<StrStack<u16>>(&json)
+            .map(drop)
+            .unwrap_err();
+        assert!(err.to_string().starts_with("StrStack offset overflow"));
+
+        let stack: StrStack<u16> = serde_json::from_str(r#"["123","456"]"#).unwrap();
+        assert_eq!(serde_json::to_string(&stack).unwrap(), r#"["123","456"]"#);
+    }
+
+    #[test]
+    fn test_tagged() {
+        let mut stack = TaggedStrStack::new();
+        stack.push("let", 1);
+        stack.push("x", 2);
+
+        let json = serde_json::to_string(&stack).unwrap();
+        assert_eq!(json, r#"[["let",1],["x",2]]"#);
+
+        let stack: TaggedStrStack<u8> = serde_json::from_str(&json).unwrap();
+        assert_eq!(stack.iter().collect::<Vec<_>>(), [("let", &1), ("x", &2)]);
+
+        let json = format!(r#"[["{}",1],["b",2]]"#, "a".repeat(u16::MAX as usize));
+        let err = serde_json::from_str::<TaggedStrStack<u8, u16>>(&json).unwrap_err();
+        assert!(err.to_string().starts_with("StrStack offset overflow"));
+    }
+
+    #[test]
+    fn test_tagged_unborrowed() {
+        let json = r#"[["a\"b",1],["a string too long to fit a SmartString",2]]"#;
+        let stack: TaggedStrStack<u8> = serde_json::from_str(json).unwrap();
+        assert_eq!(stack.get(0), Some(("a\"b", &1)));
+
+        let stack: TaggedStrStack<u8> = serde_json::from_reader(json.as_bytes()).unwrap();
+        assert_eq!(
+            stack.get_top(),
+            Some(("a string too long to fit a SmartString", &2))
+        );
+    }
 }